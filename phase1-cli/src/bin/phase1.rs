@@ -57,6 +57,7 @@ fn execute_cmd<E: Engine>(opts: Phase1Opts) {
                     opts.force_correctness_checks,
                 ),
                 opts.batch_exp_mode,
+                opts.pipelined,
                 &parameters,
                 rng,
             );
@@ -76,6 +77,7 @@ fn execute_cmd<E: Engine>(opts: Phase1Opts) {
                     opts.force_correctness_checks,
                 ),
                 opts.batch_exp_mode,
+                opts.pipelined,
                 &parameters,
                 rng,
             );
@@ -36,6 +36,14 @@ struct PreparePhase2Opts {
     pub batch_size: usize,
     #[options(help = "the number of powers used for phase 1 (circuit size will be 2^{power})")]
     pub power: usize,
+    #[options(
+        help = "if given, the memory in MiB used to transform the response out of core, instead of loading it entirely"
+    )]
+    pub memory_budget: usize,
+    #[options(
+        help = "if given, the size of the phase 2 domain, which may be smaller than 2^{power}. A smaller mixed-radix domain is selected automatically if the curve supports one, in which case proofs need a prover which supports such domains"
+    )]
+    pub phase2_size: usize,
 }
 
 fn main() -> Result<()> {
@@ -46,15 +54,41 @@ fn main() -> Result<()> {
 
     let opts = PreparePhase2Opts::parse_args_default_or_exit();
 
+    let memory_budget = if opts.memory_budget > 0 {
+        Some(opts.memory_budget << 20)
+    } else {
+        None
+    };
+
+    let phase2_size = if opts.phase2_size > 0 {
+        Some(opts.phase2_size)
+    } else {
+        None
+    };
+
     let now = Instant::now();
     match opts.curve_kind {
         CurveKind::Bls12_377 => {
             let parameters = Phase1Parameters::<Bls12_377>::new_full(opts.proving_system, opts.power, opts.batch_size);
-            prepare_phase2::<Bls12_377>(&opts.phase2_fname, &opts.response_fname, opts.power, &parameters)?
+            prepare_phase2::<Bls12_377>(
+                &opts.phase2_fname,
+                &opts.response_fname,
+                opts.power,
+                phase2_size,
+                &parameters,
+                memory_budget,
+            )?
         }
         CurveKind::BW6 => {
             let parameters = Phase1Parameters::<BW6_761>::new_full(opts.proving_system, opts.power, opts.batch_size);
-            prepare_phase2::<BW6_761>(&opts.phase2_fname, &opts.response_fname, opts.power, &parameters)?
+            prepare_phase2::<BW6_761>(
+                &opts.phase2_fname,
+                &opts.response_fname,
+                opts.power,
+                phase2_size,
+                &parameters,
+                memory_budget,
+            )?
         }
     }
 
@@ -14,6 +14,7 @@ use tracing::info;
 const COMPRESSED_INPUT: UseCompression = UseCompression::No;
 const COMPRESSED_OUTPUT: UseCompression = UseCompression::Yes;
 
+#[allow(clippy::too_many_arguments)]
 pub fn contribute<T: Engine + Sync>(
     challenge_filename: &str,
     challenge_hash_filename: &str,
@@ -21,6 +22,7 @@ pub fn contribute<T: Engine + Sync>(
     response_hash_filename: &str,
     check_input_correctness: CheckForCorrectness,
     batch_exp_mode: BatchExpMode,
+    pipelined: bool,
     parameters: &Phase1Parameters<T>,
     mut rng: impl Rng,
 ) {
@@ -120,7 +122,12 @@ pub fn contribute<T: Engine + Sync>(
     info!("Computing and writing your contribution, this could take a while...");
 
     // this computes a transformation and writes it
-    Phase1::computation(
+    let computation = if pipelined {
+        Phase1::computation_pipelined
+    } else {
+        Phase1::computation
+    };
+    computation(
         &readable_map,
         &mut writable_map,
         COMPRESSED_INPUT,
@@ -79,6 +79,8 @@ pub struct Phase1Opts {
         parse(try_from_str = "batch_exp_mode_from_str")
     )]
     pub batch_exp_mode: BatchExpMode,
+    #[options(help = "whether to read and write the next and previous batches while contributing to a batch")]
+    pub pipelined: bool,
     #[options(
         help = "which subgroup check version to use",
         default = "auto",
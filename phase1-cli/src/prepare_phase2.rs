@@ -1,20 +1,55 @@
-use phase1::{parameters::*, Phase1};
-use setup_utils::{CheckForCorrectness, Groth16Params, Result, UseCompression};
+use phase1::{helpers::buffers::split, parameters::*, Phase1};
+use setup_utils::{
+    buffer_size, domain_size, CheckForCorrectness, Groth16Params, PowersOfTauBuffers, Result, UseCompression,
+    UseMixedRadix,
+};
 
 use algebra::PairingEngine as Engine;
 
 use memmap::*;
 use std::fs::OpenOptions;
+use tracing::{info, warn};
 
 const INPUT_IS_COMPRESSED: UseCompression = UseCompression::No;
 const OUTPUT_IS_COMPRESSED: UseCompression = UseCompression::No;
 
+/// Transforms the Powers of Tau of the response to coefficient form for
+/// phase 2. If `memory_budget` is given, the response is not loaded in memory
+/// and the transformation is done out of core, using about that many bytes.
+/// If `phase2_size` is given, the coefficients are computed over its
+/// evaluation domain rather than over all the `2^num_powers` powers. A
+/// mixed-radix domain is selected automatically if the curve supports one and
+/// it is smaller than the radix-2 one. The prepared file records the domain
+/// through its length, which `phase2 new` reads it from. The out-of-core mode
+/// only supports radix-2 domains.
 pub fn prepare_phase2<T: Engine + Sync>(
     phase2_filename: &str,
     response_filename: &str,
     num_powers: usize,
+    phase2_size: Option<usize>,
     parameters: &Phase1Parameters<T>,
+    memory_budget: Option<usize>,
 ) -> Result<()> {
+    let size = phase2_size.unwrap_or(1 << num_powers);
+    let mixed_radix = if memory_budget.is_some() {
+        UseMixedRadix::No
+    } else {
+        UseMixedRadix::Yes
+    };
+    let phase2_size = domain_size::<T::Fr>(size, mixed_radix);
+    assert!(
+        phase2_size <= 1 << num_powers,
+        "the phase 2 size should not exceed the number of powers"
+    );
+    if !phase2_size.is_power_of_two() {
+        info!("Using a mixed-radix domain of size {}", phase2_size);
+    } else if memory_budget.is_some() && domain_size::<T::Fr>(size, UseMixedRadix::Yes) < phase2_size {
+        info!(
+            "Using a radix-2 domain of size {}, as the out-of-core mode does not support the smaller mixed-radix one",
+            phase2_size
+        );
+    }
+
     // Try to load response file from disk.
     let reader = OpenOptions::new()
         .read(true)
@@ -26,6 +61,16 @@ pub fn prepare_phase2<T: Engine + Sync>(
             .expect("unable to create a memory map for input")
     };
 
+    if let Some(memory_budget) = memory_budget {
+        return prepare_phase2_out_of_core(
+            phase2_filename,
+            &response_readable_map,
+            phase2_size,
+            parameters,
+            memory_budget,
+        );
+    }
+
     // Create the parameter file
     let mut writer = OpenOptions::new()
         .read(false)
@@ -45,7 +90,8 @@ pub fn prepare_phase2<T: Engine + Sync>(
 
     // Load the elements to the Groth16 utility
     let groth16_params = Groth16Params::<T>::new(
-        1 << num_powers,
+        phase2_size,
+        UseMixedRadix::Yes,
         current_accumulator.tau_powers_g1,
         current_accumulator.tau_powers_g2,
         current_accumulator.alpha_tau_powers_g1,
@@ -59,3 +105,86 @@ pub fn prepare_phase2<T: Engine + Sync>(
 
     Ok(())
 }
+
+fn prepare_phase2_out_of_core<T: Engine + Sync>(
+    phase2_filename: &str,
+    response: &[u8],
+    phase2_size: usize,
+    parameters: &Phase1Parameters<T>,
+    memory_budget: usize,
+) -> Result<()> {
+    info!(
+        "Preparing phase 2 out of core, with a memory budget of {} bytes",
+        memory_budget
+    );
+
+    // Create the parameter file with its final size, so that it can be mapped
+    let writer = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(phase2_filename)
+        .expect("unable to create parameter file in this directory");
+    writer
+        .set_len(Groth16Params::<T>::serialized_size(phase2_size, OUTPUT_IS_COMPRESSED) as u64)
+        .expect("unable to allocate parameter file");
+    let mut phase2_map = unsafe {
+        MmapOptions::new()
+            .map_mut(&writer)
+            .expect("unable to create a memory map for output")
+    };
+
+    // The intermediate results of the IFFTs are kept on disk as well. A
+    // scratch file left over by an interrupted run is overwritten
+    let scratch_file = ScratchFile(format!("{}.scratch", phase2_filename));
+    let scratch = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&scratch_file.0)
+        .expect("unable to create scratch file in this directory");
+    scratch
+        .set_len((phase2_size * buffer_size::<T::G2Affine>(UseCompression::No)) as u64)
+        .expect("unable to allocate scratch file");
+    let mut scratch_map = unsafe {
+        MmapOptions::new()
+            .map_mut(&scratch)
+            .expect("unable to create a memory map for scratch")
+    };
+
+    let (tau_g1, tau_g2, alpha_g1, beta_g1, beta_g2) = split(response, parameters, INPUT_IS_COMPRESSED);
+    let powers = PowersOfTauBuffers {
+        tau_g1,
+        tau_g2,
+        alpha_g1,
+        beta_g1,
+        beta_g2,
+    };
+    Groth16Params::<T>::write_out_of_core(
+        phase2_size,
+        &powers,
+        CheckForCorrectness::Full,
+        memory_budget,
+        &mut scratch_map,
+        &mut phase2_map,
+    )?;
+    phase2_map.flush().expect("unable to flush parameter file");
+
+    drop(scratch_map);
+    drop(scratch_file);
+
+    Ok(())
+}
+
+/// Removes the scratch file when dropped, so that it is also cleaned up if the
+/// transformation fails or panics
+struct ScratchFile(String);
+
+impl Drop for ScratchFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.0) {
+            warn!("unable to remove scratch file {}: {}", self.0, e);
+        }
+    }
+}
@@ -1,13 +1,26 @@
 use phase1::{
     helpers::testing::{generate_input, setup_verify},
-    Phase1, Phase1Parameters, ProvingSystem,
+    Phase1, Phase1Parameters, PrivateKey, ProvingSystem,
 };
 use setup_utils::*;
 
 use algebra::Bls12_377;
 
-use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use memmap::{Mmap, MmapMut, MmapOptions};
 use rand::thread_rng;
+use std::fs::{self, OpenOptions};
+
+type Computation = fn(
+    &[u8],
+    &mut [u8],
+    UseCompression,
+    UseCompression,
+    CheckForCorrectness,
+    BatchExpMode,
+    &PrivateKey<Bls12_377>,
+    &Phase1Parameters<Bls12_377>,
+) -> Result<()>;
 
 // Benchmark comparing the generation of the iterator in parallel chunks
 // Parallel generation is strictly better
@@ -26,7 +39,7 @@ fn benchmark_initialization(c: &mut Criterion) {
                     group.sample_size(10);
                 }
 
-                let parameters = Phase1Parameters::<Bls12_377>::new_full(proof_system, power, power);
+                let parameters = Phase1Parameters::<Bls12_377>::new_full(*proof_system, power, power);
                 let expected_challenge_length = parameters.get_length(*compression);
 
                 // count in `other` powers (G1 will be 2x that)
@@ -40,7 +53,40 @@ fn benchmark_initialization(c: &mut Criterion) {
     }
 }
 
-// Benchmark comparing contributing to the ceremony
+// Maps a file with the contents in the temporary directory
+fn map_input(name: &str, contents: &[u8]) -> Mmap {
+    let path = std::env::temp_dir().join(name);
+    fs::write(&path, contents).expect("should have written the input file");
+    let file = OpenOptions::new()
+        .read(true)
+        .open(&path)
+        .expect("should have opened the input file");
+    unsafe { MmapOptions::new().map(&file) }.expect("should have mapped the input file")
+}
+
+// Maps a file of the given length in the temporary directory
+fn map_output(name: &str, length: usize) -> MmapMut {
+    let path = std::env::temp_dir().join(name);
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)
+        .expect("should have created the output file");
+    file.set_len(length as u64)
+        .expect("should have allocated the output file");
+    unsafe { MmapOptions::new().map_mut(&file) }.expect("should have mapped the output file")
+}
+
+// Benchmark comparing contributing to the ceremony, with the batches processed
+// one after the other or pipelined, which overlaps reading and writing the
+// batches with their exponentiation. The challenge and the response are memory
+// mapped files as in the CLI, so that reading and writing them goes through
+// the page cache. The sizes span several batches, so that there is something
+// to overlap, and the throughput is reported in powers per second: compare the
+// `_sequential` and `_pipelined` results of each size with
+// `cargo bench --features benchmark --bench phase1 -- computation`
 fn benchmark_computation(c: &mut Criterion) {
     let correctness = CheckForCorrectness::No;
     let compressed_input = UseCompression::No;
@@ -48,18 +94,25 @@ fn benchmark_computation(c: &mut Criterion) {
 
     // Iterate over all combinations of the following parameters
     let proving_system = &[ProvingSystem::Groth16, ProvingSystem::Marlin];
+    let computations = [
+        ("sequential", Phase1::computation as Computation),
+        ("pipelined", Phase1::computation_pipelined),
+    ];
 
     let batch = 256;
     let mut group = c.benchmark_group(format!("computation_{}", batch));
     group.sample_size(10);
 
-    // We gather data on various sizes.
-    for power in 4..8 {
+    // We gather data on various sizes, from 8 to 32 batches of tau powers in G1
+    for power in 10..13 {
         for proof_system in proving_system {
-            let parameters = Phase1Parameters::<Bls12_377>::new_full(proof_system, power, batch);
+            let parameters = Phase1Parameters::<Bls12_377>::new_full(*proof_system, power, batch);
 
             let (input, _) = generate_input(&parameters, compressed_input, correctness);
-            let mut output = vec![0; parameters.get_length(compressed_output)];
+            let input_name = format!("phase1_bench_{:?}_{}.challenge", proof_system, power);
+            let output_name = format!("phase1_bench_{:?}_{}.response", proof_system, power);
+            let input = map_input(&input_name, &input);
+            let mut output = map_output(&output_name, parameters.get_length(compressed_output));
             let current_accumulator_hash = blank_hash();
 
             // Generate the private key.
@@ -67,25 +120,36 @@ fn benchmark_computation(c: &mut Criterion) {
             let (_, private_key) = Phase1::key_generation(&mut rng, current_accumulator_hash.as_ref())
                 .expect("could not generate keypair");
 
-            group.throughput(Throughput::Elements(power as u64));
-            group.bench_with_input(
-                format!("{}_{}", compressed_input, compressed_output),
-                &power,
-                |b, _size| {
-                    b.iter(|| {
-                        Phase1::computation(
-                            &input,
-                            &mut output,
-                            compressed_input,
-                            compressed_output,
-                            CheckForCorrectness::Full,
-                            &private_key,
-                            &parameters,
-                        )
-                        .unwrap()
-                    })
-                },
-            );
+            group.throughput(Throughput::Elements(1 << power));
+            for (name, computation) in &computations {
+                group.bench_with_input(
+                    BenchmarkId::new(
+                        format!("{:?}_{}_{}_{}", proof_system, compressed_input, compressed_output, name),
+                        power,
+                    ),
+                    &power,
+                    |b, _size| {
+                        b.iter(|| {
+                            computation(
+                                &input,
+                                &mut output,
+                                compressed_input,
+                                compressed_output,
+                                CheckForCorrectness::Full,
+                                BatchExpMode::Auto,
+                                &private_key,
+                                &parameters,
+                            )
+                            .unwrap()
+                        })
+                    },
+                );
+            }
+
+            drop((input, output));
+            for name in &[input_name, output_name] {
+                fs::remove_file(std::env::temp_dir().join(name)).expect("should have removed the benchmark file");
+            }
         }
     }
 }
@@ -114,10 +178,16 @@ fn benchmark_verification(c: &mut Criterion) {
     for power in powers {
         for (compressed_input, compressed_output) in compression {
             for proof_system in proving_system {
-                let parameters = Phase1Parameters::<Bls12_377>::new_full(proof_system, power, batch);
-
-                let (input, output, pubkey, current_accumulator_hash) =
-                    setup_verify(*compressed_input, correctness, *compressed_output, &parameters);
+                let parameters = Phase1Parameters::<Bls12_377>::new_full(*proof_system, power, batch);
+
+                let (input, output, pubkey, current_accumulator_hash) = setup_verify(
+                    *compressed_input,
+                    correctness,
+                    *compressed_output,
+                    BatchExpMode::Auto,
+                    &parameters,
+                );
+                let mut new_challenge = vec![0; parameters.get_length(UseCompression::No)];
 
                 group.throughput(Throughput::Elements(power as u64));
                 group.bench_with_input(
@@ -128,10 +198,12 @@ fn benchmark_verification(c: &mut Criterion) {
                             Phase1::verification(
                                 &input,
                                 &output,
+                                &mut new_challenge,
                                 &pubkey,
                                 &current_accumulator_hash,
                                 *compressed_input,
                                 *compressed_output,
+                                UseCompression::No,
                                 correctness,
                                 correctness,
                                 SubgroupCheckMode::Auto,
@@ -36,15 +36,11 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
         match parameters.proving_system {
             ProvingSystem::Groth16 => {
                 // Write beta_g2 (0th index element) to beta_g2_outputs.
-                {
-                    // Fetch the element.
-                    let mut beta_g2_el =
-                        beta_g2_inputs.read_element::<E::G2Affine>(compressed_input, check_input_for_correctness)?;
-                    // Multiply it by the key's beta element.
-                    beta_g2_el = beta_g2_el.mul(key.beta).into_affine();
-                    // Write it back.
-                    beta_g2_outputs.write_element(&beta_g2_el, compressed_output)?;
-                }
+                apply_beta_g2::<E>(
+                    (beta_g2_outputs, compressed_output),
+                    (&mut beta_g2_inputs, compressed_input, check_input_for_correctness),
+                    key,
+                )?;
 
                 // load `batch_size` chunks on each iteration and perform the transformation
                 iter_chunk(&parameters, |start, end| {
@@ -190,68 +186,18 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                 })?;
             }
             ProvingSystem::Marlin => {
-                // we assume batch_size > 3 + 3*total_size_in_log2, allowing all the smaller amounts
-                // of powers in tau G2 and alpha tau G1 to reside there
-                if parameters.chunk_index == 0 {
-                    let degree_bound_powers = (0..parameters.total_size_in_log2)
-                        .map(|i| key.tau.pow([parameters.powers_length as u64 - 1 - (1 << i) + 2]))
-                        .collect::<Vec<_>>();
-
-                    let mut g2_inverse_powers = degree_bound_powers.clone();
-
-                    batch_inversion(&mut g2_inverse_powers);
-
-                    apply_powers::<E::G2Affine>(
-                        (tau_g2_outputs, compressed_output),
-                        (tau_g2_inputs, compressed_input, check_input_for_correctness),
-                        (2, parameters.total_size_in_log2 + 2),
-                        &g2_inverse_powers,
-                        None,
-                        batch_exp_mode,
-                    )
-                    .expect("could not apply powers of tau to tau_g2 elements");
-
-                    let g1_degree_powers = degree_bound_powers
-                        .into_iter()
-                        .map(|f| vec![f, f * &key.tau, f * &key.tau.pow([2])])
-                        .flatten()
-                        .collect::<Vec<_>>();
-
-                    apply_powers::<E::G1Affine>(
-                        (alpha_g1_outputs, compressed_output),
-                        (alpha_g1_inputs, compressed_input, check_input_for_correctness),
-                        (3, 3 + 3 * parameters.total_size_in_log2),
-                        &g1_degree_powers,
-                        Some(&key.alpha),
-                        batch_exp_mode,
-                    )
-                    .expect("could not apply powers of tau to tau_g2 elements");
-
-                    let num_alpha_powers = 3;
-                    let powers = generate_powers_of_tau::<E>(&key.tau, 0, num_alpha_powers);
-
-                    apply_powers::<E::G1Affine>(
-                        (alpha_g1_outputs, compressed_output),
-                        (alpha_g1_inputs, compressed_input, check_input_for_correctness),
-                        (0, num_alpha_powers),
-                        &powers,
-                        Some(&key.alpha),
-                        batch_exp_mode,
-                    )
-                    .expect("could not apply powers of tau alpha to tau_g1 elements");
-
-                    let powers = generate_powers_of_tau::<E>(&key.tau, 0, 2);
-
-                    apply_powers::<E::G2Affine>(
-                        (tau_g2_outputs, compressed_output),
-                        (tau_g2_inputs, compressed_input, check_input_for_correctness),
-                        (0, 2),
-                        &powers,
-                        None,
-                        batch_exp_mode,
-                    )
-                    .expect("could not apply powers of tau to initial tau_g2 elements");
-                }
+                apply_marlin_degree_bounds::<E>(
+                    (tau_g2_outputs, alpha_g1_outputs, compressed_output),
+                    (
+                        tau_g2_inputs,
+                        alpha_g1_inputs,
+                        compressed_input,
+                        check_input_for_correctness,
+                    ),
+                    batch_exp_mode,
+                    key,
+                    parameters,
+                );
 
                 // load `batch_size` chunks on each iteration and perform the transformation
                 iter_chunk(&parameters, |start, end| {
@@ -303,6 +249,262 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
 
         Ok(())
     }
+
+    ///
+    /// Phase 1 - Computation, pipelined
+    ///
+    /// Same as `computation`, except that each batch is read and decoded while the previous
+    /// batch is exponentiated and the one before it is written, so that the cores are not idle
+    /// while waiting on the input and output buffers (e.g. page faults on large memory maps).
+    ///
+    #[cfg(not(feature = "wasm"))]
+    pub fn computation_pipelined(
+        input: &[u8],
+        output: &mut [u8],
+        compressed_input: UseCompression,
+        compressed_output: UseCompression,
+        check_input_for_correctness: CheckForCorrectness,
+        batch_exp_mode: BatchExpMode,
+        key: &PrivateKey<E>,
+        parameters: &'a Phase1Parameters<E>,
+    ) -> Result<()> {
+        let span = info_span!("phase1-computation-pipelined");
+        let _ = span.enter();
+
+        info!("starting...");
+
+        // Get immutable references of the input chunks.
+        let (tau_g1_inputs, tau_g2_inputs, alpha_g1_inputs, beta_g1_inputs, mut beta_g2_inputs) =
+            split(&input, parameters, compressed_input);
+
+        // Get mutable references of the outputs.
+        let (tau_g1_outputs, tau_g2_outputs, alpha_g1_outputs, beta_g1_outputs, beta_g2_outputs) =
+            split_mut(output, parameters, compressed_output);
+
+        // The index of the first element of the chunk.
+        let offset = match parameters.contribution_mode {
+            ContributionMode::Chunked => parameters.chunk_index * parameters.chunk_size,
+            ContributionMode::Full => 0,
+        };
+
+        // Collect the batches of the chunk, relative to its start.
+        let mut batches = vec![];
+        iter_chunk(&parameters, |start, end| {
+            batches.push((start - offset, end - offset));
+            Ok(())
+        })?;
+
+        // Generate the powers of the batch (e.g. [0,4) then [4, 8) etc.)
+        let powers = |start: usize, end: usize| generate_powers_of_tau::<E>(&key.tau, start + offset, end + offset);
+
+        match parameters.proving_system {
+            ProvingSystem::Groth16 => {
+                // Write beta_g2 (0th index element) to beta_g2_outputs.
+                apply_beta_g2::<E>(
+                    (beta_g2_outputs, compressed_output),
+                    (&mut beta_g2_inputs, compressed_input, check_input_for_correctness),
+                    key,
+                )?;
+
+                // The other elements only go up to `powers_length`, so the batches
+                // past it are dropped and the last one is truncated.
+                let max = match parameters.contribution_mode {
+                    ContributionMode::Chunked => std::cmp::min(
+                        (parameters.chunk_index + 1) * parameters.chunk_size,
+                        parameters.powers_length,
+                    ),
+                    ContributionMode::Full => parameters.powers_length,
+                };
+                let other_batches = batches
+                    .iter()
+                    .filter(|(start, _)| start + offset < parameters.powers_length)
+                    .map(|&(start, end)| {
+                        let end = if start + offset + parameters.batch_size > max {
+                            max - offset
+                        } else {
+                            end
+                        };
+                        (start, end)
+                    })
+                    .collect::<Vec<_>>();
+
+                crossbeam::scope(|s| -> Result<_> {
+                    let mut threads = Vec::with_capacity(4);
+                    threads.push(s.spawn(|_| {
+                        let _ = span.enter();
+                        apply_powers_pipelined::<E::G1Affine>(
+                            (tau_g1_outputs, compressed_output),
+                            (tau_g1_inputs, compressed_input, check_input_for_correctness),
+                            &batches,
+                            powers,
+                            None,
+                            batch_exp_mode,
+                        )
+                    }));
+                    threads.push(s.spawn(|_| {
+                        let _ = span.enter();
+                        apply_powers_pipelined::<E::G2Affine>(
+                            (tau_g2_outputs, compressed_output),
+                            (tau_g2_inputs, compressed_input, check_input_for_correctness),
+                            &other_batches,
+                            powers,
+                            None,
+                            batch_exp_mode,
+                        )
+                    }));
+                    threads.push(s.spawn(|_| {
+                        let _ = span.enter();
+                        apply_powers_pipelined::<E::G1Affine>(
+                            (alpha_g1_outputs, compressed_output),
+                            (alpha_g1_inputs, compressed_input, check_input_for_correctness),
+                            &other_batches,
+                            powers,
+                            Some(&key.alpha),
+                            batch_exp_mode,
+                        )
+                    }));
+                    threads.push(s.spawn(|_| {
+                        let _ = span.enter();
+                        apply_powers_pipelined::<E::G1Affine>(
+                            (beta_g1_outputs, compressed_output),
+                            (beta_g1_inputs, compressed_input, check_input_for_correctness),
+                            &other_batches,
+                            powers,
+                            Some(&key.beta),
+                            batch_exp_mode,
+                        )
+                    }));
+
+                    for thread in threads {
+                        thread.join()??;
+                    }
+
+                    Ok(())
+                })??;
+            }
+            ProvingSystem::Marlin => {
+                apply_marlin_degree_bounds::<E>(
+                    (tau_g2_outputs, alpha_g1_outputs, compressed_output),
+                    (
+                        tau_g2_inputs,
+                        alpha_g1_inputs,
+                        compressed_input,
+                        check_input_for_correctness,
+                    ),
+                    batch_exp_mode,
+                    key,
+                    parameters,
+                );
+
+                apply_powers_pipelined::<E::G1Affine>(
+                    (tau_g1_outputs, compressed_output),
+                    (tau_g1_inputs, compressed_input, check_input_for_correctness),
+                    &batches,
+                    powers,
+                    None,
+                    batch_exp_mode,
+                )?;
+            }
+        }
+
+        info!("phase1-contribution complete");
+
+        Ok(())
+    }
+}
+
+/// Multiplies the beta element in G2 by the key's beta
+fn apply_beta_g2<E: PairingEngine>(
+    (output, compressed_output): (&mut [u8], UseCompression),
+    (input, compressed_input, check_input_for_correctness): (&mut &[u8], UseCompression, CheckForCorrectness),
+    key: &PrivateKey<E>,
+) -> Result<()> {
+    // Fetch the element.
+    let mut beta_g2_el = input.read_element::<E::G2Affine>(compressed_input, check_input_for_correctness)?;
+    // Multiply it by the key's beta element.
+    beta_g2_el = beta_g2_el.mul(key.beta).into_affine();
+    // Write it back.
+    output.write_element(&beta_g2_el, compressed_output)?;
+
+    Ok(())
+}
+
+/// Applies the key to the Marlin elements of tau in G2 and alpha tau in G1
+/// which are used for the degree bounds, and which all lie in the first chunk
+fn apply_marlin_degree_bounds<E: PairingEngine>(
+    (tau_g2_outputs, alpha_g1_outputs, compressed_output): (&mut [u8], &mut [u8], UseCompression),
+    (tau_g2_inputs, alpha_g1_inputs, compressed_input, check_input_for_correctness): (
+        &[u8],
+        &[u8],
+        UseCompression,
+        CheckForCorrectness,
+    ),
+    batch_exp_mode: BatchExpMode,
+    key: &PrivateKey<E>,
+    parameters: &Phase1Parameters<E>,
+) {
+    // we assume batch_size > 3 + 3*total_size_in_log2, allowing all the smaller amounts
+    // of powers in tau G2 and alpha tau G1 to reside there
+    if parameters.chunk_index == 0 {
+        let degree_bound_powers = (0..parameters.total_size_in_log2)
+            .map(|i| key.tau.pow([parameters.powers_length as u64 - 1 - (1 << i) + 2]))
+            .collect::<Vec<_>>();
+
+        let mut g2_inverse_powers = degree_bound_powers.clone();
+
+        batch_inversion(&mut g2_inverse_powers);
+
+        apply_powers::<E::G2Affine>(
+            (tau_g2_outputs, compressed_output),
+            (tau_g2_inputs, compressed_input, check_input_for_correctness),
+            (2, parameters.total_size_in_log2 + 2),
+            &g2_inverse_powers,
+            None,
+            batch_exp_mode,
+        )
+        .expect("could not apply powers of tau to tau_g2 elements");
+
+        let g1_degree_powers = degree_bound_powers
+            .into_iter()
+            .map(|f| vec![f, f * &key.tau, f * &key.tau.pow([2])])
+            .flatten()
+            .collect::<Vec<_>>();
+
+        apply_powers::<E::G1Affine>(
+            (alpha_g1_outputs, compressed_output),
+            (alpha_g1_inputs, compressed_input, check_input_for_correctness),
+            (3, 3 + 3 * parameters.total_size_in_log2),
+            &g1_degree_powers,
+            Some(&key.alpha),
+            batch_exp_mode,
+        )
+        .expect("could not apply powers of tau to tau_g2 elements");
+
+        let num_alpha_powers = 3;
+        let powers = generate_powers_of_tau::<E>(&key.tau, 0, num_alpha_powers);
+
+        apply_powers::<E::G1Affine>(
+            (alpha_g1_outputs, compressed_output),
+            (alpha_g1_inputs, compressed_input, check_input_for_correctness),
+            (0, num_alpha_powers),
+            &powers,
+            Some(&key.alpha),
+            batch_exp_mode,
+        )
+        .expect("could not apply powers of tau alpha to tau_g1 elements");
+
+        let powers = generate_powers_of_tau::<E>(&key.tau, 0, 2);
+
+        apply_powers::<E::G2Affine>(
+            (tau_g2_outputs, compressed_output),
+            (tau_g2_inputs, compressed_input, check_input_for_correctness),
+            (0, 2),
+            &powers,
+            None,
+            batch_exp_mode,
+        )
+        .expect("could not apply powers of tau to initial tau_g2 elements");
+    }
 }
 
 #[cfg(test)]
@@ -351,6 +553,21 @@ mod tests {
                 )
                 .unwrap();
 
+                // The pipelined computation produces the same output
+                let mut pipelined_output = vec![0; expected_response_length];
+                Phase1::computation_pipelined(
+                    &input,
+                    &mut pipelined_output,
+                    compressed_input,
+                    compressed_output,
+                    input_correctness,
+                    batch_exp_mode,
+                    &privkey,
+                    &parameters,
+                )
+                .unwrap();
+                assert_eq!(pipelined_output, output);
+
                 let deserialized =
                     Phase1::deserialize(&output, compressed_output, input_correctness, &parameters).unwrap();
 
@@ -7,9 +7,9 @@ use setup_utils::{BatchDeserializer, BatchSerializer, Deserializer, Serializer,
 use algebra::{AffineCurve, PairingEngine};
 
 #[cfg(not(feature = "wasm"))]
-use setup_utils::SubgroupCheckMode;
+use crate::ContributionMode;
 #[cfg(not(feature = "wasm"))]
-use {crate::ContributionMode, algebra::batch_verify_in_subgroup};
+use setup_utils::SubgroupCheckMode;
 
 #[allow(type_alias_bounds)]
 type AccumulatorElements<E: PairingEngine> = (
@@ -32,10 +32,7 @@ type AccumulatorElementsRef<'a, E: PairingEngine> = (
 
 cfg_if! {
     if #[cfg(not(feature = "wasm"))] {
-        use algebra::{PrimeField, FpParameters, cfg_iter, Zero};
-        #[cfg(feature = "parallel")]
-        use rayon::prelude::*;
-        use tracing::{warn,debug};
+        use tracing::debug;
 
         use crate::PublicKey;
         /// Given a public key and the accumulator's digest, it hashes each G1 element
@@ -95,38 +92,14 @@ cfg_if! {
             subgroup_check_mode: SubgroupCheckMode,
         ) -> Result<()> {
             let size = buffer_size::<C>(compression);
-            buffer[start * size..end * size].read_batch_preallocated(
+            let now = std::time::Instant::now();
+            buffer[start * size..end * size].read_batch_preallocated_with_subgroup_check_mode(
                 &mut elements[0..end - start],
                 compression,
-                CheckForCorrectness::OnlyNonZero,
+                CheckForCorrectness::Full,
+                subgroup_check_mode,
             )?;
-
-            const SECURITY_PARAM: usize = 128;
-            const BATCH_SIZE: usize = 1 << 12;
-            let now = std::time::Instant::now();
-            let prime_order_subgroup_check_pass = match (elements.len() > BATCH_SIZE, subgroup_check_mode) {
-                (_, SubgroupCheckMode::No) => true,
-                (true, SubgroupCheckMode::Auto) | (_, SubgroupCheckMode::Batched) => {
-                    match batch_verify_in_subgroup(elements, SECURITY_PARAM, &mut rand::thread_rng()) {
-                        Ok(()) => true,
-                        _ => false,
-                    }
-                }
-                (false, SubgroupCheckMode::Auto) | (_, SubgroupCheckMode::Direct) => {
-                    cfg_iter!(elements).enumerate().all(|(i, p)| {
-                        let res = p.mul(<<C::ScalarField as PrimeField>::Params as FpParameters>::MODULUS)
-                            .is_zero();
-                        if !res {
-                            warn!("Wasn't in subgroup {} index {}", p, i)
-                        }
-                        res
-                    })
-                }
-            };
-            debug!("Subgroup verification for {} elems: {}us", end - start, now.elapsed().as_micros());
-            if !prime_order_subgroup_check_pass {
-                return Err(Error::IncorrectSubgroup);
-            }
+            debug!("Read and subgroup verification for {} elems: {}us", end - start, now.elapsed().as_micros());
             Ok(())
         }
 
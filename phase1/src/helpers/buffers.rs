@@ -96,6 +96,70 @@ pub(crate) fn apply_powers<C: AffineCurve>(
     Ok(())
 }
 
+#[cfg(not(feature = "wasm"))]
+/// The number of batches which may wait between two stages of `apply_powers_pipelined`
+const PIPELINE_DEPTH: usize = 1;
+
+#[cfg(not(feature = "wasm"))]
+/// Same as calling `apply_powers` on each of the `batches`, except that the
+/// reading, exponentiation and writing of consecutive batches overlap: the next
+/// batch is read and decoded while the current one is exponentiated and the
+/// previous one is written. `powers` returns the powers for a given batch.
+pub(crate) fn apply_powers_pipelined<C: AffineCurve>(
+    (output, output_compressed): Output,
+    (input, input_compressed, check_input_for_correctness): Input,
+    batches: &[(usize, usize)],
+    powers: impl Fn(usize, usize) -> Vec<C::ScalarField> + Send,
+    coeff: Option<&C::ScalarField>,
+    batch_exp_mode: BatchExpMode,
+) -> Result<()> {
+    let in_size = buffer_size::<C>(input_compressed);
+    let out_size = buffer_size::<C>(output_compressed);
+
+    crossbeam::scope(|s| -> Result<()> {
+        let (read_sender, read_receiver) = crossbeam::channel::bounded::<Vec<C>>(PIPELINE_DEPTH);
+        let (exp_sender, exp_receiver) = crossbeam::channel::bounded::<Vec<C>>(PIPELINE_DEPTH);
+
+        // Read the input
+        let reader = s.spawn(move |_| -> Result<()> {
+            for &(start, end) in batches {
+                let elements = input[start * in_size..end * in_size]
+                    .read_batch::<C>(input_compressed, check_input_for_correctness)?;
+                // the next stage only hangs up if it failed, in which case its error is returned
+                if read_sender.send(elements).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        });
+
+        // calculate the powers
+        let exponentiator = s.spawn(move |_| -> Result<()> {
+            for (mut elements, &(start, end)) in read_receiver.iter().zip(batches) {
+                batch_exp(&mut elements, &powers(start, end), coeff, batch_exp_mode)?;
+                if exp_sender.send(elements).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        });
+
+        // write back
+        let written = exp_receiver
+            .iter()
+            .zip(batches)
+            .try_for_each(|(elements, &(start, end))| {
+                output[start * out_size..end * out_size].write_batch(&elements, output_compressed)
+            });
+        // stop the other stages if writing failed
+        drop(exp_receiver);
+
+        reader.join()??;
+        exponentiator.join()??;
+        written
+    })?
+}
+
 #[cfg(not(feature = "wasm"))]
 /// Splits the full buffer in 5 non overlapping mutable slice for a given chunk and batch size.
 /// Each slice corresponds to the group elements in the following order
@@ -290,7 +354,7 @@ pub(crate) fn split_mut<'a, E: PairingEngine>(
 /// Splits the full buffer in 5 non overlapping immutable slice.
 /// Each slice corresponds to the group elements in the following order
 /// [TauG1, TauG2, AlphaG1, BetaG1, BetaG2]
-pub(crate) fn split<'a, E: PairingEngine>(
+pub fn split<'a, E: PairingEngine>(
     buffer: &'a [u8],
     parameters: &Phase1Parameters<E>,
     compressed: UseCompression,
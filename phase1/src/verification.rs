@@ -87,35 +87,26 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
             let [tau_g2_s, alpha_g2_s, beta_g2_s] = compute_g2_s_key(&key, &digest)?;
 
             // Compose into tuple form for convenience.
-            let tau_single_g1_check = &(key.tau_g1.0, key.tau_g1.1);
-            let tau_single_g2_check = &(tau_g2_s, key.tau_g2);
-            // let alpha_single_g1_check = &(key.alpha_g1.0, key.alpha_g1.1);
-            let alpha_single_g2_check = &(alpha_g2_s, key.alpha_g2);
-            let beta_single_g1_check = &(key.beta_g1.0, key.beta_g1.1);
-            let beta_single_g2_check = &(beta_g2_s, key.beta_g2);
-
-            // Ensure the key ratios are correctly produced.
-            {
-                // Check the proofs of knowledge for tau, alpha, and beta.
-                let check_ratios = &[
-                    (&(key.tau_g1.0, key.tau_g1.1), &(tau_g2_s, key.tau_g2), "Tau G1<>G2"),
-                    (
-                        &(key.alpha_g1.0, key.alpha_g1.1),
-                        &(alpha_g2_s, key.alpha_g2),
-                        "Alpha G1<>G2",
-                    ),
-                    (
-                        &(key.beta_g1.0, key.beta_g1.1),
-                        &(beta_g2_s, key.beta_g2),
-                        "Beta G1<>G2",
-                    ),
-                ];
-
-                for (a, b, err) in check_ratios {
-                    check_same_ratio::<E>(a, b, err)?;
-                }
-                debug!("key ratios were correctly produced");
-            }
+            let tau_single_g1_check = (key.tau_g1.0, key.tau_g1.1);
+            let tau_single_g2_check = (tau_g2_s, key.tau_g2);
+            // let alpha_single_g1_check = (key.alpha_g1.0, key.alpha_g1.1);
+            let alpha_single_g2_check = (alpha_g2_s, key.alpha_g2);
+            let beta_single_g1_check = (key.beta_g1.0, key.beta_g1.1);
+            let beta_single_g2_check = (beta_g2_s, key.beta_g2);
+
+            // The key ratios and the ratios of the initial elements are checked together.
+            let mut ratio_checks = Vec::with_capacity(8);
+
+            // Check the proofs of knowledge for tau, alpha, and beta.
+            ratio_checks.extend_from_slice(&[
+                ((key.tau_g1.0, key.tau_g1.1), (tau_g2_s, key.tau_g2), "Tau G1<>G2"),
+                (
+                    (key.alpha_g1.0, key.alpha_g1.1),
+                    (alpha_g2_s, key.alpha_g2),
+                    "Alpha G1<>G2",
+                ),
+                ((key.beta_g1.0, key.beta_g1.1), (beta_g2_s, key.beta_g2), "Beta G1<>G2"),
+            ]);
 
             // Ensure that the initial conditions are correctly formed (first 2 elements).
             // We allocate a G1 vector of length 2 and re-use it for our G1 elements.
@@ -136,11 +127,7 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                 }
 
                 // Check that tau^1 was multiplied correctly.
-                check_same_ratio::<E>(
-                    &(before_g1[1], after_g1[1]),
-                    tau_single_g2_check,
-                    "Before-After: tau_g1",
-                )?;
+                ratio_checks.push(((before_g1[1], after_g1[1]), tau_single_g2_check, "Before-After: tau_g1"));
 
                 (before_g1, after_g1)
             };
@@ -160,11 +147,7 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                 }
 
                 // Check that tau^1 was multiplied correctly.
-                check_same_ratio::<E>(
-                    tau_single_g1_check,
-                    &(before_g2[1], after_g2[1]),
-                    "Before-After: tau_g2",
-                )?;
+                ratio_checks.push((tau_single_g1_check, (before_g2[1], after_g2[1]), "Before-After: tau_g2"));
             }
 
             // Check that alpha_g1[0] and beta_g1[0] were computed correctly.
@@ -179,14 +162,14 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                 };
 
                 // Check that alpha_g1[0] and beta_g1[0] was multiplied correctly.
-                for (before, after, check) in &checks {
+                for (before, after, check) in checks {
                     before.read_batch_preallocated(&mut before_g1, compressed_input, check_input_for_correctness)?;
                     after.read_batch_preallocated(&mut after_g1, compressed_output, check_output_for_correctness)?;
-                    check_same_ratio::<E>(
-                        &(before_g1[0], after_g1[0]),
+                    ratio_checks.push((
+                        (before_g1[0], after_g1[0]),
                         check,
                         "Before-After: alpha_g1[0] / beta_g1[0]",
-                    )?;
+                    ));
                 }
             }
 
@@ -201,13 +184,16 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                     new_challenge_beta_g2.write_element(&after_beta_g2, compressed_new_challenge)?;
 
                     // Check that beta_g2[0] was multiplied correctly.
-                    check_same_ratio::<E>(
+                    ratio_checks.push((
                         beta_single_g1_check,
-                        &(before_beta_g2, after_beta_g2),
+                        (before_beta_g2, after_beta_g2),
                         "Before-After: beta_g2[0]",
-                    )?;
+                    ));
                 }
             }
+
+            check_same_ratios_batched::<E>(&ratio_checks)?;
+            debug!("key ratios were correctly produced");
         };
 
         debug!("initial elements were computed correctly");
@@ -671,13 +657,6 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                                 let g2 = (&tau_g2[(2 + i) * g2_size..(2 + i + 1) * g2_size])
                                     .read_element(compressed_output, check_output_for_correctness)
                                     .expect("should have read g2 element");
-                                check_same_ratio::<E>(
-                                    &(g1, E::G1Affine::prime_subgroup_generator()),
-                                    &(E::G2Affine::prime_subgroup_generator(), g2),
-                                    "G1<>G2",
-                                )
-                                .expect("should have checked same ratio");
-
                                 let mut alpha_g1_elements = vec![E::G1Affine::zero(); 3];
                                 (&alpha_g1[(3 + 3 * i) * g1_size..(3 + 3 * i + 3) * g1_size])
                                     .read_batch_preallocated(
@@ -686,23 +665,28 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                                         check_output_for_correctness,
                                     )
                                     .expect("should have read alpha g1 elements");
-                                check_same_ratio::<E>(
-                                    &(alpha_g1_elements[0], alpha_g1_elements[1]),
-                                    &g2_check,
-                                    "alpha_g1 ratio 1",
-                                )
-                                .expect("should have checked same ratio");
-                                check_same_ratio::<E>(
-                                    &(alpha_g1_elements[1], alpha_g1_elements[2]),
-                                    &g2_check,
-                                    "alpha_g1 ratio 2",
-                                )
-                                .expect("should have checked same ratio");
-                                check_same_ratio::<E>(
-                                    &(alpha_g1_elements[0], g1_alpha_check.0),
-                                    &(E::G2Affine::prime_subgroup_generator(), g2),
-                                    "alpha consistent",
-                                )
+                                check_same_ratios_batched::<E>(&[
+                                    (
+                                        (g1, E::G1Affine::prime_subgroup_generator()),
+                                        (E::G2Affine::prime_subgroup_generator(), g2),
+                                        "G1<>G2",
+                                    ),
+                                    (
+                                        (alpha_g1_elements[0], alpha_g1_elements[1]),
+                                        g2_check,
+                                        "alpha_g1 ratio 1",
+                                    ),
+                                    (
+                                        (alpha_g1_elements[1], alpha_g1_elements[2]),
+                                        g2_check,
+                                        "alpha_g1 ratio 2",
+                                    ),
+                                    (
+                                        (alpha_g1_elements[0], g1_alpha_check.0),
+                                        (E::G2Affine::prime_subgroup_generator(), g2),
+                                        "alpha consistent",
+                                    ),
+                                ])
                                 .expect("should have checked same ratio");
                             }
                         }
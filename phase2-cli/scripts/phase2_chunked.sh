@@ -27,7 +27,7 @@ phase2_2="../../target/release/phase2 --curve-kind $CURVE --batch-size $BATCH --
 
 MAX_CHUNK_INDEX=1
 
-$phase2_chunked new --challenge-fname challenge --challenge-hash-fname challenge.verified.hash --phase1-fname ../../phase1-tests/phase1 --phase1-powers $POWER --num-validators 1 --num-epochs 1
+$phase2_chunked new --challenge-fname challenge --challenge-hash-fname challenge.verified.hash --phase1-fname ../../phase1-tests/phase1 --phase1-powers $POWER
 for i in $(seq 0 $(($MAX_CHUNK_INDEX/2))); do
   echo "Contributing and verifying chunk $i..."
   $phase2_1 --chunk-index $i contribute --challenge-fname challenge.$i --challenge-hash-fname challenge.$i.hash --response-fname response_$i --response-hash-fname response_$i.hash
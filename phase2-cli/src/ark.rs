@@ -0,0 +1,144 @@
+//! Conversion of Zexe Groth16 parameters to their arkworks equivalents.
+//!
+//! Zexe and arkworks share the same uncompressed point encoding, so points are
+//! converted by serializing them with Zexe and deserializing them with arkworks.
+use algebra::{AffineCurve, Bls12_377, CanonicalSerialize, ConstantSerializedSize, PairingEngine, BW6_761};
+use ark_serialize::CanonicalDeserialize;
+use groth16::{Parameters, VerifyingKey};
+
+use anyhow::ensure;
+
+/// A Zexe pairing engine which has an arkworks counterpart over the same curve.
+pub trait ArkPairingEngine: PairingEngine {
+    type Ark: ark_ec::PairingEngine;
+}
+
+impl ArkPairingEngine for Bls12_377 {
+    type Ark = ark_bls12_377::Bls12_377;
+}
+
+impl ArkPairingEngine for BW6_761 {
+    type Ark = ark_bw6_761::BW6_761;
+}
+
+/// Re-encodes a Zexe affine point as an arkworks affine point. The point is
+/// not checked for subgroup membership, since it comes from already verified
+/// parameters.
+pub fn to_ark_point<Z: AffineCurve, A: ark_ec::AffineCurve>(point: &Z) -> anyhow::Result<A> {
+    let mut bytes = Vec::with_capacity(Z::UNCOMPRESSED_SIZE);
+    point.serialize_uncompressed(&mut bytes)?;
+    Ok(A::deserialize_unchecked(&bytes[..])?)
+}
+
+fn to_ark_points<Z: AffineCurve, A: ark_ec::AffineCurve>(points: &[Z]) -> anyhow::Result<Vec<A>> {
+    points.iter().map(to_ark_point).collect()
+}
+
+/// Converts a Zexe `VerifyingKey` to an arkworks `VerifyingKey`
+pub fn to_ark_verifying_key<E: ArkPairingEngine>(
+    vk: &VerifyingKey<E>,
+) -> anyhow::Result<ark_groth16::VerifyingKey<E::Ark>> {
+    Ok(ark_groth16::VerifyingKey {
+        alpha_g1: to_ark_point(&vk.alpha_g1)?,
+        beta_g2: to_ark_point(&vk.beta_g2)?,
+        gamma_g2: to_ark_point(&vk.gamma_g2)?,
+        delta_g2: to_ark_point(&vk.delta_g2)?,
+        gamma_abc_g1: to_ark_points(&vk.gamma_abc_g1)?,
+    })
+}
+
+/// Converts Zexe Groth16 `Parameters` to an arkworks `ProvingKey`. ark-groth16
+/// always proves over a radix-2 domain, so parameters generated over a
+/// mixed-radix domain are rejected, as their proofs would not verify.
+pub fn to_ark_proving_key<E: ArkPairingEngine>(
+    params: &Parameters<E>,
+) -> anyhow::Result<ark_groth16::ProvingKey<E::Ark>> {
+    let domain_size = params.h_query.len() + 1;
+    ensure!(
+        domain_size.is_power_of_two(),
+        "ark-groth16 only supports radix-2 domains, the parameters use a mixed-radix domain of size {}",
+        domain_size
+    );
+    Ok(ark_groth16::ProvingKey {
+        vk: to_ark_verifying_key(&params.vk)?,
+        beta_g1: to_ark_point(&params.beta_g1)?,
+        delta_g1: to_ark_point(&params.delta_g1)?,
+        a_query: to_ark_points(&params.a_query)?,
+        b_g1_query: to_ark_points(&params.b_g1_query)?,
+        b_g2_query: to_ark_points(&params.b_g2_query)?,
+        h_query: to_ark_points(&params.h_query)?,
+        l_query: to_ark_points(&params.l_query)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::Field as ArkField;
+    use ark_relations::r1cs::{
+        ConstraintSynthesizer as ArkConstraintSynthesizer, ConstraintSystemRef as ArkConstraintSystemRef,
+        SynthesisError as ArkSynthesisError,
+    };
+    use ark_serialize::CanonicalSerialize as ArkCanonicalSerialize;
+    use phase2::helpers::testing::TestCircuit;
+
+    // arkworks port of `phase2::helpers::testing::TestCircuit`, which must
+    // allocate variables and constraints in the exact same order
+    #[derive(Clone)]
+    struct ArkTestCircuit<F: ArkField>(Option<F>);
+
+    impl<F: ArkField> ArkConstraintSynthesizer<F> for ArkTestCircuit<F> {
+        fn generate_constraints(self, cs: ArkConstraintSystemRef<F>) -> Result<(), ArkSynthesisError> {
+            use ark_relations::lc;
+            let x = cs.new_witness_variable(|| self.0.ok_or(ArkSynthesisError::AssignmentMissing))?;
+            let out =
+                cs.new_input_variable(|| self.0.map(|x| x.square()).ok_or(ArkSynthesisError::AssignmentMissing))?;
+            for _ in 0..4 {
+                cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + out)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn ark_round_trip_proof() {
+        ark_round_trip_proof_curve::<Bls12_377>()
+    }
+
+    fn ark_round_trip_proof_curve<E: ArkPairingEngine>() {
+        let rng = &mut rand::thread_rng();
+        let params = groth16::generate_random_parameters::<E, _, _>(TestCircuit::<E>(None), rng).unwrap();
+
+        let pk = to_ark_proving_key(&params).unwrap();
+        let vk = to_ark_verifying_key(&params.vk).unwrap();
+
+        // the keys must survive an arkworks serialization round trip
+        let mut serialized_pk = vec![];
+        pk.serialize_uncompressed(&mut serialized_pk).unwrap();
+        let pk = ark_groth16::ProvingKey::<E::Ark>::deserialize_uncompressed(&serialized_pk[..]).unwrap();
+        let mut serialized_vk = vec![];
+        vk.serialize(&mut serialized_vk).unwrap();
+        let vk = ark_groth16::VerifyingKey::<E::Ark>::deserialize(&serialized_vk[..]).unwrap();
+        assert!(pk.vk == vk);
+
+        let input = <<E::Ark as ark_ec::PairingEngine>::Fr as From<u64>>::from(5);
+        let out = <<E::Ark as ark_ec::PairingEngine>::Fr as From<u64>>::from(25);
+        let proof = ark_groth16::create_random_proof(ArkTestCircuit(Some(input)), &pk, rng).unwrap();
+        let pvk = ark_groth16::prepare_verifying_key(&vk);
+        assert!(ark_groth16::verify_proof(&pvk, &proof, &[out]).unwrap());
+    }
+
+    #[test]
+    fn mixed_radix_parameters_are_rejected() {
+        mixed_radix_parameters_are_rejected_curve::<BW6_761>()
+    }
+
+    fn mixed_radix_parameters_are_rejected_curve<E: ArkPairingEngine>() {
+        let rng = &mut rand::thread_rng();
+        let mut params = groth16::generate_random_parameters::<E, _, _>(TestCircuit::<E>(None), rng).unwrap();
+        assert!((params.h_query.len() + 1).is_power_of_two());
+        // the H query of parameters over a domain of size 6
+        params.h_query.truncate(5);
+        assert!(to_ark_proving_key(&params).is_err());
+    }
+}
@@ -1,12 +1,16 @@
 use setup_utils::converters::CurveKind;
 
-use algebra::{Bls12_377, PairingEngine as Engine, BW6_761};
+use algebra::{Bls12_377, BW6_761};
 
 use gumdrop::Options;
-use phase2_cli::{combine, contribute, new_challenge, verify, Command, Phase2Opts};
+use phase2_cli::{
+    ark::ArkPairingEngine, check_contribution, circuit_info, combine, contribute, export, export_vk, new_challenge,
+    optional_filename, parse_contribution_hash, smoke_test, smoke_test_provided_circuit, split_filenames, transcript,
+    verify, verify_initial, verify_round_transcript, CircuitSource, Command, Phase2Opts,
+};
 use setup_utils::{
-    derive_rng_from_seed, upgrade_correctness_check_config, CheckForCorrectness,
-    DEFAULT_CONTRIBUTE_CHECK_INPUT_CORRECTNESS, DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS,
+    upgrade_correctness_check_config, CheckForCorrectness, DEFAULT_CONTRIBUTE_CHECK_INPUT_CORRECTNESS,
+    DEFAULT_VERIFY_CHECK_INPUT_CORRECTNESS,
 };
 use std::fs::read_to_string;
 use std::{process, time::Instant};
@@ -16,7 +20,7 @@ use tracing_subscriber::{
     fmt::{time::ChronoUtc, Subscriber},
 };
 
-fn execute_cmd<E: Engine>(opts: Phase2Opts) {
+fn execute_cmd<E: ArkPairingEngine>(opts: Phase2Opts) {
     let command = opts.clone().command.unwrap_or_else(|| {
         error!("No command was provided.");
         error!("{}", Phase2Opts::usage());
@@ -27,34 +31,87 @@ fn execute_cmd<E: Engine>(opts: Phase2Opts) {
 
     match command {
         Command::New(opt) => {
+            let circuits = CircuitSource::parse(&opt.circuit_fname, &opt.circuit, &opt.param).unwrap_or_else(|e| {
+                error!("{}", e);
+                process::exit(2)
+            });
             new_challenge(
                 &opt.challenge_fname,
                 &opt.challenge_hash_fname,
                 &opt.challenge_list_fname,
+                optional_filename(&opt.transcript_fname),
                 opts.chunk_size,
+                if opt.memory_budget > 0 {
+                    Some(opt.memory_budget << 20)
+                } else {
+                    None
+                },
                 &opt.phase1_fname,
                 opt.phase1_powers,
-                &opt.circuit_fname,
+                circuits,
             );
         }
         Command::Contribute(opt) => {
             let seed = hex::decode(&read_to_string(&opts.seed).expect("should have read seed").trim())
                 .expect("seed should be a hex string");
-            let rng = derive_rng_from_seed(&seed);
-            contribute(
-                &opt.challenge_fname,
-                &opt.challenge_hash_fname,
-                &opt.response_fname,
-                &opt.response_hash_fname,
-                upgrade_correctness_check_config(
-                    DEFAULT_CONTRIBUTE_CHECK_INPUT_CORRECTNESS,
-                    opts.force_correctness_checks,
-                ),
-                opts.batch_exp_mode,
-                rng,
-            );
+            let challenge_fnames = split_filenames(&opt.challenge_fname);
+            let challenge_hash_fnames = split_filenames(&opt.challenge_hash_fname);
+            let response_fnames = split_filenames(&opt.response_fname);
+            let response_hash_fnames = split_filenames(&opt.response_hash_fname);
+            if [&challenge_hash_fnames, &response_fnames, &response_hash_fnames]
+                .iter()
+                .any(|fnames| fnames.len() != challenge_fnames.len())
+            {
+                error!("A challenge hash, response and response hash file name must be given for each challenge");
+                process::exit(2);
+            }
+            let challenge_transcript_fnames = split_filenames(&opt.challenge_transcript_fname);
+            let response_transcript_fnames = split_filenames(&opt.response_transcript_fname);
+            let detached = !challenge_transcript_fnames.is_empty() || !response_transcript_fnames.is_empty();
+            if detached
+                && [&challenge_transcript_fnames, &response_transcript_fnames]
+                    .iter()
+                    .any(|fnames| fnames.len() != challenge_fnames.len())
+            {
+                error!("A challenge and response transcript file name must be given for each detached challenge");
+                process::exit(2);
+            }
+            // the delta of each chunk is derived from the seed and the circuit it belongs to
+            for (i, challenge_fname) in challenge_fnames.iter().enumerate() {
+                contribute(
+                    challenge_fname,
+                    &challenge_hash_fnames[i],
+                    &response_fnames[i],
+                    &response_hash_fnames[i],
+                    if detached {
+                        Some((
+                            challenge_transcript_fnames[i].as_str(),
+                            response_transcript_fnames[i].as_str(),
+                        ))
+                    } else {
+                        None
+                    },
+                    upgrade_correctness_check_config(
+                        DEFAULT_CONTRIBUTE_CHECK_INPUT_CORRECTNESS,
+                        opts.force_correctness_checks,
+                    ),
+                    opts.batch_exp_mode,
+                    &seed,
+                );
+            }
         }
         Command::Verify(opt) => {
+            let transcript_fnames = match (
+                optional_filename(&opt.challenge_transcript_fname),
+                optional_filename(&opt.response_transcript_fname),
+            ) {
+                (Some(challenge), Some(response)) => Some((challenge, response)),
+                (None, None) => None,
+                _ => {
+                    error!("The challenge and response transcript files must be given together");
+                    process::exit(2)
+                }
+            };
             verify(
                 &opt.challenge_fname,
                 &opt.challenge_hash_fname,
@@ -64,19 +121,84 @@ fn execute_cmd<E: Engine>(opts: Phase2Opts) {
                 CheckForCorrectness::OnlyNonZero,
                 &opt.new_challenge_fname,
                 &opt.new_challenge_hash_fname,
+                transcript_fnames,
                 opts.subgroup_check_mode,
                 false,
             );
         }
+        Command::VerifyTranscript(opt) => {
+            verify_round_transcript(&opt.challenge_transcript_fname, &opt.response_transcript_fname);
+        }
+        Command::VerifyInitial(opt) => {
+            let mut circuits = CircuitSource::parse(&opt.circuit_fname, &opt.circuit, &opt.param).unwrap_or_else(|e| {
+                error!("{}", e);
+                process::exit(2)
+            });
+            if circuits.len() != 1 {
+                error!("A single circuit must be given");
+                process::exit(2);
+            }
+            let mismatches = verify_initial(
+                &opt.challenge_fname,
+                optional_filename(&opt.transcript_fname),
+                opts.chunk_size,
+                if opt.memory_budget > 0 {
+                    Some(opt.memory_budget << 20)
+                } else {
+                    None
+                },
+                &opt.phase1_fname,
+                opt.phase1_powers,
+                circuits.remove(0),
+            );
+            if mismatches > 0 {
+                process::exit(1);
+            }
+        }
         Command::Combine(opt) => {
             combine(
                 &opt.initial_query_fname,
                 &opt.initial_full_fname,
                 &opt.response_list_fname,
                 &opt.combined_fname,
+                optional_filename(&opt.transcript_fname),
                 false,
             );
         }
+        Command::CheckContribution(opt) => {
+            let contribution_hash = parse_contribution_hash(&opt.hash).unwrap_or_else(|e| {
+                error!("{}", e);
+                process::exit(2)
+            });
+            if check_contribution(&opt.combined_fname, &contribution_hash).is_none() {
+                process::exit(1);
+            }
+        }
+        Command::Transcript(opt) => {
+            transcript(&opt.combined_fname, &opt.output_fname);
+        }
+        Command::SmokeTest(opt) => {
+            let problems = if !opt.circuit.is_empty() {
+                smoke_test_provided_circuit(&opt.params_fname, &opt.vk_fname, &opt.circuit, &opt.param)
+            } else {
+                smoke_test(&opt.params_fname, &opt.vk_fname, &opt.circuit_fname, &opt.witness_fname)
+            };
+            if problems > 0 {
+                process::exit(1);
+            }
+        }
+        Command::Export(opt) => {
+            export::<E>(&opt.params_fname, &opt.output_fname, opt.format);
+        }
+        Command::ExportVk(opt) => {
+            if let Err(e) = export_vk::<E>(&opt.params_fname, &opt.output_fname, opt.format) {
+                error!("{}", e);
+                process::exit(2);
+            }
+        }
+        Command::CircuitInfo(opt) => {
+            circuit_info(&opt.circuit_fname);
+        }
     };
 
     let new_now = Instant::now();
@@ -0,0 +1,136 @@
+use phase2::parameters::{verify_transcript, MPCParameters};
+use setup_utils::{CheckForCorrectness, Result, SubgroupCheckMode};
+
+use algebra::{PairingEngine, BW6_761};
+
+use crate::{print_transcript_header, COMBINED_IS_COMPRESSED};
+use tracing::{error, info};
+
+/// Parses a contribution hash, ignoring whitespace (as printed by `contribute`)
+/// and an optional `0x` prefix
+pub fn parse_contribution_hash(hash: &str) -> Result<[u8; 64], String> {
+    let hash = hash.split_whitespace().collect::<String>();
+    let hash = hash.trim_start_matches("0x");
+    let bytes = hex::decode(hash).map_err(|e| format!("the hash should be a hex string: {}", e))?;
+    if bytes.len() != 64 {
+        return Err(format!("the hash should be 64 bytes, got {}", bytes.len()));
+    }
+    let mut contribution_hash = [0u8; 64];
+    contribution_hash.copy_from_slice(&bytes);
+    Ok(contribution_hash)
+}
+
+/// Verifies the transcript of the parameters and that the parameters have the
+/// delta of its last contribution, so that the transcript cannot come from
+/// another ceremony. Returns the hashes of the contributions.
+pub fn verify_contributions<E: PairingEngine>(mpc: &MPCParameters<E>) -> Result<Vec<[u8; 64]>> {
+    mpc.verify_delta()?;
+    verify_transcript(mpc.cs_hash, &mpc.contributions)
+}
+
+/// Verifies the transcript of the combined parameters and returns the position
+/// of the contribution with the provided hash, if it is included
+pub fn check_contribution(combined_filename: &str, contribution_hash: &[u8; 64]) -> Option<usize> {
+    info!("Checking the inclusion of a contribution in phase 2");
+
+    let combined_contents = std::fs::read(combined_filename).expect("should have read combined file");
+    let combined = MPCParameters::<BW6_761>::read_fast(
+        combined_contents.as_slice(),
+        COMBINED_IS_COMPRESSED,
+        CheckForCorrectness::No,
+        false,
+        SubgroupCheckMode::Auto,
+    )
+    .expect("should have read combined parameters");
+    print_transcript_header(&combined.header);
+
+    let contributions_hash =
+        verify_contributions(&combined).expect("should have verified the transcript against the parameters");
+
+    info!("The transcript contains {} contributions:", contributions_hash.len());
+    for (i, hash) in contributions_hash.iter().enumerate() {
+        let marker = if hash[..] == contribution_hash[..] {
+            " <- yours"
+        } else {
+            ""
+        };
+        info!("{}: {}{}", i, hex::encode(&hash[..]), marker);
+    }
+
+    match contributions_hash
+        .iter()
+        .position(|hash| hash[..] == contribution_hash[..])
+    {
+        Some(position) => {
+            info!(
+                "The contribution is included at position {} of {}",
+                position,
+                contributions_hash.len()
+            );
+            Some(position)
+        }
+        None => {
+            error!("The contribution is not included in the transcript");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algebra::{AffineCurve, Bls12_377, ProjectiveCurve};
+    use phase2::helpers::testing::TestCircuit;
+    use setup_utils::BatchExpMode;
+
+    #[test]
+    fn parse_printed_hash() {
+        let hash = [0xabu8; 64];
+        let hex_hash = hex::encode(&hash[..]);
+        assert_eq!(&parse_contribution_hash(&hex_hash).unwrap()[..], &hash[..]);
+        assert_eq!(
+            &parse_contribution_hash(&format!("0x{}", hex_hash)).unwrap()[..],
+            &hash[..]
+        );
+
+        // the format of `print_hash`
+        let printed = hex_hash
+            .as_bytes()
+            .chunks(32)
+            .map(|line| format!("\t{}\n", std::str::from_utf8(line).unwrap()))
+            .collect::<String>();
+        assert_eq!(&parse_contribution_hash(&printed).unwrap()[..], &hash[..]);
+
+        assert!(parse_contribution_hash("abcd").is_err());
+        assert!(parse_contribution_hash(&"zz".repeat(64)).is_err());
+    }
+
+    #[test]
+    fn transcript_is_bound_to_delta() {
+        transcript_is_bound_to_delta_curve::<Bls12_377>()
+    }
+
+    fn transcript_is_bound_to_delta_curve<E: PairingEngine>() {
+        let rng = &mut rand::thread_rng();
+        let params = groth16::generate_random_parameters::<E, _, _>(TestCircuit::<E>(None), rng).unwrap();
+        let mut mpc = MPCParameters {
+            params,
+            cs_hash: [1; 64],
+            contributions: vec![],
+            header: None,
+        };
+        let mut hashes = vec![];
+        for _ in 0..2 {
+            hashes.push(mpc.contribute(BatchExpMode::Auto, rng).unwrap());
+        }
+        assert_eq!(verify_contributions(&mpc).unwrap(), hashes);
+
+        // the transcript does not verify with another delta in G1 or G2
+        let mut tampered = mpc.clone();
+        tampered.params.delta_g1 = tampered.params.delta_g1.into_projective().double().into_affine();
+        assert!(verify_contributions(&tampered).is_err());
+        let mut tampered = mpc.clone();
+        tampered.params.vk.delta_g2 = tampered.params.vk.delta_g2.into_projective().double().into_affine();
+        assert!(verify_contributions(&tampered).is_err());
+    }
+}
@@ -0,0 +1,42 @@
+use phase2::load_circuit::Matrices;
+
+use algebra::BW6_761;
+use tracing::{info, warn};
+
+/// Reports the size of the circuit and of the domains `phase2 new` may use
+/// for it
+pub fn circuit_info(circuit_filename: &str) {
+    let circuit_contents = std::fs::read(circuit_filename).expect("should have read circuit");
+    let matrices = Matrices::<BW6_761>::read(&circuit_contents).expect("should have read a valid circuit");
+    let circuit_info = matrices.info();
+
+    info!("Constraints: {}", circuit_info.num_constraints);
+    info!(
+        "Variables: {} instance (including the constant one), {} witness",
+        circuit_info.num_instance_variables, circuit_info.num_witness_variables
+    );
+    info!(
+        "Non-zero entries: A {}, B {}, C {}",
+        circuit_info.a_num_non_zero, circuit_info.b_num_non_zero, circuit_info.c_num_non_zero
+    );
+    info!("Density: {:.6}%", circuit_info.density * 100.0);
+    info!(
+        "Phase 2 size: {}, requiring a phase 1 power of at least {}",
+        circuit_info.phase2_size, circuit_info.required_phase1_power
+    );
+    if let Some(mixed_radix_size) = circuit_info.mixed_radix_size {
+        info!(
+            "Mixed-radix phase 2 size: {}, used if phase 1 is prepared with --phase2-size {}",
+            mixed_radix_size, mixed_radix_size
+        );
+    }
+    if circuit_info.unconstrained_variables.is_empty() {
+        info!("All witness variables are constrained");
+    } else {
+        warn!(
+            "{} witness variables are unconstrained and would make `new` fail: {:?}",
+            circuit_info.unconstrained_variables.len(),
+            circuit_info.unconstrained_variables
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Circuits which `phase2 new` can synthesize itself, selected with `--circuit`
+//! and configured with `--param`, instead of reading a circuit file.
+use crate::split_filenames;
+use phase2::{
+    helpers::SquareRootCircuit,
+    load_circuit::{synthesize_assignment, Matrices},
+};
+
+use algebra::{bw6_761::Fr, Bls12_377, PairingEngine, UniformRand, BW6_761};
+use epoch_snark::ValidatorSetUpdate;
+use rand::RngCore;
+use std::{collections::BTreeMap, str::FromStr};
+use tracing::info;
+
+/// The parameters of a circuit, given as comma-separated `key=value` pairs
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CircuitParams(BTreeMap<String, String>);
+
+impl CircuitParams {
+    pub fn parse(src: &str) -> Result<Self, String> {
+        let mut params = BTreeMap::new();
+        for pair in src.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let mut parts = pair.splitn(2, '=');
+            let key = parts.next().unwrap_or_default().trim();
+            let value = parts
+                .next()
+                .ok_or_else(|| format!("parameter `{}` should be of the form key=value", pair))?
+                .trim();
+            if params.insert(key.to_string(), value.to_string()).is_some() {
+                return Err(format!("parameter `{}` was given more than once", key));
+            }
+        }
+        Ok(CircuitParams(params))
+    }
+
+    /// Returns the value of a parameter, or `default` if it was not given
+    pub fn get<T: FromStr>(&self, key: &str, default: T) -> Result<T, String> {
+        match self.0.get(key) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| format!("invalid value `{}` for parameter `{}`", value, key)),
+            None => Ok(default),
+        }
+    }
+
+    /// Fails if a parameter which the circuit does not accept was given
+    pub fn check_known(&self, known: &[&str]) -> Result<(), String> {
+        match self.0.keys().find(|key| !known.contains(&key.as_str())) {
+            Some(key) => Err(format!("unknown parameter `{}`", key)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A circuit which can be synthesized for given parameters
+pub trait CircuitProvider<E: PairingEngine> {
+    /// The name the circuit is selected with
+    fn name(&self) -> &'static str;
+
+    /// A short description of the circuit and of the parameters it accepts
+    fn description(&self) -> &'static str;
+
+    /// Synthesizes the circuit with `circuit_to_qap` and returns its matrices
+    fn synthesize(&self, params: &CircuitParams) -> Result<Matrices<E>, String>;
+
+    /// Returns a full assignment, starting with the constant one, which
+    /// satisfies the circuit and is chosen at random, as used by `smoke-test`
+    fn random_assignment(&self, params: &CircuitParams, rng: &mut dyn RngCore) -> Result<Vec<E::Fr>, String>;
+}
+
+/// The square root circuit, which has no parameters
+pub struct SquareRootProvider;
+
+impl<E: PairingEngine> CircuitProvider<E> for SquareRootProvider {
+    fn name(&self) -> &'static str {
+        "square-root"
+    }
+
+    fn description(&self) -> &'static str {
+        "proves knowledge of a square root, has no parameters"
+    }
+
+    fn synthesize(&self, params: &CircuitParams) -> Result<Matrices<E>, String> {
+        params.check_known(&[])?;
+        Matrices::from_circuit(SquareRootCircuit::<E>(None)).map_err(|e| e.to_string())
+    }
+
+    fn random_assignment(&self, params: &CircuitParams, rng: &mut dyn RngCore) -> Result<Vec<E::Fr>, String> {
+        params.check_known(&[])?;
+        synthesize_assignment(SquareRootCircuit::<E>(Some(E::Fr::rand(rng)))).map_err(|e| e.to_string())
+    }
+}
+
+/// The epoch circuit of Celo's light client, which proves the validator set
+/// updates of `num_epochs` epochs with `num_validators` validators each. At
+/// most `maximum_non_signers` validators, a third by default, may not sign.
+pub struct EpochProvider;
+
+impl CircuitProvider<BW6_761> for EpochProvider {
+    fn name(&self) -> &'static str {
+        "epoch"
+    }
+
+    fn description(&self) -> &'static str {
+        "proves validator set updates, takes num_validators, num_epochs and maximum_non_signers"
+    }
+
+    fn synthesize(&self, params: &CircuitParams) -> Result<Matrices<BW6_761>, String> {
+        params.check_known(&["num_validators", "num_epochs", "maximum_non_signers"])?;
+        let num_validators = params.get("num_validators", 0usize)?;
+        let num_epochs = params.get("num_epochs", 0usize)?;
+        if num_validators == 0 || num_epochs == 0 {
+            return Err("num_validators and num_epochs should be given and positive".to_string());
+        }
+        let maximum_non_signers = params.get("maximum_non_signers", (num_validators - 1) / 3)?;
+        if maximum_non_signers >= num_validators {
+            return Err("maximum_non_signers should be less than num_validators".to_string());
+        }
+
+        // the circuit is synthesized without a witness, and with the hashes to
+        // the BLS12-377 curve computed outside of it
+        let circuit = ValidatorSetUpdate::<Bls12_377>::empty(num_validators, num_epochs, maximum_non_signers, None);
+        Matrices::from_circuit(circuit).map_err(|e| e.to_string())
+    }
+
+    fn random_assignment(&self, _: &CircuitParams, _: &mut dyn RngCore) -> Result<Vec<Fr>, String> {
+        Err("the epoch circuit needs signed epoch blocks as its witness, it cannot be proven at random".to_string())
+    }
+}
+
+/// Returns the circuits which can be selected with `--circuit`
+pub fn circuit_providers() -> Vec<Box<dyn CircuitProvider<BW6_761>>> {
+    vec![Box::new(SquareRootProvider), Box::new(EpochProvider)]
+}
+
+/// Synthesizes the registered circuit with the given name
+pub fn synthesize_circuit(name: &str, params: &CircuitParams) -> Result<Matrices<BW6_761>, String> {
+    find_provider(name)?.synthesize(params)
+}
+
+/// Returns a random satisfying assignment of the registered circuit with the given name
+pub fn random_assignment(name: &str, params: &CircuitParams, rng: &mut dyn RngCore) -> Result<Vec<Fr>, String> {
+    find_provider(name)?.random_assignment(params, rng)
+}
+
+fn find_provider(name: &str) -> Result<Box<dyn CircuitProvider<BW6_761>>, String> {
+    let mut providers = circuit_providers();
+    match providers.iter().position(|provider| provider.name() == name) {
+        Some(index) => Ok(providers.swap_remove(index)),
+        None => Err(format!(
+            "unknown circuit `{}`. Available circuits: {}",
+            name,
+            providers
+                .iter()
+                .map(|provider| format!("{} ({})", provider.name(), provider.description()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// Where the matrices of a circuit come from: a circuit file, or a registered
+/// circuit which is synthesized in memory
+pub enum CircuitSource {
+    File(String),
+    Provided { name: String, matrices: Matrices<BW6_761> },
+}
+
+impl CircuitSource {
+    /// Returns the circuit files, or the registered circuit if a name is given
+    pub fn parse(circuit_filenames: &str, name: &str, params: &str) -> Result<Vec<Self>, String> {
+        if name.is_empty() {
+            return Ok(split_filenames(circuit_filenames)
+                .into_iter()
+                .map(CircuitSource::File)
+                .collect());
+        }
+        let matrices = synthesize_circuit(name, &CircuitParams::parse(params)?)?;
+        info!(
+            "Synthesized circuit {} with {} constraints",
+            name, matrices.num_constraints
+        );
+        Ok(vec![CircuitSource::Provided {
+            name: name.to_string(),
+            matrices,
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_circuit_params() {
+        let params = CircuitParams::parse("num_validators=4, num_epochs = 2").unwrap();
+        assert_eq!(params.get("num_validators", 0usize), Ok(4));
+        assert_eq!(params.get("num_epochs", 0usize), Ok(2));
+        assert_eq!(params.get("missing", 7usize), Ok(7));
+        assert!(params.check_known(&["num_validators", "num_epochs"]).is_ok());
+        assert!(params.check_known(&["num_validators"]).is_err());
+
+        assert_eq!(CircuitParams::parse("").unwrap(), CircuitParams::default());
+        assert!(CircuitParams::parse("num_validators").is_err());
+        assert!(CircuitParams::parse("a=1,a=2").is_err());
+        assert!(CircuitParams::parse("a=x").unwrap().get("a", 0usize).is_err());
+    }
+
+    #[test]
+    fn synthesize_square_root() {
+        let matrices = synthesize_circuit("square-root", &CircuitParams::default()).unwrap();
+        assert_eq!(
+            matrices,
+            Matrices::from_circuit(SquareRootCircuit::<BW6_761>(None)).unwrap()
+        );
+
+        let params = CircuitParams::parse("size=3").unwrap();
+        assert!(synthesize_circuit("square-root", &params).is_err());
+        assert!(synthesize_circuit("unknown", &CircuitParams::default()).is_err());
+
+        let rng = &mut rand::thread_rng();
+        let assignment = random_assignment("square-root", &CircuitParams::default(), rng).unwrap();
+        assert_eq!(matrices.unsatisfied_constraint(&assignment), None);
+    }
+
+    #[test]
+    fn epoch_params_are_checked() {
+        let synthesize = |params: &str| synthesize_circuit("epoch", &CircuitParams::parse(params).unwrap());
+        assert!(synthesize("").is_err());
+        assert!(synthesize("num_validators=4").is_err());
+        assert!(synthesize("num_validators=4,num_epochs=1,maximum_non_signers=4").is_err());
+        assert!(synthesize("num_validators=4,num_epochs=1,size=3").is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use setup_utils::derive_rng_from_seed;
+
+use rand::Rng;
+
+/// Splits a comma-separated list of file names, as accepted by the commands
+/// which can handle several circuits at once
+pub fn split_filenames(filenames: &str) -> Vec<String> {
+    filenames
+        .split(',')
+        .map(|filename| filename.trim().to_string())
+        .filter(|filename| !filename.is_empty())
+        .collect()
+}
+
+/// Returns the file name of an optional option, which is `None` when empty
+pub fn optional_filename(filename: &str) -> Option<&str> {
+    Some(filename.trim()).filter(|filename| !filename.is_empty())
+}
+
+/// Returns the name of a file belonging to the `index`-th of `count` circuits.
+/// With a single circuit the name is left unchanged, so that single circuit
+/// ceremonies keep their file layout.
+pub fn circuit_filename(filename: &str, index: usize, count: usize) -> String {
+    if count == 1 {
+        filename.to_string()
+    } else {
+        format!("{}.circuit{}", filename, index)
+    }
+}
+
+/// Derives the RNG used to contribute to the circuit with the given `cs_hash`
+/// from the seed. The `cs_hash` binds the circuit through the transcript header,
+/// so every chunk of a circuit gets the same delta, whichever run or order they
+/// are contributed to in, and every circuit gets an independent one.
+pub fn circuit_rng(seed: &[u8], cs_hash: &[u8; 64]) -> impl Rng {
+    let mut circuit_seed = seed.to_vec();
+    circuit_seed.extend_from_slice(cs_hash);
+    derive_rng_from_seed(&circuit_seed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phase2::{helpers::SquareRootCircuit, parameters::MPCParameters};
+    use setup_utils::BatchExpMode;
+
+    use algebra::{Bls12_377, PairingEngine};
+    use groth16::Parameters;
+
+    #[test]
+    fn circuit_filenames() {
+        assert_eq!(split_filenames("a.constraints"), vec!["a.constraints".to_string()]);
+        assert_eq!(
+            split_filenames("a.constraints, b.constraints,"),
+            vec!["a.constraints".to_string(), "b.constraints".to_string()]
+        );
+        assert_eq!(optional_filename(""), None);
+        assert_eq!(optional_filename("transcript"), Some("transcript"));
+        assert_eq!(circuit_filename("challenge", 0, 1), "challenge");
+        assert_eq!(circuit_filename("challenge", 1, 2), "challenge.circuit1");
+    }
+
+    #[test]
+    fn circuit_deltas() {
+        circuit_deltas_curve::<Bls12_377>()
+    }
+
+    fn circuit_deltas_curve<E: PairingEngine>() {
+        let rng = &mut rand::thread_rng();
+        let params = groth16::generate_random_parameters::<E, _, _>(SquareRootCircuit::<E>(None), rng).unwrap();
+        let chunk = |cs_hash: [u8; 64], h_query: &[E::G1Affine], l_query: &[E::G1Affine]| MPCParameters {
+            params: Parameters {
+                h_query: h_query.to_vec(),
+                l_query: l_query.to_vec(),
+                ..params.clone()
+            },
+            cs_hash,
+            contributions: vec![],
+            header: None,
+        };
+        let (h_half, l_half) = (params.h_query.len() / 2, params.l_query.len() / 2);
+        let chunks = vec![
+            chunk([1; 64], &params.h_query[..h_half], &params.l_query[..l_half]),
+            chunk([1; 64], &params.h_query[h_half..], &params.l_query[l_half..]),
+            chunk([2; 64], &params.h_query, &params.l_query),
+        ];
+        let seed = b"seed";
+        let deltas = chunks
+            .into_iter()
+            .map(|mut chunk| {
+                let mut rng = circuit_rng(seed, &chunk.cs_hash);
+                chunk.contribute(BatchExpMode::Auto, &mut rng).unwrap();
+                chunk.params.delta_g1
+            })
+            .collect::<Vec<_>>();
+
+        // the chunks of a circuit get the same delta, which `combine` requires
+        assert_eq!(deltas[0], deltas[1]);
+        // and another circuit gets an independent one
+        assert_ne!(deltas[0], deltas[2]);
+    }
+}
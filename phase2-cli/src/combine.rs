@@ -7,13 +7,20 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use tracing::info;
 
-use crate::{COMBINED_IS_COMPRESSED, COMPRESS_CONTRIBUTE_INPUT, COMPRESS_CONTRIBUTE_OUTPUT};
+use crate::{
+    print_transcript_header,
+    transcript_file::{read_chunk, read_transcript},
+    COMBINED_IS_COMPRESSED, COMPRESS_CONTRIBUTE_INPUT, COMPRESS_CONTRIBUTE_OUTPUT,
+};
 
+/// Combines the chunks listed in the response list. If a transcript file name
+/// is given, the chunks are in the detached format and are read against it.
 pub fn combine(
     initial_query_filename: &str,
     initial_full_filename: &str,
     response_list_filename: &str,
     combined_filename: &str,
+    transcript_filename: Option<&str>,
     combine_initial: bool,
 ) {
     info!("Combining phase 2");
@@ -30,6 +37,10 @@ pub fn combine(
         SubgroupCheckMode::Auto,
     )
     .expect("should have read full parameters");
+    // the initial parameters must be the ones the transcript was started from
+    full_parameters
+        .verify_cs_hash()
+        .expect("the initial parameters should match the transcript hash");
 
     let mut query_contents =
         std::io::Cursor::new(std::fs::read(initial_query_filename).expect("should have read initial query"));
@@ -47,12 +58,14 @@ pub fn combine(
     } else {
         COMPRESS_CONTRIBUTE_OUTPUT
     };
+    let transcript = transcript_filename.map(read_transcript);
     let mut all_parameters = vec![];
     for line in response_list_reader.lines() {
         let line = line.expect("should have read line");
         let contents = std::fs::read(line).expect("should have read response");
-        let parameters = MPCParameters::<BW6_761>::read_fast(
+        let parameters = read_chunk(
             contents.as_slice(),
+            transcript.as_ref(),
             parameters_compressed,
             CheckForCorrectness::No,
             false,
@@ -64,6 +77,10 @@ pub fn combine(
 
     let combined =
         MPCParameters::<BW6_761>::combine(&query_parameters, &all_parameters).expect("should have combined parameters");
+    assert!(
+        combined.header == full_parameters.header,
+        "the responses should have the same transcript header as the initial parameters"
+    );
 
     let contributions_hash = if combine_initial {
         verify_transcript(full_parameters.cs_hash, &combined.contributions).expect("should have verified successfully")
@@ -73,6 +90,8 @@ pub fn combine(
             .expect("should have verified successfully")
     };
 
+    print_transcript_header(&combined.header);
+
     info!("Contributions hashes:");
     for contribution_hash in contributions_hash {
         print_hash(&contribution_hash[..]);
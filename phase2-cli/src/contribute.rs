@@ -1,21 +1,27 @@
-use phase2::parameters::MPCParameters;
 use setup_utils::{calculate_hash, print_hash, BatchExpMode, CheckForCorrectness, SubgroupCheckMode};
 
-use algebra::BW6_761;
-
-use crate::{COMPRESS_CONTRIBUTE_INPUT, COMPRESS_CONTRIBUTE_OUTPUT};
-use rand::Rng;
+use crate::{
+    circuit_rng,
+    transcript_file::{read_chunk, read_transcript, write_chunk, write_round_transcript},
+    COMPRESS_CONTRIBUTE_INPUT, COMPRESS_CONTRIBUTE_OUTPUT,
+};
 use std::io::Write;
 use tracing::info;
 
+/// Contributes to a chunk. If the challenge and response transcript file names
+/// are given, the chunk files are in the detached format and the response
+/// transcript is shared by all chunks of the round: it is written by the first
+/// chunk and checked against by the others. The delta is derived from the seed
+/// and the `cs_hash` of the chunk with `circuit_rng`.
 pub fn contribute(
     challenge_filename: &str,
     challenge_hash_filename: &str,
     response_filename: &str,
     response_hash_filename: &str,
+    transcript_filenames: Option<(&str, &str)>,
     check_input_correctness: CheckForCorrectness,
     batch_exp_mode: BatchExpMode,
-    mut rng: impl Rng,
+    seed: &[u8],
 ) {
     info!("Contributing to phase 2");
 
@@ -29,21 +35,32 @@ pub fn contribute(
     info!("`challenge` file contains decompressed points and has a hash:");
     print_hash(&challenge_hash);
 
-    let mut parameters = MPCParameters::<BW6_761>::read_fast(
+    let challenge_transcript =
+        transcript_filenames.map(|(challenge_transcript_filename, _)| read_transcript(challenge_transcript_filename));
+    let mut parameters = read_chunk(
         challenge_contents.as_slice(),
+        challenge_transcript.as_ref(),
         COMPRESS_CONTRIBUTE_INPUT,
         check_input_correctness,
         false,
         SubgroupCheckMode::Auto,
     )
     .expect("should have read parameters");
-    parameters
+    let mut rng = circuit_rng(seed, &parameters.cs_hash);
+    let contribution_hash = parameters
         .contribute(batch_exp_mode, &mut rng)
         .expect("should have successfully contributed");
+    if let Some((_, response_transcript_filename)) = transcript_filenames {
+        write_round_transcript(response_transcript_filename, &parameters.transcript());
+    }
     let mut serialized_response = vec![];
-    parameters
-        .write(&mut serialized_response, COMPRESS_CONTRIBUTE_OUTPUT)
-        .expect("should have written input");
+    write_chunk(
+        &mut serialized_response,
+        &parameters,
+        transcript_filenames.is_some(),
+        COMPRESS_CONTRIBUTE_OUTPUT,
+    )
+    .expect("should have written input");
     std::fs::File::create(response_filename)
         .expect("unable to create response")
         .write_all(&serialized_response)
@@ -59,4 +76,9 @@ pub fn contribute(
               The BLAKE2b hash of response file is:\n"
     );
     print_hash(&response_hash);
+    info!(
+        "The hash of your contribution, which can be checked against the final transcript \
+         with the check-contribution command, is:"
+    );
+    print_hash(&contribution_hash);
 }
@@ -0,0 +1,75 @@
+use crate::{
+    ark::{to_ark_proving_key, to_ark_verifying_key, ArkPairingEngine},
+    ExportFormat,
+};
+use phase2::mmap_params::write_mmap_params;
+use setup_utils::{calculate_hash, print_hash};
+
+use algebra::CanonicalDeserialize;
+use ark_serialize::CanonicalSerialize as ArkCanonicalSerialize;
+use groth16::Parameters;
+
+use memmap::MmapOptions;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+use tracing::info;
+
+/// Exports the combined parameters over the curve of `E`. The snarkjs `.zkey`
+/// format is not offered here: snarkjs only proves over bn128 and BLS12-381,
+/// which the ceremony does not run on, so `zkey::write_zkey` is a library API.
+pub fn export<E: ArkPairingEngine>(params_filename: &str, output_filename: &str, format: ExportFormat) {
+    info!("Exporting phase 2 parameters");
+
+    match format {
+        ExportFormat::Ark => {
+            let params_contents = std::fs::read(params_filename).expect("should have read combined parameters");
+            let params = Parameters::<E>::deserialize_uncompressed_unchecked(params_contents.as_slice())
+                .expect("should have deserialized combined parameters");
+
+            let proving_key = to_ark_proving_key(&params).expect("should have converted the proving key");
+            let verifying_key = to_ark_verifying_key(&params.vk).expect("should have converted the verifying key");
+
+            let mut serialized_proving_key = vec![];
+            proving_key
+                .serialize_uncompressed(&mut serialized_proving_key)
+                .expect("should have serialized the proving key");
+            std::fs::write(format!("{}.pk", output_filename), &serialized_proving_key)
+                .expect("should have written the proving key");
+
+            let mut serialized_verifying_key = vec![];
+            verifying_key
+                .serialize(&mut serialized_verifying_key)
+                .expect("should have serialized the verifying key");
+            std::fs::write(format!("{}.vk", output_filename), &serialized_verifying_key)
+                .expect("should have written the verifying key");
+
+            info!("The BLAKE2b hash of the arkworks proving key is:");
+            print_hash(&calculate_hash(&serialized_proving_key));
+            info!("The BLAKE2b hash of the arkworks verifying key is:");
+            print_hash(&calculate_hash(&serialized_verifying_key));
+        }
+        ExportFormat::Mmap => {
+            let params_contents = std::fs::read(params_filename).expect("should have read combined parameters");
+            let params = Parameters::<E>::deserialize_uncompressed_unchecked(params_contents.as_slice())
+                .expect("should have deserialized combined parameters");
+            drop(params_contents);
+
+            // the layout is as large as the uncompressed parameters, so it is written directly to the file
+            let mmap_filename = format!("{}.mmap", output_filename);
+            let mut writer = BufWriter::new(File::create(&mmap_filename).expect("should have created the mmap file"));
+            write_mmap_params(&params, &mut writer).expect("should have written the mmap file");
+            writer.flush().expect("should have written the mmap file");
+
+            let reader = File::open(&mmap_filename).expect("should have opened the mmap file");
+            let readable_map = unsafe {
+                MmapOptions::new()
+                    .map(&reader)
+                    .expect("unable to create a memory map for input")
+            };
+            info!("The BLAKE2b hash of the memory-mappable parameters is:");
+            print_hash(&calculate_hash(&readable_map));
+        }
+    }
+}
@@ -0,0 +1,338 @@
+use crate::VkFormat;
+
+use algebra::{
+    bls12_377, bls12_381, AffineCurve, CanonicalDeserialize, CanonicalSerialize, ConstantSerializedSize, Field,
+    PairingEngine,
+};
+use groth16::{Parameters, VerifyingKey};
+
+use serde_json::json;
+use tracing::info;
+
+/// The EVM precompiles which are needed by the generated Solidity verifier
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Precompiles {
+    pub name: &'static str,
+    pub g1_add: u8,
+    pub g1_msm: u8,
+    pub pairing: u8,
+}
+
+/// BLS12-381 precompiles as specified in EIP-2537
+pub const EIP2537_BLS12_381: Precompiles = Precompiles {
+    name: "BLS12-381",
+    g1_add: 0x0b,
+    g1_msm: 0x0c,
+    pairing: 0x0f,
+};
+
+/// BLS12-377 precompiles of the Celo blockchain, which use the EIP-2537 encoding
+pub const CELO_BLS12_377: Precompiles = Precompiles {
+    name: "BLS12-377",
+    g1_add: 0xea,
+    g1_msm: 0xe8,
+    pairing: 0xe4,
+};
+
+/// Size of a base field element when encoded for the precompiles
+const PRECOMPILE_FIELD_SIZE: usize = 64;
+
+const VERIFIER_TEMPLATE: &str = include_str!("templates/verifier.sol");
+
+fn is_curve<E: PairingEngine, F: Field>() -> bool {
+    E::Fq::characteristic() == F::characteristic()
+}
+
+/// Returns the EVM precompiles for the curve of `E`, or an error if there are none
+pub fn evm_precompiles<E: PairingEngine>() -> Result<Precompiles, String> {
+    if is_curve::<E, bls12_381::Fq>() {
+        Ok(EIP2537_BLS12_381)
+    } else if is_curve::<E, bls12_377::Fq>() {
+        Ok(CELO_BLS12_377)
+    } else {
+        Err("there are EVM precompiles only for BLS12-381 and BLS12-377, \
+             a Solidity verifier cannot be generated for this curve"
+            .to_string())
+    }
+}
+
+/// Splits a point into the big-endian encodings of its base field elements, in
+/// the order `[x_0, .., x_{k-1}, y_0, .., y_{k-1}]` where `k` is the degree of
+/// the extension the point is defined over.
+pub fn point_limbs<C: AffineCurve>(point: &C, base_field_size: usize) -> Vec<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(C::UNCOMPRESSED_SIZE);
+    point
+        .serialize_uncompressed(&mut bytes)
+        .expect("should have serialized point");
+    if point.is_zero() {
+        // the point at infinity is encoded as all zeroes
+        bytes.iter_mut().for_each(|b| *b = 0);
+    }
+    // the infinity and sign flags are kept in the top bits of the last byte
+    let last = bytes.len() - 1;
+    bytes[last] &= 0x3f;
+
+    bytes
+        .chunks(base_field_size)
+        .map(|limb| limb.iter().rev().cloned().collect())
+        .collect()
+}
+
+fn hex_point<C: AffineCurve>(point: &C, base_field_size: usize) -> serde_json::Value {
+    let limbs = point_limbs(point, base_field_size)
+        .iter()
+        .map(|limb| format!("0x{}", hex::encode(limb)))
+        .collect::<Vec<_>>();
+    let (x, y) = limbs.split_at(limbs.len() / 2);
+    if x.len() == 1 {
+        json!({ "x": x[0], "y": y[0] })
+    } else {
+        json!({ "x": x, "y": y })
+    }
+}
+
+/// Returns the verifying key as JSON, with coordinates as big-endian hex strings
+pub fn vk_to_json<E: PairingEngine>(vk: &VerifyingKey<E>) -> serde_json::Value {
+    let base_field_size = E::G1Affine::UNCOMPRESSED_SIZE / 2;
+    json!({
+        "alpha_g1": hex_point(&vk.alpha_g1, base_field_size),
+        "beta_g2": hex_point(&vk.beta_g2, base_field_size),
+        "gamma_g2": hex_point(&vk.gamma_g2, base_field_size),
+        "delta_g2": hex_point(&vk.delta_g2, base_field_size),
+        "gamma_abc_g1": vk
+            .gamma_abc_g1
+            .iter()
+            .map(|p| hex_point(p, base_field_size))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Reads a point written by `hex_point`, checking that it is on the curve and
+/// in the prime order subgroup
+fn point_from_hex<C: AffineCurve>(value: &serde_json::Value) -> Result<C, String> {
+    let coordinate = |name: &str| -> Result<Vec<&str>, String> {
+        match &value[name] {
+            serde_json::Value::String(limb) => Ok(vec![limb.as_str()]),
+            serde_json::Value::Array(limbs) => limbs
+                .iter()
+                .map(|limb| {
+                    limb.as_str()
+                        .ok_or_else(|| format!("`{}` should contain hex strings", name))
+                })
+                .collect(),
+            _ => Err(format!("missing coordinate `{}`", name)),
+        }
+    };
+    let mut bytes = Vec::with_capacity(C::UNCOMPRESSED_SIZE);
+    for limb in coordinate("x")?.into_iter().chain(coordinate("y")?) {
+        let limb = hex::decode(limb.trim_start_matches("0x")).map_err(|e| e.to_string())?;
+        bytes.extend(limb.into_iter().rev());
+    }
+    if bytes.iter().all(|b| *b == 0) {
+        return Ok(C::zero());
+    }
+    C::deserialize_uncompressed(&bytes[..]).map_err(|e| e.to_string())
+}
+
+/// Reads a verifying key written by `vk_to_json`
+pub fn vk_from_json<E: PairingEngine>(value: &serde_json::Value) -> Result<VerifyingKey<E>, String> {
+    let gamma_abc_g1 = value["gamma_abc_g1"]
+        .as_array()
+        .ok_or_else(|| "missing `gamma_abc_g1`".to_string())?
+        .iter()
+        .map(point_from_hex)
+        .collect::<Result<_, _>>()?;
+    Ok(VerifyingKey {
+        alpha_g1: point_from_hex(&value["alpha_g1"])?,
+        beta_g2: point_from_hex(&value["beta_g2"])?,
+        gamma_g2: point_from_hex(&value["gamma_g2"])?,
+        delta_g2: point_from_hex(&value["delta_g2"])?,
+        gamma_abc_g1,
+    })
+}
+
+/// Encodes a point as expected by the EIP-2537 style precompiles, where each base
+/// field element is left-padded to 64 bytes
+pub fn precompile_encoding<C: AffineCurve>(point: &C, base_field_size: usize) -> Vec<u8> {
+    assert!(
+        base_field_size <= PRECOMPILE_FIELD_SIZE,
+        "base field elements do not fit the precompile encoding"
+    );
+    let mut encoded = vec![];
+    for limb in point_limbs(point, base_field_size) {
+        encoded.extend(std::iter::repeat(0).take(PRECOMPILE_FIELD_SIZE - limb.len()));
+        encoded.extend(limb);
+    }
+    encoded
+}
+
+/// Returns `alpha_g1 || -beta_g2 || -gamma_g2 || -delta_g2` in the precompile encoding.
+/// With these, a proof is checked with a single pairing precompile call for
+/// `e(A, B) * e(alpha, -beta) * e(vk_x, -gamma) * e(C, -delta) == 1`.
+pub fn prepared_vk_bytes<E: PairingEngine>(vk: &VerifyingKey<E>) -> Vec<u8> {
+    let base_field_size = E::G1Affine::UNCOMPRESSED_SIZE / 2;
+    let mut prepared = precompile_encoding(&vk.alpha_g1, base_field_size);
+    prepared.extend(precompile_encoding(&-vk.beta_g2, base_field_size));
+    prepared.extend(precompile_encoding(&-vk.gamma_g2, base_field_size));
+    prepared.extend(precompile_encoding(&-vk.delta_g2, base_field_size));
+    prepared
+}
+
+/// Fills in the Solidity verifier template for the provided verifying key
+pub fn solidity_verifier<E: PairingEngine>(vk: &VerifyingKey<E>, precompiles: &Precompiles) -> String {
+    let base_field_size = E::G1Affine::UNCOMPRESSED_SIZE / 2;
+    let gamma_abc = vk
+        .gamma_abc_g1
+        .iter()
+        .flat_map(|p| precompile_encoding(p, base_field_size))
+        .collect::<Vec<_>>();
+    VERIFIER_TEMPLATE
+        .replace("{{CURVE}}", precompiles.name)
+        .replace("{{G1_ADD}}", &format!("0x{:02x}", precompiles.g1_add))
+        .replace("{{G1_MSM}}", &format!("0x{:02x}", precompiles.g1_msm))
+        .replace("{{PAIRING}}", &format!("0x{:02x}", precompiles.pairing))
+        .replace("{{NUM_INPUTS}}", &(vk.gamma_abc_g1.len() - 1).to_string())
+        .replace("{{PREPARED_VK}}", &hex::encode(prepared_vk_bytes(vk)))
+        .replace("{{GAMMA_ABC}}", &hex::encode(gamma_abc))
+}
+
+/// Returns an error unless `E` is BLS12-377, whose precompiles on Celo the prepared
+/// verifying key is for
+pub fn check_prepared_vk_curve<E: PairingEngine>() -> Result<(), String> {
+    if is_curve::<E, bls12_377::Fq>() {
+        Ok(())
+    } else {
+        Err("the prepared verifying key is for Celo's BLS12-377 precompiles, \
+             it cannot be generated for this curve"
+            .to_string())
+    }
+}
+
+fn read_verifying_key<E: PairingEngine>(params_filename: &str) -> VerifyingKey<E> {
+    let params_contents = std::fs::read(params_filename).expect("should have read combined parameters");
+    Parameters::<E>::deserialize_uncompressed_unchecked(params_contents.as_slice())
+        .expect("should have deserialized combined parameters")
+        .vk
+}
+
+/// Exports the verifying key of the combined parameters over the curve of `E`.
+/// The Solidity verifier and the prepared bytes are only available for curves
+/// with precompiles, and an error is returned for other curves.
+pub fn export_vk<E: PairingEngine>(
+    params_filename: &str,
+    output_filename: &str,
+    format: VkFormat,
+) -> Result<(), String> {
+    info!("Exporting phase 2 verifying key");
+
+    match format {
+        VkFormat::Json => {
+            let vk = read_verifying_key::<E>(params_filename);
+            let vk_json = serde_json::to_string_pretty(&vk_to_json(&vk)).expect("should have encoded JSON");
+            std::fs::write(format!("{}.json", output_filename), vk_json)
+                .expect("should have written the verifying key");
+        }
+        VkFormat::Solidity => {
+            let precompiles = evm_precompiles::<E>()?;
+            let vk = read_verifying_key::<E>(params_filename);
+            std::fs::write(format!("{}.sol", output_filename), solidity_verifier(&vk, &precompiles))
+                .expect("should have written the verifier");
+        }
+        VkFormat::Prepared => {
+            check_prepared_vk_curve::<E>()?;
+            let vk = read_verifying_key::<E>(params_filename);
+            std::fs::write(format!("{}.prepared", output_filename), prepared_vk_bytes(&vk))
+                .expect("should have written the prepared verifying key");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algebra::{Bls12_377, Bls12_381, ProjectiveCurve, UniformRand, Zero, BW6_761};
+
+    #[test]
+    fn limbs_round_trip() {
+        limbs_round_trip_curve::<Bls12_377>();
+        limbs_round_trip_curve::<BW6_761>();
+    }
+
+    fn limbs_round_trip_curve<E: PairingEngine>() {
+        let rng = &mut rand::thread_rng();
+        let base_field_size = E::G1Affine::UNCOMPRESSED_SIZE / 2;
+        let g1 = E::G1Projective::rand(rng).into_affine();
+        let g2 = E::G2Projective::rand(rng).into_affine();
+        assert_eq!(point_limbs(&g1, base_field_size).len(), 2);
+        assert_eq!(
+            point_limbs(&g2, base_field_size).len(),
+            E::G2Affine::UNCOMPRESSED_SIZE / base_field_size
+        );
+
+        // reversing the limbs back to little-endian gives the Zexe encoding
+        let bytes = point_limbs(&g1, base_field_size)
+            .into_iter()
+            .flat_map(|limb| limb.into_iter().rev())
+            .collect::<Vec<_>>();
+        let deserialized = E::G1Affine::deserialize_uncompressed(&bytes[..]).unwrap();
+        assert_eq!(deserialized, g1);
+    }
+
+    #[test]
+    fn vk_json_round_trip() {
+        vk_json_round_trip_curve::<Bls12_377>();
+        vk_json_round_trip_curve::<BW6_761>();
+    }
+
+    fn vk_json_round_trip_curve<E: PairingEngine>() {
+        let rng = &mut rand::thread_rng();
+        let vk = VerifyingKey::<E> {
+            alpha_g1: E::G1Projective::rand(rng).into_affine(),
+            beta_g2: E::G2Projective::rand(rng).into_affine(),
+            gamma_g2: E::G2Projective::rand(rng).into_affine(),
+            delta_g2: E::G2Projective::rand(rng).into_affine(),
+            gamma_abc_g1: vec![E::G1Projective::rand(rng).into_affine(), E::G1Affine::zero()],
+        };
+        let json = serde_json::to_string(&vk_to_json(&vk)).unwrap();
+        let read = vk_from_json::<E>(&serde_json::from_str(&json).unwrap()).unwrap();
+        assert_eq!(read, vk);
+
+        let mut tampered = vk_to_json(&vk);
+        tampered["alpha_g1"]["x"] = tampered["alpha_g1"]["y"].clone();
+        assert!(vk_from_json::<E>(&tampered).is_err());
+    }
+
+    #[test]
+    fn precompile_encoding_size() {
+        let base_field_size = <Bls12_377 as PairingEngine>::G1Affine::UNCOMPRESSED_SIZE / 2;
+        let g1 = <Bls12_377 as PairingEngine>::G1Affine::prime_subgroup_generator();
+        let g2 = <Bls12_377 as PairingEngine>::G2Affine::prime_subgroup_generator();
+        assert_eq!(precompile_encoding(&g1, base_field_size).len(), 128);
+        assert_eq!(precompile_encoding(&g2, base_field_size).len(), 256);
+    }
+
+    #[test]
+    fn precompiles_are_gated_by_curve() {
+        assert_eq!(evm_precompiles::<Bls12_381>(), Ok(EIP2537_BLS12_381));
+        assert_eq!(evm_precompiles::<Bls12_377>(), Ok(CELO_BLS12_377));
+        assert!(evm_precompiles::<BW6_761>().is_err());
+        assert!(check_prepared_vk_curve::<Bls12_377>().is_ok());
+        assert!(check_prepared_vk_curve::<Bls12_381>().is_err());
+        assert!(check_prepared_vk_curve::<BW6_761>().is_err());
+
+        let rng = &mut rand::thread_rng();
+        let vk = VerifyingKey::<Bls12_377> {
+            alpha_g1: <Bls12_377 as PairingEngine>::G1Projective::rand(rng).into_affine(),
+            beta_g2: <Bls12_377 as PairingEngine>::G2Projective::rand(rng).into_affine(),
+            gamma_g2: <Bls12_377 as PairingEngine>::G2Projective::rand(rng).into_affine(),
+            delta_g2: <Bls12_377 as PairingEngine>::G2Projective::rand(rng).into_affine(),
+            gamma_abc_g1: vec![<Bls12_377 as PairingEngine>::G1Projective::rand(rng).into_affine(); 3],
+        };
+        assert_eq!(prepared_vk_bytes(&vk).len(), 128 + 3 * 256);
+        let verifier = solidity_verifier(&vk, &CELO_BLS12_377);
+        assert!(!verifier.contains("{{"));
+        assert!(verifier.contains("uint256 constant NUM_INPUTS = 2;"));
+        assert!(verifier.contains(&hex::encode(prepared_vk_bytes(&vk))));
+    }
+}
@@ -2,6 +2,15 @@
 #![cfg_attr(nightly, feature(doc_cfg, external_doc))]
 #![cfg_attr(nightly, doc(include = "../README.md"))]
 
+mod circuit_provider;
+pub use circuit_provider::{
+    circuit_providers, synthesize_circuit, CircuitParams, CircuitProvider, CircuitSource, EpochProvider,
+    SquareRootProvider,
+};
+
+mod circuits;
+pub use circuits::{circuit_filename, circuit_rng, optional_filename, split_filenames};
+
 mod new_challenge;
 pub use new_challenge::new_challenge;
 
@@ -9,27 +18,103 @@ mod contribute;
 pub use contribute::contribute;
 
 mod verify;
-pub use verify::verify;
+pub use verify::{verify, verify_round_transcript};
+
+mod verify_initial;
+pub use verify_initial::verify_initial;
 
 mod combine;
 pub use combine::combine;
 
+mod circuit_info;
+pub use circuit_info::circuit_info;
+
+mod check_contribution;
+pub use check_contribution::{check_contribution, parse_contribution_hash};
+
+mod transcript;
+pub use transcript::{transcript, transcript_to_json};
+
+mod transcript_file;
+
+mod smoke_test;
+pub use smoke_test::{check_parameters, prove_and_verify, smoke_test, smoke_test_provided_circuit};
+
+pub mod ark;
+
+mod export;
+pub use export::export;
+
+pub mod export_vk;
+pub use export_vk::export_vk;
+
+pub mod zkey;
+
 use setup_utils::converters::{ContributionMode, CurveKind, ProvingSystem};
 
 use gumdrop::Options;
+use phase2::transcript_header::TranscriptHeader;
 use setup_utils::{
     converters::{
         batch_exp_mode_from_str, contribution_mode_from_str, curve_from_str, proving_system_from_str,
         subgroup_check_mode_from_str,
     },
-    BatchExpMode, SubgroupCheckMode, UseCompression,
+    print_hash, BatchExpMode, SubgroupCheckMode, UseCompression,
 };
 use std::default::Default;
+use tracing::{info, warn};
 
 pub const COMPRESS_CONTRIBUTE_INPUT: UseCompression = UseCompression::No;
 pub const COMPRESS_CONTRIBUTE_OUTPUT: UseCompression = UseCompression::Yes;
 pub const COMBINED_IS_COMPRESSED: UseCompression = UseCompression::No;
 
+/// Prints the circuit and Phase 1 hashes the transcript is bound to
+fn print_transcript_header(header: &Option<TranscriptHeader>) {
+    match header {
+        Some(header) => {
+            info!("The transcript is bound to the circuit file with hash:");
+            print_hash(&header.circuit_hash);
+            info!("and to the Phase 1 file with hash:");
+            print_hash(&header.phase1_hash);
+        }
+        None => warn!("The transcript has no header, it is not bound to a circuit or a Phase 1 file"),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExportFormat {
+    Ark,
+    Mmap,
+}
+
+pub fn export_format_from_str(src: &str) -> Result<ExportFormat, String> {
+    let format = match src.to_lowercase().as_str() {
+        "ark" => ExportFormat::Ark,
+        "mmap" => ExportFormat::Mmap,
+        _ => return Err("unsupported export format. Currently supported: ark, mmap".to_string()),
+    };
+    Ok(format)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VkFormat {
+    Json,
+    Solidity,
+    Prepared,
+}
+
+pub fn vk_format_from_str(src: &str) -> Result<VkFormat, String> {
+    let format = match src.to_lowercase().as_str() {
+        "json" => VkFormat::Json,
+        "solidity" => VkFormat::Solidity,
+        "prepared" => VkFormat::Prepared,
+        _ => {
+            return Err("unsupported verifying key format. Currently supported: json, solidity, prepared".to_string());
+        }
+    };
+    Ok(format)
+}
+
 #[derive(Debug, Options, Clone)]
 pub struct Phase2Opts {
     help: bool,
@@ -92,6 +177,26 @@ pub enum Command {
     Verify(VerifyOpts),
     #[options(help = "combine the contributions and verify the final parameters")]
     Combine(CombineOpts),
+    #[options(
+        help = "re-derive the initial parameters from the circuit and phase 1 and compare them with the challenge"
+    )]
+    VerifyInitial(VerifyInitialOpts),
+    #[options(
+        help = "verify the contribution of a round to the transcript shared by its detached chunks, ahead of verifying them"
+    )]
+    VerifyTranscript(VerifyTranscriptOpts),
+    #[options(help = "check that a contribution is included in the final transcript")]
+    CheckContribution(CheckContributionOpts),
+    #[options(help = "export the contributions of the transcript and their verification results as JSON")]
+    Transcript(TranscriptOpts),
+    #[options(help = "create and verify a proof with the combined parameters for a circuit and a witness")]
+    SmokeTest(SmokeTestOpts),
+    #[options(help = "export the combined parameters to a format used by other provers")]
+    Export(ExportOpts),
+    #[options(help = "export the verifying key of the combined parameters")]
+    ExportVk(ExportVkOpts),
+    #[options(help = "validate a circuit file and report its size and unconstrained variables")]
+    CircuitInfo(CircuitInfoOpts),
 }
 
 // Options for the Contribute command
@@ -108,26 +213,52 @@ pub struct NewOpts {
     pub phase1_fname: String,
     #[options(help = "phase 1 powers")]
     pub phase1_powers: usize,
-    #[options(help = "number of validators")]
-    pub num_validators: usize,
-    #[options(help = "number of epochs")]
-    pub num_epochs: usize,
-    #[options(help = "circuit file name", default = "circuit.constraints")]
+    #[options(
+        help = "circuit file names, separated by commas. With several circuits, the file names of each circuit get a `.circuit{index}` suffix",
+        default = "circuit.constraints"
+    )]
     pub circuit_fname: String,
+    #[options(help = "a built-in circuit to synthesize, instead of reading circuit files")]
+    pub circuit: String,
+    #[options(help = "the parameters of the built-in circuit, as comma-separated key=value pairs")]
+    pub param: String,
+    #[options(
+        help = "if given, the chunks only refer to the contributions, which are written to this transcript file"
+    )]
+    pub transcript_fname: String,
+    #[options(
+        help = "if given, the memory in MiB used for the matrices of a circuit, which are then transposed through a spill file instead of being loaded entirely"
+    )]
+    pub memory_budget: usize,
 }
 
 // Options for the Contribute command
 #[derive(Debug, Options, Clone)]
 pub struct ContributeOpts {
     help: bool,
-    #[options(help = "the provided challenge file", default = "challenge")]
+    #[options(
+        help = "the provided challenge file, or several separated by commas to contribute to several circuits",
+        default = "challenge"
+    )]
     pub challenge_fname: String,
-    #[options(help = "the provided challenge file hash", default = "challenge.hash")]
+    #[options(
+        help = "the provided challenge file hash, one per challenge",
+        default = "challenge.hash"
+    )]
     pub challenge_hash_fname: String,
-    #[options(help = "the response file which will be generated")]
+    #[options(help = "the response file which will be generated, one per challenge")]
     pub response_fname: String,
-    #[options(help = "the response file which will be generated hash", default = "response.hash")]
+    #[options(
+        help = "the response file which will be generated hash, one per challenge",
+        default = "response.hash"
+    )]
     pub response_hash_fname: String,
+    #[options(help = "the transcript file of the round, shared by its detached chunks, one per challenge")]
+    pub challenge_transcript_fname: String,
+    #[options(
+        help = "the transcript file of the responses of the round, which is generated by the first chunk and shared by the others, one per challenge"
+    )]
+    pub response_transcript_fname: String,
     #[options(
         help = "the beacon hash to be used if running a beacon contribution",
         default = "0000000000000000000a558a61ddc8ee4e488d647a747fe4dcc362fe2026c620"
@@ -153,6 +284,44 @@ pub struct VerifyOpts {
     pub new_challenge_fname: String,
     #[options(help = "the new challenge file hash", default = "response.verified.hash")]
     pub new_challenge_hash_fname: String,
+    #[options(help = "the transcript file of the round, if the chunks are detached")]
+    pub challenge_transcript_fname: String,
+    #[options(
+        help = "the transcript file of the responses of the round, which the new challenge refers to, if the chunks are detached"
+    )]
+    pub response_transcript_fname: String,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct VerifyTranscriptOpts {
+    help: bool,
+    #[options(help = "the transcript file of the round")]
+    pub challenge_transcript_fname: String,
+    #[options(help = "the transcript file of the responses of the round")]
+    pub response_transcript_fname: String,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct VerifyInitialOpts {
+    help: bool,
+    #[options(help = "the published challenge file name", default = "challenge")]
+    pub challenge_fname: String,
+    #[options(help = "phase 1 file name", default = "phase1")]
+    pub phase1_fname: String,
+    #[options(help = "phase 1 powers")]
+    pub phase1_powers: usize,
+    #[options(help = "circuit file name", default = "circuit.constraints")]
+    pub circuit_fname: String,
+    #[options(help = "a built-in circuit to synthesize, instead of reading the circuit file")]
+    pub circuit: String,
+    #[options(help = "the parameters of the built-in circuit, as comma-separated key=value pairs")]
+    pub param: String,
+    #[options(help = "the transcript file, if the chunks are detached")]
+    pub transcript_fname: String,
+    #[options(
+        help = "if given, the memory in MiB used for the matrices of the circuit, which are then transposed through a spill file instead of being loaded entirely"
+    )]
+    pub memory_budget: usize,
 }
 
 #[derive(Debug, Options, Clone)]
@@ -166,4 +335,80 @@ pub struct CombineOpts {
     pub response_list_fname: String,
     #[options(help = "the combined response file", default = "combined")]
     pub combined_fname: String,
+    #[options(help = "the transcript file, if the responses are detached")]
+    pub transcript_fname: String,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct CheckContributionOpts {
+    help: bool,
+    #[options(help = "the combined file", default = "combined")]
+    pub combined_fname: String,
+    #[options(help = "the hash of the contribution, as printed by the contribute command")]
+    pub hash: String,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct TranscriptOpts {
+    help: bool,
+    #[options(help = "the combined file", default = "combined")]
+    pub combined_fname: String,
+    #[options(help = "the JSON file which will be generated", default = "transcript.json")]
+    pub output_fname: String,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct SmokeTestOpts {
+    help: bool,
+    #[options(help = "the combined parameters file", default = "combined.params")]
+    pub params_fname: String,
+    #[options(help = "the verifying key file written by export-vk", default = "vk.json")]
+    pub vk_fname: String,
+    #[options(help = "circuit file name", default = "circuit.constraints")]
+    pub circuit_fname: String,
+    #[options(help = "the witness file, in the snarkjs wtns format", default = "witness.wtns")]
+    pub witness_fname: String,
+    #[options(
+        help = "a built-in circuit to prove with a random satisfying witness, instead of reading the circuit and witness files"
+    )]
+    pub circuit: String,
+    #[options(help = "the parameters of the built-in circuit, as comma-separated key=value pairs")]
+    pub param: String,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct ExportOpts {
+    help: bool,
+    #[options(help = "the combined parameters file", default = "combined.params")]
+    pub params_fname: String,
+    #[options(help = "the prefix of the exported files", default = "combined")]
+    pub output_fname: String,
+    #[options(
+        help = "the format to export to (ark, or mmap, a layout which provers can memory-map)",
+        default = "ark",
+        parse(try_from_str = "export_format_from_str")
+    )]
+    pub format: ExportFormat,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct ExportVkOpts {
+    help: bool,
+    #[options(help = "the combined parameters file", default = "combined.params")]
+    pub params_fname: String,
+    #[options(help = "the prefix of the exported verifying key file", default = "vk")]
+    pub output_fname: String,
+    #[options(
+        help = "the format to export to (json, solidity for BLS12-381 and BLS12-377, or prepared precompile bytes for BLS12-377)",
+        default = "json",
+        parse(try_from_str = "vk_format_from_str")
+    )]
+    pub format: VkFormat,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct CircuitInfoOpts {
+    help: bool,
+    #[options(help = "circuit file name", default = "circuit.constraints")]
+    pub circuit_fname: String,
 }
@@ -1,57 +1,302 @@
-use phase2::load_circuit::Matrices;
+use phase2::load_circuit::{CircuitFile, Matrices};
 use phase2::parameters::MPCParameters;
-use setup_utils::{calculate_hash, print_hash, CheckForCorrectness, UseCompression};
+use phase2::transcript_header::TranscriptHeader;
+use phase2::transpose::TransposedMatrices;
+use setup_utils::{
+    calculate_hash, domain_size, print_hash, CheckForCorrectness, Groth16Params, Groth16ParamsView, UseCompression,
+    UseMixedRadix,
+};
 
-use crate::COMPRESS_CONTRIBUTE_INPUT;
-use algebra::{CanonicalDeserialize, CanonicalSerialize, BW6_761};
+use crate::{
+    circuit_filename, circuit_provider::CircuitSource, print_transcript_header, transcript_file::write_transcript,
+    COMPRESS_CONTRIBUTE_INPUT,
+};
+use algebra::{CanonicalSerialize, PairingEngine, BW6_761};
+use groth16::Parameters;
 use memmap::*;
-use std::{fs::File, fs::OpenOptions, io::Read, io::Write};
-use tracing::info;
+use std::{fs::OpenOptions, io::Write};
+use tracing::{info, warn};
 
-pub fn new_challenge(
-    challenge_filename: &str,
-    challenge_hash_filename: &str,
-    challenge_list_filename: &str,
+/// The initial full parameters, the queries and the chunks of a circuit
+pub(crate) type InitialParameters = (MPCParameters<BW6_761>, Parameters<BW6_761>, Vec<MPCParameters<BW6_761>>);
+
+/// Derives the initial full parameters, the queries and the chunks from the
+/// circuit and the prepared phase 1 file
+pub(crate) fn generate_initial_parameters(
+    circuit: CircuitSource,
+    phase1_filename: &str,
+    phase1_powers: usize,
     chunk_size: usize,
+    memory_budget: Option<usize>,
+) -> InitialParameters {
+    generate_initial_parameters_for_circuits(
+        vec![circuit],
+        phase1_filename,
+        phase1_powers,
+        chunk_size,
+        memory_budget,
+        |_, parameters| parameters,
+    )
+    .remove(0)
+}
+
+/// Derives the initial parameters of each circuit from the same prepared
+/// phase 1 file and passes them to `process` along with the index of the
+/// circuit, so that only the parameters of one circuit are held at a time.
+/// The phase 1 file is memory mapped and its Lagrange coefficients are decoded
+/// as the QAP of each circuit is evaluated. Every circuit gets its own
+/// `cs_hash` as the hash of its circuit file is bound into the transcript
+/// header. If a memory budget is given, the matrices of each circuit file are
+/// transposed through a spill file rather than loaded, see `new_from_spill`.
+/// The QAPs are evaluated over the domains `read_phase1_view` selects.
+pub(crate) fn generate_initial_parameters_for_circuits<T>(
+    circuits: Vec<CircuitSource>,
     phase1_filename: &str,
     phase1_powers: usize,
-    circuit_filename: &str,
-) -> usize {
-    info!("Generating phase 2");
+    chunk_size: usize,
+    memory_budget: Option<usize>,
+    mut process: impl FnMut(usize, InitialParameters) -> T,
+) -> Vec<T> {
+    let reader = OpenOptions::new()
+        .read(true)
+        .open(&phase1_filename)
+        .expect("unable open phase 1 file in this directory");
+    let phase1_readable_map = unsafe {
+        MmapOptions::new()
+            .map(&reader)
+            .expect("unable to create a memory map for input")
+    };
+    let phase1_hash = calculate_hash(&phase1_readable_map);
 
-    let mut file = File::open(circuit_filename).unwrap();
-    let mut buffer = Vec::<u8>::new();
-    file.read_to_end(&mut buffer).unwrap();
-    let m = Matrices::<BW6_761>::deserialize(&*buffer).unwrap();
+    let mut results = vec![];
+    for (i, circuit) in circuits.into_iter().enumerate() {
+        let parameters = match circuit {
+            CircuitSource::File(circuit_filename) => {
+                let reader = OpenOptions::new()
+                    .read(true)
+                    .open(&circuit_filename)
+                    .expect("unable open circuit file in this directory");
+                let circuit_readable_map = unsafe {
+                    MmapOptions::new()
+                        .map(&reader)
+                        .expect("unable to create a memory map for circuit")
+                };
 
-    info!("Loaded circuit with {} constraints", m.num_constraints);
+                let header = TranscriptHeader::new(&calculate_hash(&circuit_readable_map), &phase1_hash);
+                print_transcript_header(&Some(header));
+                match memory_budget {
+                    Some(memory_budget) => new_from_spill(
+                        &circuit_filename,
+                        &circuit_readable_map,
+                        (&phase1_readable_map, phase1_powers),
+                        memory_budget,
+                        chunk_size,
+                        header,
+                    ),
+                    None => {
+                        let m =
+                            Matrices::<BW6_761>::read(&circuit_readable_map).expect("should have read a valid circuit");
+                        info!(
+                            "Loaded circuit {} with {} constraints",
+                            circuit_filename, m.num_constraints
+                        );
+                        new_from_matrices(m, (&phase1_readable_map, phase1_powers), chunk_size, header)
+                    }
+                }
+            }
+            CircuitSource::Provided { name, matrices } => {
+                // the header binds the circuit file the matrices would be written to
+                let mut serialized = vec![];
+                matrices.write(&mut serialized).expect("should have serialized circuit");
+                let header = TranscriptHeader::new(&calculate_hash(&serialized), &phase1_hash);
+                print_transcript_header(&Some(header));
+                if memory_budget.is_some() {
+                    info!(
+                        "Circuit {} is already in memory, so the memory budget does not apply",
+                        name
+                    );
+                }
+                new_from_matrices(matrices, (&phase1_readable_map, phase1_powers), chunk_size, header)
+            }
+        };
+        results.push(process(i, parameters));
+    }
+    results
+}
 
-    let phase2_size =
-        std::cmp::max(m.num_constraints, m.num_witness_variables + m.num_instance_variables).next_power_of_two();
+/// Derives the initial parameters of a circuit whose matrices are loaded
+fn new_from_matrices(
+    matrices: Matrices<BW6_761>,
+    (phase1_readable_map, phase1_powers): (&[u8], usize),
+    chunk_size: usize,
+    header: TranscriptHeader,
+) -> InitialParameters {
+    let params = read_phase1_view(
+        phase1_readable_map,
+        phase1_powers,
+        matrices.num_constraints,
+        matrices.num_instance_variables + matrices.num_witness_variables,
+    );
+    MPCParameters::<BW6_761>::new_chunked_from_view(matrices, &params, chunk_size, Some(header)).unwrap()
+}
 
-    let reader = OpenOptions::new()
+/// Derives the initial parameters of a circuit without loading its matrices.
+/// They are transposed into a `.spill` file next to the circuit file, which is
+/// then read one range of variables at a time, so that at most
+/// `memory_budget` bytes of the matrices are held in memory.
+fn new_from_spill(
+    circuit_filename: &str,
+    circuit_readable_map: &[u8],
+    (phase1_readable_map, phase1_powers): (&[u8], usize),
+    memory_budget: usize,
+    chunk_size: usize,
+    header: TranscriptHeader,
+) -> InitialParameters {
+    let circuit = CircuitFile::<BW6_761>::new(circuit_readable_map).expect("should have read a valid circuit");
+    info!(
+        "Opened circuit {} with {} constraints, transposing it with a memory budget of {} bytes",
+        circuit_filename, circuit.num_constraints, memory_budget
+    );
+
+    // a spill file left over by an interrupted run is overwritten
+    let spill_file = SpillFile(format!("{}.spill", circuit_filename));
+    let spill = OpenOptions::new()
         .read(true)
         .write(true)
-        .open(&phase1_filename)
-        .expect("unable open phase 1 file in this directory");
-    let mut phase1_readable_map = unsafe {
+        .create(true)
+        .truncate(true)
+        .open(&spill_file.0)
+        .expect("unable to create spill file in this directory");
+    spill
+        .set_len(TransposedMatrices::spill_size(&circuit) as u64)
+        .expect("unable to allocate spill file");
+    let mut spill_map = unsafe {
         MmapOptions::new()
-            .map_mut(&reader)
-            .expect("unable to create a memory map for input")
+            .map_mut(&spill)
+            .expect("unable to create a memory map for spill")
     };
 
-    let (full_mpc_parameters, query_parameters, all_mpc_parameters) =
-        MPCParameters::<BW6_761>::new_from_buffer_chunked(
-            m,
-            &mut phase1_readable_map,
-            UseCompression::No,
-            CheckForCorrectness::No,
-            1 << phase1_powers,
-            phase2_size,
-            chunk_size,
-        )
-        .unwrap();
+    let matrices = TransposedMatrices::new(&circuit, &mut spill_map).expect("should have transposed the circuit");
+    let params = read_phase1_view(
+        phase1_readable_map,
+        phase1_powers,
+        matrices.num_constraints,
+        matrices.num_instance_variables + matrices.num_witness_variables,
+    );
+    let parameters = MPCParameters::<BW6_761>::new_chunked_from_transposed(
+        &matrices,
+        &params,
+        memory_budget,
+        chunk_size,
+        Some(header),
+    )
+    .unwrap();
+
+    drop((matrices, params));
+    drop(spill_map);
+    drop(spill_file);
+    parameters
+}
+
+/// Removes the spill file when dropped, so that it is also cleaned up if the
+/// transposition or the evaluation panics
+struct SpillFile(String);
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.0) {
+            warn!("unable to remove spill file {}: {}", self.0, e);
+        }
+    }
+}
+
+/// Opens a lazy view of the prepared phase 1 file, for a circuit with the
+/// given number of constraints and variables. The file records the domain it
+/// was prepared for through its length. If that is a mixed-radix domain, the
+/// circuit is evaluated over the mixed-radix domain which fits it, which must
+/// be exactly that one. Otherwise it is evaluated over the radix-2 domain
+/// which fits it, within the prepared one. The view rejects a phase 1 file
+/// prepared for another domain.
+fn read_phase1_view(
+    phase1_readable_map: &[u8],
+    phase1_powers: usize,
+    num_constraints: usize,
+    num_variables: usize,
+) -> Groth16ParamsView<'_, BW6_761> {
+    let phase1_size = Groth16Params::<BW6_761>::prepared_domain_size(phase1_readable_map.len(), UseCompression::No)
+        .expect("phase 1 file should have been prepared with prepare_phase2");
+    assert!(
+        phase1_size <= 1 << phase1_powers,
+        "phase 1 was prepared for a domain of size {}, which needs more than {} powers",
+        phase1_size,
+        phase1_powers
+    );
+    let mixed_radix = if phase1_size.is_power_of_two() {
+        UseMixedRadix::No
+    } else {
+        info!("Phase 1 was prepared for a mixed-radix domain of size {}", phase1_size);
+        UseMixedRadix::Yes
+    };
+    let phase2_size =
+        domain_size::<<BW6_761 as PairingEngine>::Fr>(std::cmp::max(num_constraints, num_variables), mixed_radix);
+    Groth16ParamsView::<BW6_761>::new(
+        phase1_readable_map,
+        UseCompression::No,
+        CheckForCorrectness::No,
+        phase1_size,
+        phase2_size,
+    )
+    .expect("should have read phase 1")
+}
+
+/// Creates the initial parameters of each circuit. If a transcript file name
+/// is given, the chunks are written in the detached format along with the
+/// transcript file, while the `.full` file keeps its contributions. If a
+/// memory budget is given, the circuits are transposed through spill files.
+#[allow(clippy::too_many_arguments)]
+pub fn new_challenge(
+    challenge_filename: &str,
+    challenge_hash_filename: &str,
+    challenge_list_filename: &str,
+    transcript_filename: Option<&str>,
+    chunk_size: usize,
+    memory_budget: Option<usize>,
+    phase1_filename: &str,
+    phase1_powers: usize,
+    circuits: Vec<CircuitSource>,
+) -> Vec<usize> {
+    let num_circuits = circuits.len();
+    info!("Generating phase 2 for {} circuits", num_circuits);
 
+    generate_initial_parameters_for_circuits(
+        circuits,
+        phase1_filename,
+        phase1_powers,
+        chunk_size,
+        memory_budget,
+        |i, parameters| {
+            write_initial_parameters(
+                &circuit_filename(challenge_filename, i, num_circuits),
+                &circuit_filename(challenge_hash_filename, i, num_circuits),
+                &circuit_filename(challenge_list_filename, i, num_circuits),
+                transcript_filename
+                    .map(|transcript_filename| circuit_filename(transcript_filename, i, num_circuits))
+                    .as_deref(),
+                parameters,
+            )
+        },
+    )
+}
+
+/// Writes the `.full`, `.query` and chunk files of a circuit, returning the
+/// number of chunks
+fn write_initial_parameters(
+    challenge_filename: &str,
+    challenge_hash_filename: &str,
+    challenge_list_filename: &str,
+    transcript_filename: Option<&str>,
+    (full_mpc_parameters, query_parameters, all_mpc_parameters): InitialParameters,
+) -> usize {
     let mut serialized_mpc_parameters = vec![];
     full_mpc_parameters
         .write(&mut serialized_mpc_parameters, COMPRESS_CONTRIBUTE_INPUT)
@@ -81,11 +326,18 @@ pub fn new_challenge(
     let mut challenge_list_file =
         std::fs::File::create(challenge_list_filename).expect("unable to open new challenge list file");
 
+    // all chunks start from the same transcript as the full parameters
+    if let Some(transcript_filename) = transcript_filename {
+        write_transcript(transcript_filename, &full_mpc_parameters.transcript());
+    }
+
     for (i, chunk) in all_mpc_parameters.iter().enumerate() {
         let mut serialized_chunk = vec![];
-        chunk
-            .write(&mut serialized_chunk, COMPRESS_CONTRIBUTE_INPUT)
-            .expect("unable to write chunk");
+        match transcript_filename {
+            Some(_) => chunk.write_detached(&mut serialized_chunk, COMPRESS_CONTRIBUTE_INPUT),
+            None => chunk.write(&mut serialized_chunk, COMPRESS_CONTRIBUTE_INPUT),
+        }
+        .expect("unable to write chunk");
         std::fs::File::create(format!("{}.{}", challenge_filename, i))
             .expect("unable to open new challenge hash file")
             .write_all(&serialized_chunk)
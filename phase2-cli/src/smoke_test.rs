@@ -0,0 +1,292 @@
+use crate::{
+    circuit_provider::{random_assignment, synthesize_circuit, CircuitParams},
+    export_vk::vk_from_json,
+    verify_initial::list_indices,
+};
+use phase2::{
+    load_circuit::{read_wtns, Matrices, MatricesCircuit},
+    prover::create_random_proof,
+};
+use setup_utils::{dense_multiexp, same_ratio, UseMixedRadix};
+
+use algebra::{AffineCurve, CanonicalDeserialize, PairingEngine, PrimeField, ProjectiveCurve, UniformRand, BW6_761};
+use groth16::{prepare_verifying_key, verify_proof, Parameters, VerifyingKey};
+use r1cs_core::Matrix;
+
+use rand::Rng;
+use tracing::{error, info};
+
+/// Creates a proof for the circuit and its full assignment with the parameters,
+/// over their domain, and verifies it with the given verifying key. Parameters
+/// over a radix-2 domain are proven with Zexe's prover, as a verifier would.
+/// Zexe's prover cannot evaluate the QAP over a mixed-radix domain, so such
+/// parameters are proven with `phase2::prover` instead.
+pub fn prove_and_verify<E: PairingEngine, R: Rng>(
+    params: &Parameters<E>,
+    vk: &VerifyingKey<E>,
+    matrices: &Matrices<E>,
+    assignment: &[E::Fr],
+    rng: &mut R,
+) -> setup_utils::Result<bool> {
+    let domain_size = params.h_query.len() + 1;
+    let proof = if domain_size.is_power_of_two() {
+        let circuit = MatricesCircuit { matrices, assignment };
+        groth16::create_random_proof(circuit, params, rng)?
+    } else {
+        info!(
+            "The parameters are over a mixed-radix domain of size {}, proving with the phase 2 prover",
+            domain_size
+        );
+        create_random_proof(params, matrices, assignment, rng)?
+    };
+    let pvk = prepare_verifying_key(vk);
+    let public_inputs = &assignment[1..matrices.num_instance_variables];
+    Ok(verify_proof(&pvk, &proof, public_inputs)?)
+}
+
+/// Checks the parameters against the circuit, as far as this is possible
+/// without the toxic waste, and describes each inconsistency found. The H
+/// query may be over the radix-2 domain or over a smaller mixed-radix one,
+/// depending on the domain phase 1 was prepared for.
+pub fn check_parameters<E: PairingEngine>(params: &Parameters<E>, matrices: &Matrices<E>) -> Vec<String> {
+    let mut problems = vec![];
+    let num_variables = matrices.num_instance_variables + matrices.num_witness_variables;
+    // the domains `phase2 new` may evaluate the QAP over
+    let radix2_size = matrices.phase2_size(UseMixedRadix::No);
+    let mixed_radix_size = matrices.phase2_size(UseMixedRadix::Yes);
+
+    let mut check_length = |name: &str, length: usize, expected: usize| {
+        if length != expected {
+            problems.push(format!("{} has length {}, expected {}", name, length, expected));
+        }
+    };
+    check_length("a_query", params.a_query.len(), num_variables);
+    check_length("b_g1_query", params.b_g1_query.len(), num_variables);
+    check_length("b_g2_query", params.b_g2_query.len(), num_variables);
+    check_length(
+        "gamma_abc_g1",
+        params.vk.gamma_abc_g1.len(),
+        matrices.num_instance_variables,
+    );
+    check_length("l_query", params.l_query.len(), matrices.num_witness_variables);
+    if params.h_query.len() + 1 != mixed_radix_size {
+        check_length("h_query", params.h_query.len(), radix2_size - 1);
+    }
+
+    if !same_ratio_as_generators::<E>(&params.beta_g1, &params.vk.beta_g2) {
+        problems.push("beta_g1 and beta_g2 do not have the same discrete log".to_string());
+    }
+    if !same_ratio_as_generators::<E>(&params.delta_g1, &params.vk.delta_g2) {
+        problems.push("delta_g1 and delta_g2 do not have the same discrete log".to_string());
+    }
+
+    // a variable's element of a query vanishes iff its column of the matrix is empty
+    let used_in = |matrix: &Matrix<E::Fr>| {
+        let mut used = vec![false; num_variables];
+        for (_, variable) in matrix.iter().flatten() {
+            used[*variable] = true;
+        }
+        used
+    };
+    let used_in_a = used_in(&matrices.a);
+    let used_in_b = used_in(&matrices.b);
+    check_zeroes(&mut problems, "a_query", &params.a_query, &used_in_a);
+    check_zeroes(&mut problems, "b_g1_query", &params.b_g1_query, &used_in_b);
+    check_zeroes(&mut problems, "b_g2_query", &params.b_g2_query, &used_in_b);
+
+    if params.b_g1_query.len() == params.b_g2_query.len() {
+        check_b_queries(&mut problems, &params.b_g1_query, &params.b_g2_query);
+    }
+
+    problems
+}
+
+/// Checks that `e(g1, h) == e(g, g2)`, where `g` and `h` are the generators
+fn same_ratio_as_generators<E: PairingEngine>(g1: &E::G1Affine, g2: &E::G2Affine) -> bool {
+    same_ratio::<E>(
+        &(E::G1Affine::prime_subgroup_generator(), *g1),
+        &(E::G2Affine::prime_subgroup_generator(), *g2),
+    )
+}
+
+fn check_zeroes<C: AffineCurve>(problems: &mut Vec<String>, name: &str, query: &[C], used: &[bool]) {
+    let inconsistent = query
+        .iter()
+        .zip(used)
+        .enumerate()
+        .filter(|(_, (element, used))| element.is_zero() == **used)
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    if !inconsistent.is_empty() {
+        problems.push(format!(
+            "{} does not match the circuit at {} indices: {}",
+            name,
+            inconsistent.len(),
+            list_indices(&inconsistent)
+        ));
+    }
+}
+
+/// Checks that the B queries in G1 and G2 encode the same polynomials with a
+/// random linear combination, and locates the inconsistent elements if not
+fn check_b_queries<E: PairingEngine>(
+    problems: &mut Vec<String>,
+    b_g1_query: &[E::G1Affine],
+    b_g2_query: &[E::G2Affine],
+) {
+    let rng = &mut rand::thread_rng();
+    let randomness = (0..b_g1_query.len())
+        .map(|_| E::Fr::rand(rng).into_repr())
+        .collect::<Vec<_>>();
+    let b_g1 = dense_multiexp(b_g1_query, &randomness).into_affine();
+    let b_g2 = dense_multiexp(b_g2_query, &randomness).into_affine();
+    if same_ratio_as_generators::<E>(&b_g1, &b_g2) {
+        return;
+    }
+
+    let inconsistent = b_g1_query
+        .iter()
+        .zip(b_g2_query)
+        .enumerate()
+        .filter(|(_, (b_g1, b_g2))| !same_ratio_as_generators::<E>(b_g1, b_g2))
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    problems.push(format!(
+        "b_g1_query and b_g2_query differ at {} indices: {}",
+        inconsistent.len(),
+        list_indices(&inconsistent)
+    ));
+}
+
+/// Creates a proof with the combined parameters for a circuit file and a
+/// witness computed for it, and verifies it with the verifying key exported by
+/// `export-vk`. Returns the number of problems found.
+pub fn smoke_test(params_filename: &str, vk_filename: &str, circuit_filename: &str, witness_filename: &str) -> usize {
+    let circuit_contents = std::fs::read(circuit_filename).expect("should have read circuit");
+    let matrices = Matrices::<BW6_761>::read(&circuit_contents).expect("should have read a valid circuit");
+    let witness_contents = std::fs::read(witness_filename).expect("should have read witness");
+    let assignment = read_wtns::<BW6_761>(&witness_contents).expect("should have read a valid witness");
+    run_smoke_test(params_filename, vk_filename, &matrices, &assignment)
+}
+
+/// Like `smoke_test`, for a built-in circuit, which is proven with a random
+/// satisfying witness
+pub fn smoke_test_provided_circuit(params_filename: &str, vk_filename: &str, name: &str, params: &str) -> usize {
+    let params = match CircuitParams::parse(params) {
+        Ok(params) => params,
+        Err(e) => {
+            error!("{}", e);
+            return 1;
+        }
+    };
+    let rng = &mut rand::thread_rng();
+    let provided =
+        synthesize_circuit(name, &params).and_then(|matrices| Ok((matrices, random_assignment(name, &params, rng)?)));
+    match provided {
+        Ok((matrices, assignment)) => run_smoke_test(params_filename, vk_filename, &matrices, &assignment),
+        Err(e) => {
+            error!("{}", e);
+            1
+        }
+    }
+}
+
+fn run_smoke_test(
+    params_filename: &str,
+    vk_filename: &str,
+    matrices: &Matrices<BW6_761>,
+    assignment: &[<BW6_761 as PairingEngine>::Fr],
+) -> usize {
+    info!("Running a proof smoke test on the phase 2 parameters");
+
+    let params_contents = std::fs::read(params_filename).expect("should have read combined parameters");
+    let params = Parameters::<BW6_761>::deserialize_uncompressed_unchecked(params_contents.as_slice())
+        .expect("should have deserialized combined parameters");
+    let vk_contents = std::fs::read(vk_filename).expect("should have read verifying key");
+    let vk_json = serde_json::from_slice(&vk_contents).expect("should have parsed verifying key");
+    let vk = match vk_from_json::<BW6_761>(&vk_json) {
+        Ok(vk) => vk,
+        Err(e) => {
+            error!("The verifying key is invalid: {}", e);
+            return 1;
+        }
+    };
+
+    let num_variables = matrices.num_instance_variables + matrices.num_witness_variables;
+    if assignment.len() != num_variables {
+        error!(
+            "The witness has {} values but the circuit has {} variables",
+            assignment.len(),
+            num_variables
+        );
+        return 1;
+    }
+    if let Some(constraint) = matrices.unsatisfied_constraint(assignment) {
+        error!("The witness does not satisfy constraint {} of the circuit", constraint);
+        return 1;
+    }
+
+    let mut problems = check_parameters(&params, matrices);
+    let verified = match prove_and_verify(&params, &vk, matrices, assignment, &mut rand::thread_rng()) {
+        Ok(verified) => verified,
+        Err(e) => {
+            problems.push(format!("could not create a proof: {}", e));
+            false
+        }
+    };
+    if vk != params.vk {
+        problems.push("the verifying key is not the one of the parameters".to_string());
+    }
+    for problem in &problems {
+        error!("{}", problem);
+    }
+    if verified && problems.is_empty() {
+        info!("The proof verified with the exported verifying key");
+        0
+    } else if verified {
+        problems.len()
+    } else {
+        if problems.is_empty() {
+            error!("No query is inconsistent with the circuit, check the parameters against the initial ones");
+        }
+        error!("The proof did not verify");
+        problems.len() + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algebra::{Bls12_377, Field};
+    use phase2::{helpers::testing::TestCircuit, load_circuit::synthesize_assignment};
+
+    #[test]
+    fn smoke_test_test_circuit() {
+        smoke_test_test_circuit_curve::<Bls12_377>()
+    }
+
+    fn smoke_test_test_circuit_curve<E: PairingEngine>() {
+        let rng = &mut rand::thread_rng();
+        let mut params = groth16::generate_random_parameters::<E, _, _>(TestCircuit::<E>(None), rng).unwrap();
+        let matrices = Matrices::<E>::from_circuit(TestCircuit::<E>(None)).unwrap();
+
+        // any square root is a satisfying witness
+        let x = E::Fr::rand(rng);
+        let assignment = synthesize_assignment(TestCircuit::<E>(Some(x))).unwrap();
+        assert_eq!(assignment[1], x.square());
+        assert!(prove_and_verify(&params, &params.vk, &matrices, &assignment, rng).unwrap());
+        assert!(check_parameters(&params, &matrices).is_empty());
+
+        params.b_g2_query[2] = params.b_g2_query[2].into_projective().double().into_affine();
+        assert!(!prove_and_verify(&params, &params.vk, &matrices, &assignment, rng).unwrap());
+        assert_eq!(
+            check_parameters(&params, &matrices),
+            vec!["b_g1_query and b_g2_query differ at 1 indices: 2".to_string()]
+        );
+
+        params.h_query.pop();
+        params.delta_g1 = params.delta_g1.into_projective().double().into_affine();
+        assert_eq!(check_parameters(&params, &matrices).len(), 3);
+        assert!(prove_and_verify(&params, &params.vk, &matrices, &assignment, rng).is_err());
+    }
+}
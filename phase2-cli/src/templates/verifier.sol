@@ -0,0 +1,68 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.5;
+
+/// Groth16 verifier over {{CURVE}}, generated by `phase2 export-vk`.
+///
+/// Points are encoded as expected by the precompiles: each base field element
+/// is left-padded to 64 bytes, G1 points take 128 bytes and G2 points 256 bytes.
+contract Groth16Verifier {
+    address constant G1_ADD = address(uint160({{G1_ADD}}));
+    address constant G1_MSM = address(uint160({{G1_MSM}}));
+    address constant PAIRING = address(uint160({{PAIRING}}));
+
+    uint256 constant NUM_INPUTS = {{NUM_INPUTS}};
+
+    // alpha_g1 || -beta_g2 || -gamma_g2 || -delta_g2
+    bytes constant PREPARED_VK = hex"{{PREPARED_VK}}";
+
+    // gamma_abc_g1
+    bytes constant GAMMA_ABC = hex"{{GAMMA_ABC}}";
+
+    /// Verifies `proof`, encoded as A (128 bytes) || B (256 bytes) || C (128 bytes),
+    /// against the public inputs `input`.
+    function verifyProof(bytes calldata proof, uint256[] calldata input) external view returns (bool) {
+        require(proof.length == 512, "invalid proof length");
+        require(input.length == NUM_INPUTS, "invalid number of inputs");
+
+        // vk_x = gamma_abc[0] + sum(input[i] * gamma_abc[i + 1])
+        bytes memory vkX = _slice(GAMMA_ABC, 0, 128);
+        if (input.length > 0) {
+            bytes memory msmInput = new bytes(input.length * 160);
+            for (uint256 i = 0; i < input.length; i++) {
+                _copy(msmInput, i * 160, GAMMA_ABC, (i + 1) * 128, 128);
+                _copy(msmInput, i * 160 + 128, abi.encodePacked(input[i]), 0, 32);
+            }
+            bytes memory sum = _call(G1_MSM, msmInput, 128);
+            vkX = _call(G1_ADD, abi.encodePacked(vkX, sum), 128);
+        }
+
+        // e(A, B) * e(alpha, -beta) * e(vk_x, -gamma) * e(C, -delta) == 1
+        bytes memory pairingInput = abi.encodePacked(
+            proof[0:384],
+            _slice(PREPARED_VK, 0, 384),
+            vkX,
+            _slice(PREPARED_VK, 384, 256),
+            proof[384:512],
+            _slice(PREPARED_VK, 640, 256)
+        );
+        bytes memory result = _call(PAIRING, pairingInput, 32);
+        return uint256(bytes32(result)) == 1;
+    }
+
+    function _call(address precompile, bytes memory input, uint256 outputLength) private view returns (bytes memory output) {
+        bool success;
+        (success, output) = precompile.staticcall(input);
+        require(success && output.length == outputLength, "precompile call failed");
+    }
+
+    function _slice(bytes memory data, uint256 start, uint256 length) private pure returns (bytes memory out) {
+        out = new bytes(length);
+        _copy(out, 0, data, start, length);
+    }
+
+    function _copy(bytes memory dst, uint256 dstStart, bytes memory src, uint256 srcStart, uint256 length) private pure {
+        for (uint256 i = 0; i < length; i++) {
+            dst[dstStart + i] = src[srcStart + i];
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use phase2::parameters::{verify_transcript_steps, MPCParameters};
+use setup_utils::{CheckForCorrectness, SubgroupCheckMode};
+
+use algebra::{CanonicalSerialize, PairingEngine, BW6_761};
+
+use crate::COMBINED_IS_COMPRESSED;
+
+use serde_json::json;
+use tracing::{error, info};
+
+fn hex_element<T: CanonicalSerialize>(element: &T) -> String {
+    let mut bytes = vec![];
+    element.serialize(&mut bytes).expect("should have serialized element");
+    hex::encode(bytes)
+}
+
+/// Returns the contributions of the parameters as JSON, with their hashes and
+/// points in hex (using the compressed encoding for points), together with the
+/// result of verifying each step of the transcript and of checking that the
+/// delta of the parameters is the one of the last contribution
+pub fn transcript_to_json<E: PairingEngine>(mpc: &MPCParameters<E>) -> serde_json::Value {
+    let steps = verify_transcript_steps(mpc.cs_hash, &mpc.contributions);
+    let delta = mpc.verify_delta();
+    let contributions = mpc
+        .contributions
+        .iter()
+        .zip(&steps)
+        .enumerate()
+        .map(|(i, (pubkey, step))| {
+            json!({
+                "index": i,
+                "hash": hex::encode(&pubkey.hash()[..]),
+                "delta_after": hex_element(&pubkey.delta_after),
+                "s": hex_element(&pubkey.s),
+                "s_delta": hex_element(&pubkey.s_delta),
+                "r_delta": hex_element(&pubkey.r_delta),
+                "transcript": hex::encode(&pubkey.transcript[..]),
+                "verified": step.is_ok(),
+                "error": step.as_ref().err().map(|e| e.to_string()),
+            })
+        })
+        .collect::<Vec<_>>();
+    let header = mpc.header.map(|header| {
+        json!({
+            "circuit_hash": hex::encode(&header.circuit_hash[..]),
+            "phase1_hash": hex::encode(&header.phase1_hash[..]),
+        })
+    });
+
+    json!({
+        "cs_hash": hex::encode(&mpc.cs_hash[..]),
+        "header": header,
+        "verified": delta.is_ok() && steps.iter().all(|step| step.is_ok()),
+        "delta_verified": delta.is_ok(),
+        "delta_error": delta.as_ref().err().map(|e| e.to_string()),
+        "contributions": contributions,
+    })
+}
+
+pub fn transcript(combined_filename: &str, output_filename: &str) {
+    info!("Exporting the phase 2 transcript");
+
+    let contents = std::fs::read(combined_filename).expect("should have read combined file");
+    let parameters = MPCParameters::<BW6_761>::read_fast(
+        contents.as_slice(),
+        COMBINED_IS_COMPRESSED,
+        CheckForCorrectness::No,
+        false,
+        SubgroupCheckMode::Auto,
+    )
+    .expect("should have read combined parameters");
+
+    let transcript = transcript_to_json(&parameters);
+    if transcript["verified"] == json!(true) {
+        info!(
+            "All {} contributions of the transcript are valid",
+            parameters.contributions.len()
+        );
+    } else if transcript["delta_verified"] == json!(true) {
+        error!("The transcript contains invalid contributions");
+    } else {
+        error!(
+            "The delta of the parameters does not match the transcript: {}",
+            transcript["delta_error"]
+        );
+    }
+    let transcript = serde_json::to_string_pretty(&transcript).expect("should have encoded JSON");
+    std::fs::write(output_filename, transcript).expect("should have written the transcript");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algebra::{AffineCurve, Bls12_377, ProjectiveCurve};
+    use phase2::helpers::testing::TestCircuit;
+    use setup_utils::BatchExpMode;
+
+    #[test]
+    fn transcript_json_reports_steps() {
+        transcript_json_reports_steps_curve::<Bls12_377>()
+    }
+
+    fn transcript_json_reports_steps_curve<E: PairingEngine>() {
+        let rng = &mut rand::thread_rng();
+        let params = groth16::generate_random_parameters::<E, _, _>(TestCircuit::<E>(None), rng).unwrap();
+        let mut mpc = MPCParameters {
+            params,
+            cs_hash: [1; 64],
+            contributions: vec![],
+            header: None,
+        };
+        for _ in 0..3 {
+            mpc.contribute(BatchExpMode::Auto, rng).unwrap();
+        }
+
+        let transcript = transcript_to_json(&mpc);
+        assert_eq!(transcript["verified"], json!(true));
+        assert_eq!(transcript["delta_verified"], json!(true));
+        let contributions = transcript["contributions"].as_array().unwrap();
+        assert_eq!(contributions.len(), 3);
+        assert_eq!(contributions[1]["index"], json!(1));
+        assert_eq!(
+            contributions[1]["hash"],
+            json!(hex::encode(&mpc.contributions[1].hash()[..]))
+        );
+
+        // parameters whose delta is not the one of the last contribution fail,
+        // even though every step of the transcript is valid
+        let mut wrong_delta = mpc.clone();
+        wrong_delta.params.delta_g1 = wrong_delta.params.delta_g1.into_projective().double().into_affine();
+        let transcript = transcript_to_json(&wrong_delta);
+        assert_eq!(transcript["delta_verified"], json!(false));
+        assert_eq!(transcript["verified"], json!(false));
+        let contributions = transcript["contributions"].as_array().unwrap();
+        assert!(contributions
+            .iter()
+            .all(|contribution| contribution["verified"] == json!(true)));
+
+        // tampering with a contribution only fails the steps which depend on it
+        mpc.contributions[1].delta_after = mpc.contributions[1]
+            .delta_after
+            .into_projective()
+            .double()
+            .into_affine();
+        let transcript = transcript_to_json(&mpc);
+        assert_eq!(transcript["verified"], json!(false));
+        let contributions = transcript["contributions"].as_array().unwrap();
+        assert_eq!(contributions[0]["verified"], json!(true));
+        assert_eq!(contributions[1]["verified"], json!(false));
+        assert_eq!(contributions[2]["verified"], json!(false));
+    }
+}
@@ -0,0 +1,80 @@
+//! Helpers for chunk files in the detached format, whose contributions are
+//! kept in a separate transcript file
+use phase2::{parameters::MPCParameters, transcript::Transcript};
+use setup_utils::{CheckForCorrectness, Result, SubgroupCheckMode, UseCompression};
+
+use algebra::BW6_761;
+use std::io::Write;
+
+pub(crate) fn read_transcript(transcript_filename: &str) -> Transcript<BW6_761> {
+    let contents = std::fs::read(transcript_filename).expect("should have read transcript");
+    Transcript::read(contents.as_slice()).expect("should have deserialized transcript")
+}
+
+pub(crate) fn write_transcript(transcript_filename: &str, transcript: &Transcript<BW6_761>) {
+    let mut contents = vec![];
+    transcript
+        .write(&mut contents)
+        .expect("should have serialized transcript");
+    std::fs::write(transcript_filename, &contents).expect("should have written transcript");
+}
+
+/// Reads a chunk, in the detached format if its transcript is given
+pub(crate) fn read_chunk(
+    contents: &[u8],
+    transcript: Option<&Transcript<BW6_761>>,
+    compressed: UseCompression,
+    check_correctness: CheckForCorrectness,
+    check_subgroup_membership: bool,
+    subgroup_check_mode: SubgroupCheckMode,
+) -> Result<MPCParameters<BW6_761>> {
+    match transcript {
+        Some(transcript) => MPCParameters::read_detached(
+            contents,
+            compressed,
+            check_correctness,
+            check_subgroup_membership,
+            subgroup_check_mode,
+            transcript,
+        ),
+        None => MPCParameters::read_fast(
+            contents,
+            compressed,
+            check_correctness,
+            check_subgroup_membership,
+            subgroup_check_mode,
+        ),
+    }
+}
+
+/// Writes the transcript of a round, which the chunks of the round share. If
+/// another chunk of the round already wrote it, the transcripts must be the
+/// same, i.e. every chunk must have been contributed to with the same seed.
+pub(crate) fn write_round_transcript(transcript_filename: &str, transcript: &Transcript<BW6_761>) {
+    if std::path::Path::new(transcript_filename).exists() {
+        let existing = read_transcript(transcript_filename);
+        assert!(
+            existing == *transcript,
+            "the transcript {} was written by a different contribution, \
+             all chunks of a round must be contributed to with the same seed",
+            transcript_filename
+        );
+    } else {
+        write_transcript(transcript_filename, transcript);
+    }
+}
+
+/// Writes a chunk, in the detached format if `detached` is set, in which case
+/// its transcript has to be written separately
+pub(crate) fn write_chunk<W: Write>(
+    writer: W,
+    parameters: &MPCParameters<BW6_761>,
+    detached: bool,
+    compressed: UseCompression,
+) -> Result<()> {
+    if detached {
+        parameters.write_detached(writer, compressed)
+    } else {
+        parameters.write(writer, compressed)
+    }
+}
@@ -1,14 +1,23 @@
-use phase2::parameters::MPCParameters;
+use phase2::transcript::Transcript;
 use setup_utils::{calculate_hash, print_hash, CheckForCorrectness, SubgroupCheckMode};
 
+use crate::{
+    print_transcript_header,
+    transcript_file::{read_chunk, read_transcript, write_chunk},
+    COMBINED_IS_COMPRESSED, COMPRESS_CONTRIBUTE_INPUT, COMPRESS_CONTRIBUTE_OUTPUT,
+};
 use algebra::BW6_761;
-
-use crate::{COMBINED_IS_COMPRESSED, COMPRESS_CONTRIBUTE_INPUT, COMPRESS_CONTRIBUTE_OUTPUT};
 use memmap::MmapOptions;
 use std::fs::OpenOptions;
 use std::io::Write;
 use tracing::info;
 
+/// Verifies a contribution to a chunk and writes the new challenge. If the
+/// transcript file names of the round and of its response are given, the chunk
+/// files are in the detached format, the new challenge refers to the response
+/// transcript, and the chunk is only checked against the contribution of the
+/// response transcript, whose contribution is verified by the first chunk of
+/// the round and recorded for the others by `verify_round_contribution`.
 pub fn verify(
     challenge_filename: &str,
     challenge_hash_filename: &str,
@@ -18,6 +27,7 @@ pub fn verify(
     check_output_correctness: CheckForCorrectness,
     new_challenge_filename: &str,
     new_challenge_hash_filename: &str,
+    transcript_filenames: Option<(&str, &str)>,
     subgroup_check_mode: SubgroupCheckMode,
     verifying_full_contribution: bool,
 ) {
@@ -33,8 +43,11 @@ pub fn verify(
     info!("`challenge` file contains decompressed points and has a hash:");
     print_hash(&challenge_hash);
 
-    let parameters_before = MPCParameters::<BW6_761>::read_fast(
+    let challenge_transcript =
+        transcript_filenames.map(|(challenge_transcript_filename, _)| read_transcript(challenge_transcript_filename));
+    let parameters_before = read_chunk(
         challenge_contents.as_slice(),
+        challenge_transcript.as_ref(),
         COMPRESS_CONTRIBUTE_INPUT,
         check_input_correctness,
         true,
@@ -57,8 +70,17 @@ pub fn verify(
     } else {
         COMPRESS_CONTRIBUTE_OUTPUT
     };
-    let parameters_after = MPCParameters::<BW6_761>::read_fast(
+    let response_transcript =
+        transcript_filenames.map(|(_, response_transcript_filename)| read_transcript(response_transcript_filename));
+    // the new challenge must not be written for a contribution without a valid proof of knowledge
+    if let (Some(challenge_transcript), Some(response_transcript), Some((_, response_transcript_filename))) =
+        (&challenge_transcript, &response_transcript, transcript_filenames)
+    {
+        verify_round_contribution(challenge_transcript, response_transcript, response_transcript_filename);
+    }
+    let parameters_after = read_chunk(
         response_contents.as_slice(),
+        response_transcript.as_ref(),
         after_compressed,
         check_output_correctness,
         true,
@@ -72,9 +94,13 @@ pub fn verify(
         .create_new(true)
         .open(new_challenge_filename)
         .expect("unable to create new challenge file in this directory");
-    parameters_after
-        .write(writer, COMPRESS_CONTRIBUTE_INPUT)
-        .expect("unable to write new challenge file");
+    write_chunk(
+        writer,
+        &parameters_after,
+        transcript_filenames.is_some(),
+        COMPRESS_CONTRIBUTE_INPUT,
+    )
+    .expect("unable to write new challenge file");
 
     // Read new challenge to create hash
     let new_challenge_reader = OpenOptions::new()
@@ -93,12 +119,75 @@ pub fn verify(
         .write_all(new_challenge_hash.as_slice())
         .expect("unable to write new challenge hash");
 
-    parameters_before
-        .verify(&parameters_after)
-        .expect("should have successfully verified");
+    if response_transcript.is_some() {
+        parameters_before
+            .verify_params(&parameters_after)
+            .expect("should have successfully verified");
+    } else {
+        parameters_before
+            .verify(&parameters_after)
+            .expect("should have successfully verified");
+    }
+    print_transcript_header(&parameters_after.header);
     info!(
         "Done!\n\n\
               The BLAKE2b hash of response file is:\n"
     );
     print_hash(&response_hash);
 }
+
+/// Verifies the contribution a round adds to the transcript shared by its
+/// detached chunks, and returns its hash. `verify` does it for the first chunk
+/// of the round, and this lets it be done ahead of the chunks.
+pub fn verify_round_transcript(challenge_transcript_filename: &str, response_transcript_filename: &str) -> [u8; 64] {
+    info!("Verifying the contribution of the round");
+
+    let challenge_transcript = read_transcript(challenge_transcript_filename);
+    let response_transcript = read_transcript(response_transcript_filename);
+    let contribution_hash = verify_round_contribution(
+        &challenge_transcript,
+        &response_transcript,
+        response_transcript_filename,
+    );
+    print_transcript_header(&response_transcript.header);
+    info!("The contribution of the round is valid and has a hash:");
+    print_hash(&contribution_hash);
+    contribution_hash
+}
+
+/// The file recording that the contribution of a response transcript was verified
+fn verified_filename(response_transcript_filename: &str) -> String {
+    format!("{}.verified", response_transcript_filename)
+}
+
+/// Verifies the contribution of the response transcript, unless it was already
+/// verified against the same challenge transcript. The hashes of both
+/// transcripts and of the contribution are then recorded next to the response
+/// transcript, so that the other chunks of the round skip the check.
+fn verify_round_contribution(
+    challenge_transcript: &Transcript<BW6_761>,
+    response_transcript: &Transcript<BW6_761>,
+    response_transcript_filename: &str,
+) -> [u8; 64] {
+    let verified_filename = verified_filename(response_transcript_filename);
+    let transcript_hashes = [challenge_transcript.hash(), response_transcript.hash()].concat();
+    if let Ok(verified) = std::fs::read(&verified_filename) {
+        if verified.len() == transcript_hashes.len() + 64
+            && verified[..transcript_hashes.len()] == transcript_hashes[..]
+        {
+            let mut contribution_hash = [0u8; 64];
+            contribution_hash.copy_from_slice(&verified[transcript_hashes.len()..]);
+            return contribution_hash;
+        }
+    }
+
+    let contribution_hash = challenge_transcript
+        .verify_contribution(response_transcript)
+        .expect("should have successfully verified the transcript");
+    std::fs::write(
+        &verified_filename,
+        [&transcript_hashes[..], &contribution_hash[..]].concat(),
+    )
+    .expect("should have recorded the verified transcript");
+    contribution_hash
+}
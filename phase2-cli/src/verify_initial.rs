@@ -0,0 +1,312 @@
+use crate::{
+    circuit_provider::CircuitSource, new_challenge::generate_initial_parameters, transcript_file::read_chunk,
+    COMPRESS_CONTRIBUTE_INPUT,
+};
+use phase2::{parameters::MPCParameters, transcript::Transcript};
+use setup_utils::{CheckForCorrectness, SubgroupCheckMode};
+
+use algebra::{PairingEngine, BW6_761};
+use groth16::Parameters;
+
+use std::fmt::Debug;
+use tracing::{error, info};
+
+/// The number of differing indices which are listed per query
+const MAX_REPORTED_INDICES: usize = 10;
+
+/// Recomputes the initial parameters from the circuit and the prepared phase 1
+/// file and compares them with the published `.full`, `.query` and chunk files.
+/// Returns the number of mismatches found. If a transcript file name is
+/// given, the chunks are read in the detached format. A memory budget bounds
+/// the memory used for the matrices of the circuit, as in `new_challenge`.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_initial(
+    challenge_filename: &str,
+    transcript_filename: Option<&str>,
+    chunk_size: usize,
+    memory_budget: Option<usize>,
+    phase1_filename: &str,
+    phase1_powers: usize,
+    circuit: CircuitSource,
+) -> usize {
+    info!("Re-deriving the initial phase 2 parameters");
+
+    let (full_mpc_parameters, query_parameters, all_mpc_parameters) =
+        generate_initial_parameters(circuit, phase1_filename, phase1_powers, chunk_size, memory_budget);
+
+    let mut mismatches = vec![];
+
+    let full_filename = format!("{}.full", challenge_filename);
+    if let Some(published) = read_mpc_parameters(&full_filename, None, &mut mismatches) {
+        compare_mpc_parameters(&mut mismatches, &full_filename, &full_mpc_parameters, &published);
+    }
+
+    let query_filename = format!("{}.query", challenge_filename);
+    match std::fs::read(&query_filename) {
+        Ok(contents) => match MPCParameters::<BW6_761>::read_groth16_fast(
+            contents.as_slice(),
+            COMPRESS_CONTRIBUTE_INPUT,
+            CheckForCorrectness::No,
+            false,
+            SubgroupCheckMode::Auto,
+        ) {
+            Ok(published) => compare_parameters(&mut mismatches, &query_filename, &query_parameters, &published),
+            Err(e) => mismatches.push(format!("{}: could not be read: {}", query_filename, e)),
+        },
+        Err(e) => mismatches.push(format!("{}: could not be read: {}", query_filename, e)),
+    }
+
+    let transcript = transcript_filename.and_then(|transcript_filename| {
+        match std::fs::read(transcript_filename)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| Transcript::<BW6_761>::read(contents.as_slice()).map_err(|e| e.to_string()))
+        {
+            Ok(transcript) => {
+                if transcript != full_mpc_parameters.transcript() {
+                    mismatches.push(format!("{}: transcript differs", transcript_filename));
+                }
+                Some(transcript)
+            }
+            Err(e) => {
+                mismatches.push(format!("{}: could not be read: {}", transcript_filename, e));
+                None
+            }
+        }
+    });
+
+    // detached chunks cannot be read without their transcript
+    if transcript_filename.is_none() || transcript.is_some() {
+        for (i, chunk) in all_mpc_parameters.iter().enumerate() {
+            let chunk_filename = format!("{}.{}", challenge_filename, i);
+            if let Some(published) = read_mpc_parameters(&chunk_filename, transcript.as_ref(), &mut mismatches) {
+                compare_mpc_parameters(&mut mismatches, &chunk_filename, chunk, &published);
+            }
+        }
+    }
+
+    if mismatches.is_empty() {
+        info!(
+            "The initial parameters and their {} chunks match the circuit and the phase 1 file",
+            all_mpc_parameters.len()
+        );
+    } else {
+        for mismatch in &mismatches {
+            error!("{}", mismatch);
+        }
+        error!("Found {} mismatches in the initial parameters", mismatches.len());
+    }
+    mismatches.len()
+}
+
+fn read_mpc_parameters(
+    filename: &str,
+    transcript: Option<&Transcript<BW6_761>>,
+    mismatches: &mut Vec<String>,
+) -> Option<MPCParameters<BW6_761>> {
+    let contents = match std::fs::read(filename) {
+        Ok(contents) => contents,
+        Err(e) => {
+            mismatches.push(format!("{}: could not be read: {}", filename, e));
+            return None;
+        }
+    };
+    match read_chunk(
+        contents.as_slice(),
+        transcript,
+        COMPRESS_CONTRIBUTE_INPUT,
+        CheckForCorrectness::No,
+        false,
+        SubgroupCheckMode::Auto,
+    ) {
+        Ok(parameters) => Some(parameters),
+        Err(e) => {
+            mismatches.push(format!("{}: could not be read: {}", filename, e));
+            None
+        }
+    }
+}
+
+fn compare_mpc_parameters<E: PairingEngine>(
+    mismatches: &mut Vec<String>,
+    filename: &str,
+    expected: &MPCParameters<E>,
+    published: &MPCParameters<E>,
+) {
+    compare_parameters(mismatches, filename, &expected.params, &published.params);
+    if expected.cs_hash[..] != published.cs_hash[..] {
+        mismatches.push(format!("{}: cs_hash differs", filename));
+    }
+    if expected.header != published.header {
+        mismatches.push(format!("{}: transcript header differs", filename));
+    }
+    if !published.contributions.is_empty() {
+        mismatches.push(format!(
+            "{}: expected no contributions, found {}",
+            filename,
+            published.contributions.len()
+        ));
+    }
+}
+
+fn compare_parameters<E: PairingEngine>(
+    mismatches: &mut Vec<String>,
+    filename: &str,
+    expected: &Parameters<E>,
+    published: &Parameters<E>,
+) {
+    compare_element(
+        mismatches,
+        filename,
+        "alpha_g1",
+        &expected.vk.alpha_g1,
+        &published.vk.alpha_g1,
+    );
+    compare_element(mismatches, filename, "beta_g1", &expected.beta_g1, &published.beta_g1);
+    compare_element(
+        mismatches,
+        filename,
+        "beta_g2",
+        &expected.vk.beta_g2,
+        &published.vk.beta_g2,
+    );
+    compare_element(
+        mismatches,
+        filename,
+        "gamma_g2",
+        &expected.vk.gamma_g2,
+        &published.vk.gamma_g2,
+    );
+    compare_element(
+        mismatches,
+        filename,
+        "delta_g1",
+        &expected.delta_g1,
+        &published.delta_g1,
+    );
+    compare_element(
+        mismatches,
+        filename,
+        "delta_g2",
+        &expected.vk.delta_g2,
+        &published.vk.delta_g2,
+    );
+    compare_query(
+        mismatches,
+        filename,
+        "gamma_abc_g1",
+        &expected.vk.gamma_abc_g1,
+        &published.vk.gamma_abc_g1,
+    );
+    compare_query(mismatches, filename, "a_query", &expected.a_query, &published.a_query);
+    compare_query(
+        mismatches,
+        filename,
+        "b_g1_query",
+        &expected.b_g1_query,
+        &published.b_g1_query,
+    );
+    compare_query(
+        mismatches,
+        filename,
+        "b_g2_query",
+        &expected.b_g2_query,
+        &published.b_g2_query,
+    );
+    compare_query(mismatches, filename, "h_query", &expected.h_query, &published.h_query);
+    compare_query(mismatches, filename, "l_query", &expected.l_query, &published.l_query);
+}
+
+fn compare_element<T: PartialEq + Debug>(
+    mismatches: &mut Vec<String>,
+    filename: &str,
+    name: &str,
+    expected: &T,
+    published: &T,
+) {
+    if expected != published {
+        mismatches.push(format!(
+            "{}: {} differs, expected {:?}, found {:?}",
+            filename, name, expected, published
+        ));
+    }
+}
+
+/// Records a mismatch if the lengths of the queries differ, or listing the
+/// first indices at which they differ
+fn compare_query<T: PartialEq>(
+    mismatches: &mut Vec<String>,
+    filename: &str,
+    name: &str,
+    expected: &[T],
+    published: &[T],
+) {
+    if expected.len() != published.len() {
+        mismatches.push(format!(
+            "{}: {} has length {}, expected {}",
+            filename,
+            name,
+            published.len(),
+            expected.len()
+        ));
+        return;
+    }
+    let differing = expected
+        .iter()
+        .zip(published)
+        .enumerate()
+        .filter(|(_, (expected, published))| expected != published)
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    if !differing.is_empty() {
+        mismatches.push(format!(
+            "{}: {} differs at {} indices: {}",
+            filename,
+            name,
+            differing.len(),
+            list_indices(&differing)
+        ));
+    }
+}
+
+/// Lists the first `MAX_REPORTED_INDICES` indices, separated by commas
+pub(crate) fn list_indices(indices: &[usize]) -> String {
+    let listed = indices
+        .iter()
+        .take(MAX_REPORTED_INDICES)
+        .map(|i| i.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if indices.len() > MAX_REPORTED_INDICES {
+        format!("{}, ...", listed)
+    } else {
+        listed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_query_reports_indices() {
+        let mut mismatches = vec![];
+        compare_query(&mut mismatches, "f", "q", &[1, 2, 3], &[1, 2, 3]);
+        assert!(mismatches.is_empty());
+
+        compare_query(&mut mismatches, "f", "q", &[1, 2, 3, 4], &[1, 0, 3, 0]);
+        assert_eq!(mismatches, vec!["f: q differs at 2 indices: 1, 3".to_string()]);
+
+        mismatches.clear();
+        compare_query(&mut mismatches, "f", "q", &[1, 2], &[1]);
+        assert_eq!(mismatches, vec!["f: q has length 1, expected 2".to_string()]);
+
+        mismatches.clear();
+        let expected = vec![0; 20];
+        let published = vec![1; 20];
+        compare_query(&mut mismatches, "f", "q", &expected, &published);
+        assert_eq!(
+            mismatches,
+            vec!["f: q differs at 20 indices: 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, ...".to_string()]
+        );
+    }
+}
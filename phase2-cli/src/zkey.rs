@@ -0,0 +1,462 @@
+//! Export of Phase 2 parameters to the snarkjs `.zkey` format.
+//!
+//! The file consists of a `zkey` magic, a version and a list of sections, each
+//! prefixed by its type and size. Field elements are written little-endian in
+//! Montgomery form and points as affine coordinates, with the point at infinity
+//! encoded as zeroes. The contribution history is carried over from the
+//! transcript, although snarkjs hashes transcripts differently and cannot
+//! re-verify it. snarkjs only proves over bn128 and BLS12-381, so parameters
+//! over other curves are rejected. As the CLI runs on BLS12-377 and BW6-761,
+//! this exporter is only offered as a library API.
+use phase2::{
+    keypair::PublicKey,
+    load_circuit::{modulus_bytes, Matrices},
+    parameters::MPCParameters,
+};
+
+use algebra::{
+    AffineCurve, CanonicalDeserialize, CanonicalSerialize, ConstantSerializedSize, FpParameters, One, PairingEngine,
+    PrimeField, ProjectiveCurve, SquareRootField, Zero,
+};
+use fft::domain::{radix2::Radix2EvaluationDomain, EvaluationDomain};
+
+use anyhow::ensure;
+use byteorder::{LittleEndian, WriteBytesExt};
+use std::io::Write;
+
+const ZKEY_MAGIC: &[u8; 4] = b"zkey";
+const ZKEY_VERSION: u32 = 1;
+const ZKEY_NUM_SECTIONS: u32 = 10;
+const GROTH16_PROTOCOL_ID: u32 = 1;
+
+/// The curves snarkjs supports, with the little-endian limbs of their base field modulus
+const SNARKJS_CURVES: &[(&str, &[u64])] = &[
+    (
+        "bn128",
+        &[
+            0x3c208c16d87cfd47,
+            0x97816a916871ca8d,
+            0xb85045b68181585d,
+            0x30644e72e131a029,
+        ],
+    ),
+    (
+        "bls12-381",
+        &[
+            0xb9feffffffffaaab,
+            0x1eabfffeb153ffff,
+            0x6730d2a0f6b0f624,
+            0x64774b84f38512bf,
+            0x4b1ba7b6434bacd7,
+            0x1a0111ea397fe69a,
+        ],
+    ),
+];
+
+/// Returns the name snarkjs gives to the curve of `E`, or an error if snarkjs cannot prove over it
+pub fn snarkjs_curve<E: PairingEngine>() -> anyhow::Result<&'static str> {
+    SNARKJS_CURVES
+        .iter()
+        .find(|(_, modulus)| E::Fq::characteristic() == *modulus)
+        .map(|(name, _)| *name)
+        .ok_or_else(|| anyhow::anyhow!("snarkjs only supports the bn128 and bls12-381 curves"))
+}
+
+/// Writes the parameters and their contribution transcript as a snarkjs `.zkey`.
+/// `matrices` must be the circuit the parameters were generated for.
+pub fn write_zkey<E: PairingEngine, W: Write>(
+    mpc: &MPCParameters<E>,
+    matrices: &Matrices<E>,
+    mut writer: W,
+) -> anyhow::Result<()> {
+    snarkjs_curve::<E>()?;
+    let params = &mpc.params;
+    let encoder = Encoder::<E>::new();
+    let domain_size = params.h_query.len() + 1;
+    ensure!(
+        domain_size.is_power_of_two(),
+        "snarkjs only supports radix-2 domains, the parameters use a mixed-radix domain of size {}",
+        domain_size
+    );
+    let num_public = matrices.num_instance_variables - 1;
+
+    writer.write_all(ZKEY_MAGIC)?;
+    writer.write_u32::<LittleEndian>(ZKEY_VERSION)?;
+    writer.write_u32::<LittleEndian>(ZKEY_NUM_SECTIONS)?;
+
+    // 1: header
+    let mut section = vec![];
+    section.write_u32::<LittleEndian>(GROTH16_PROTOCOL_ID)?;
+    write_section(&mut writer, 1, &section)?;
+
+    // 2: groth16 header
+    let mut section = vec![];
+    section.write_u32::<LittleEndian>(encoder.n8q as u32)?;
+    section.write_all(&modulus_bytes::<E::Fq>(encoder.n8q))?;
+    section.write_u32::<LittleEndian>(encoder.n8r as u32)?;
+    section.write_all(&modulus_bytes::<E::Fr>(encoder.n8r))?;
+    section.write_u32::<LittleEndian>((matrices.num_instance_variables + matrices.num_witness_variables) as u32)?;
+    section.write_u32::<LittleEndian>(num_public as u32)?;
+    section.write_u32::<LittleEndian>(domain_size as u32)?;
+    encoder.write_point(&mut section, &params.vk.alpha_g1)?;
+    encoder.write_point(&mut section, &params.beta_g1)?;
+    encoder.write_point(&mut section, &params.vk.beta_g2)?;
+    encoder.write_point(&mut section, &params.vk.gamma_g2)?;
+    encoder.write_point(&mut section, &params.delta_g1)?;
+    encoder.write_point(&mut section, &params.vk.delta_g2)?;
+    write_section(&mut writer, 2, &section)?;
+
+    // 3: IC
+    write_section(&mut writer, 3, &encoder.points(&params.vk.gamma_abc_g1)?)?;
+
+    // 4: coefficients of A and B, snarkjs derives C from them when proving
+    let mut section = vec![];
+    let num_coefs = matrices.a_num_non_zero + matrices.b_num_non_zero;
+    section.write_u32::<LittleEndian>(num_coefs as u32)?;
+    for (matrix_index, matrix) in [&matrices.a, &matrices.b].iter().enumerate() {
+        for (constraint, row) in matrix.iter().enumerate() {
+            for (coeff, variable) in row {
+                section.write_u32::<LittleEndian>(matrix_index as u32)?;
+                section.write_u32::<LittleEndian>(constraint as u32)?;
+                section.write_u32::<LittleEndian>(*variable as u32)?;
+                encoder.write_coefficient(&mut section, coeff)?;
+            }
+        }
+    }
+    write_section(&mut writer, 4, &section)?;
+
+    // 5-8: A, B1, B2 and C (the L query, over the witness variables only)
+    write_section(&mut writer, 5, &encoder.points(&params.a_query)?)?;
+    write_section(&mut writer, 6, &encoder.points(&params.b_g1_query)?)?;
+    write_section(&mut writer, 7, &encoder.points(&params.b_g2_query)?)?;
+    write_section(&mut writer, 8, &encoder.points(&params.l_query)?)?;
+
+    // 9: H, in the Lagrange basis of the odd coset snarkjs evaluates on
+    let coset_exponent = snarkjs_coset_exponent::<E::Fr>(domain_size);
+    let h_query = coset_lagrange_h_query::<E>(&params.h_query, coset_exponent);
+    write_section(&mut writer, 9, &encoder.points(&h_query)?)?;
+
+    // 10: contributions
+    let mut section = vec![];
+    section.write_all(&mpc.cs_hash)?;
+    section.write_u32::<LittleEndian>(mpc.contributions.len() as u32)?;
+    for contribution in &mpc.contributions {
+        write_contribution(&encoder, &mut section, contribution)?;
+    }
+    write_section(&mut writer, 10, &section)?;
+
+    Ok(())
+}
+
+fn write_section<W: Write>(writer: &mut W, section_type: u32, section: &[u8]) -> anyhow::Result<()> {
+    writer.write_u32::<LittleEndian>(section_type)?;
+    writer.write_u64::<LittleEndian>(section.len() as u64)?;
+    writer.write_all(section)?;
+    Ok(())
+}
+
+fn write_contribution<E: PairingEngine>(
+    encoder: &Encoder<E>,
+    section: &mut Vec<u8>,
+    contribution: &PublicKey<E>,
+) -> anyhow::Result<()> {
+    encoder.write_point(section, &contribution.delta_after)?;
+    encoder.write_point(section, &contribution.s)?;
+    encoder.write_point(section, &contribution.s_delta)?;
+    encoder.write_point(section, &contribution.r_delta)?;
+    section.write_all(&contribution.transcript)?;
+    // a regular (non-beacon) contribution without any named parameters
+    section.write_u32::<LittleEndian>(0)?;
+    section.write_u32::<LittleEndian>(0)?;
+    Ok(())
+}
+
+/// Converts points and scalars to the Montgomery form used by snarkjs
+struct Encoder<E: PairingEngine> {
+    n8q: usize,
+    n8r: usize,
+    // Montgomery factor of the base field
+    r_q: E::Fq,
+    // snarkjs expects coefficients multiplied by the square of the Montgomery factor
+    r2_r: E::Fr,
+}
+
+impl<E: PairingEngine> Encoder<E> {
+    fn new() -> Self {
+        let n8q = E::G1Affine::UNCOMPRESSED_SIZE / 2;
+        let n8r = ((<E::Fr as PrimeField>::size_in_bits() + 63) / 64) * 8;
+        let r_q = E::Fq::from(2u64).pow([(n8q * 8) as u64]);
+        let r2_r = E::Fr::from(2u64).pow([(n8r * 16) as u64]);
+        Self { n8q, n8r, r_q, r2_r }
+    }
+
+    fn write_point<C: AffineCurve>(&self, writer: &mut Vec<u8>, point: &C) -> anyhow::Result<()> {
+        if point.is_zero() {
+            writer.extend(std::iter::repeat(0).take(C::UNCOMPRESSED_SIZE));
+            return Ok(());
+        }
+        let mut bytes = Vec::with_capacity(C::UNCOMPRESSED_SIZE);
+        point.serialize_uncompressed(&mut bytes)?;
+        // the infinity and sign flags are kept in the top bits of the last byte
+        let last = bytes.len() - 1;
+        bytes[last] &= 0x3f;
+        for limb in bytes.chunks(self.n8q) {
+            let element = E::Fq::deserialize(limb)? * &self.r_q;
+            element.serialize(&mut *writer)?;
+        }
+        Ok(())
+    }
+
+    fn points<C: AffineCurve>(&self, points: &[C]) -> anyhow::Result<Vec<u8>> {
+        let mut section = Vec::with_capacity(points.len() * C::UNCOMPRESSED_SIZE);
+        for point in points {
+            self.write_point(&mut section, point)?;
+        }
+        Ok(section)
+    }
+
+    fn write_coefficient(&self, writer: &mut Vec<u8>, coeff: &E::Fr) -> anyhow::Result<()> {
+        (*coeff * &self.r2_r).serialize(&mut *writer)?;
+        Ok(())
+    }
+}
+
+/// snarkjs evaluates the QAP on the coset `g * <g^2>`, where `g` is derived from
+/// the smallest quadratic non-residue of the scalar field and is a primitive root
+/// of unity of order `2 * domain_size`. Returns the odd `a` such that `g = z^a`,
+/// for `z` the primitive root of the same order used by Zexe.
+fn snarkjs_coset_exponent<F: PrimeField + SquareRootField>(domain_size: usize) -> usize {
+    let mut qnr = F::from(2u64);
+    while !qnr.legendre().is_qnr() {
+        qnr += &F::one();
+    }
+    // qnr^t has order 2^s, square it until its order is 2 * domain_size
+    let mut g = qnr.pow(<F::Params as FpParameters>::T);
+    while g.pow([domain_size as u64]) != -F::one() {
+        g.square_in_place();
+    }
+
+    let z = Radix2EvaluationDomain::<F>::new(2 * domain_size)
+        .expect("could not create domain")
+        .group_gen;
+    let z_squared = z.square();
+    let mut candidate = z;
+    for a in (1..2 * domain_size).step_by(2) {
+        if candidate == g {
+            return a;
+        }
+        candidate *= &z_squared;
+    }
+    unreachable!("both roots generate the same group")
+}
+
+/// Converts the Groth16 H query `[tau^i * Z(tau) / delta]` for `i` in `0..n-1` to
+/// the Lagrange basis of the coset `{z^(a * (2i + 1))}`, where `z` is a primitive
+/// root of unity of order `2n`, as used by snarkjs.
+///
+/// Since `Z(x) = -2` on the odd coset, the bases are `-1/2 * L_i(tau) * Z(tau) / delta`,
+/// which is an inverse FFT of the H query after scaling the `j`-th element by `z^-j`.
+fn coset_lagrange_h_query<E: PairingEngine>(h_query: &[E::G1Affine], coset_exponent: usize) -> Vec<E::G1Affine> {
+    let domain_size = h_query.len() + 1;
+    let domain = Radix2EvaluationDomain::<E::Fr>::new(domain_size).expect("could not create domain");
+    let z_inv = Radix2EvaluationDomain::<E::Fr>::new(2 * domain_size)
+        .expect("could not create domain")
+        .group_gen_inv;
+    let mut scale = -E::Fr::from(2u64).inverse().expect("2 is invertible");
+    let mut scaled = Vec::with_capacity(domain_size);
+    for h in h_query {
+        scaled.push(h.mul(scale));
+        scale *= &z_inv;
+    }
+    // the interpolated H polynomial has degree at most n - 2
+    scaled.push(E::G1Projective::zero());
+    let mut lagrange = domain.ifft(&scaled);
+    E::G1Projective::batch_normalization(&mut lagrange);
+
+    // the `i`-th snarkjs point z^(a * (2i + 1)) is z^(2k + 1) for a unique `k`
+    (0..domain_size)
+        .map(|i| {
+            let k = ((coset_exponent * (2 * i + 1)) % (2 * domain_size) - 1) / 2;
+            lagrange[k].into_affine()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phase2::helpers::SquareRootCircuit;
+    use setup_utils::BatchExpMode;
+
+    use algebra::{Bls12_377, Bls12_381, UniformRand, BW6_761};
+    use byteorder::{LittleEndian, ReadBytesExt};
+    use std::io::Read;
+
+    #[test]
+    fn write_zkey_end_to_end() {
+        write_zkey_curve::<Bls12_381>();
+    }
+
+    fn write_zkey_curve<E: PairingEngine>() {
+        let rng = &mut rand::thread_rng();
+        let params = groth16::generate_random_parameters::<E, _, _>(SquareRootCircuit::<E>(None), rng).unwrap();
+        let matrices = Matrices::<E>::from_circuit(SquareRootCircuit::<E>(None)).unwrap();
+        let mut mpc = MPCParameters {
+            params,
+            cs_hash: [1; 64],
+            contributions: vec![],
+            header: None,
+        };
+        for _ in 0..2 {
+            mpc.contribute(BatchExpMode::Auto, rng).unwrap();
+        }
+        let mut zkey = vec![];
+        write_zkey(&mpc, &matrices, &mut zkey).unwrap();
+
+        let mut reader = &zkey[..];
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic).unwrap();
+        assert_eq!(&magic, ZKEY_MAGIC);
+        assert_eq!(reader.read_u32::<LittleEndian>().unwrap(), ZKEY_VERSION);
+        assert_eq!(reader.read_u32::<LittleEndian>().unwrap(), ZKEY_NUM_SECTIONS);
+        let mut sections = vec![];
+        for section_type in 1..=ZKEY_NUM_SECTIONS {
+            assert_eq!(reader.read_u32::<LittleEndian>().unwrap(), section_type);
+            let size = reader.read_u64::<LittleEndian>().unwrap() as usize;
+            let (section, rest) = reader.split_at(size);
+            sections.push(section);
+            reader = rest;
+        }
+        assert!(reader.is_empty());
+
+        assert_eq!(sections[0], &GROTH16_PROTOCOL_ID.to_le_bytes()[..]);
+        let params = &mpc.params;
+        let n8q = E::G1Affine::UNCOMPRESSED_SIZE / 2;
+        let n8r = ((<E::Fr as PrimeField>::size_in_bits() + 63) / 64) * 8;
+        let domain_size = params.h_query.len() + 1;
+        let mut header = sections[1];
+        assert_eq!(header.read_u32::<LittleEndian>().unwrap() as usize, n8q);
+        header = &header[n8q..];
+        assert_eq!(header.read_u32::<LittleEndian>().unwrap() as usize, n8r);
+        header = &header[n8r..];
+        assert_eq!(
+            header.read_u32::<LittleEndian>().unwrap() as usize,
+            matrices.num_instance_variables + matrices.num_witness_variables
+        );
+        assert_eq!(
+            header.read_u32::<LittleEndian>().unwrap() as usize,
+            matrices.num_instance_variables - 1
+        );
+        assert_eq!(header.read_u32::<LittleEndian>().unwrap() as usize, domain_size);
+        assert_eq!(read_point::<E, E::G1Affine>(&mut header), params.vk.alpha_g1);
+        assert_eq!(read_point::<E, E::G1Affine>(&mut header), params.beta_g1);
+        assert_eq!(read_point::<E, E::G2Affine>(&mut header), params.vk.beta_g2);
+        assert_eq!(read_point::<E, E::G2Affine>(&mut header), params.vk.gamma_g2);
+        assert_eq!(read_point::<E, E::G1Affine>(&mut header), params.delta_g1);
+        assert_eq!(read_point::<E, E::G2Affine>(&mut header), params.vk.delta_g2);
+        assert!(header.is_empty());
+
+        let num_coefs = matrices.a_num_non_zero + matrices.b_num_non_zero;
+        assert_eq!(sections[3].len(), 4 + num_coefs * (12 + n8r));
+        assert_eq!(read_points::<E, E::G1Affine>(sections[2]), params.vk.gamma_abc_g1);
+        assert_eq!(read_points::<E, E::G1Affine>(sections[4]), params.a_query);
+        assert_eq!(read_points::<E, E::G1Affine>(sections[5]), params.b_g1_query);
+        assert_eq!(read_points::<E, E::G2Affine>(sections[6]), params.b_g2_query);
+        assert_eq!(read_points::<E, E::G1Affine>(sections[7]), params.l_query);
+        let coset_exponent = snarkjs_coset_exponent::<E::Fr>(domain_size);
+        assert_eq!(
+            read_points::<E, E::G1Affine>(sections[8]),
+            coset_lagrange_h_query::<E>(&params.h_query, coset_exponent)
+        );
+
+        let mut contributions = sections[9];
+        assert_eq!(&contributions[..64], &mpc.cs_hash[..]);
+        contributions = &contributions[64..];
+        assert_eq!(
+            contributions.read_u32::<LittleEndian>().unwrap() as usize,
+            mpc.contributions.len()
+        );
+        for contribution in &mpc.contributions {
+            assert_eq!(
+                read_point::<E, E::G1Affine>(&mut contributions),
+                contribution.delta_after
+            );
+            assert_eq!(read_point::<E, E::G1Affine>(&mut contributions), contribution.s);
+            assert_eq!(read_point::<E, E::G1Affine>(&mut contributions), contribution.s_delta);
+            assert_eq!(read_point::<E, E::G2Affine>(&mut contributions), contribution.r_delta);
+            assert_eq!(&contributions[..64], &contribution.transcript[..]);
+            contributions = &contributions[64..];
+            assert_eq!(contributions.read_u32::<LittleEndian>().unwrap(), 0);
+            assert_eq!(contributions.read_u32::<LittleEndian>().unwrap(), 0);
+        }
+        assert!(contributions.is_empty());
+    }
+
+    /// Reads a point in the Montgomery form of snarkjs, independently of `Encoder`
+    fn read_point<E: PairingEngine, C: AffineCurve>(reader: &mut &[u8]) -> C {
+        let n8q = E::G1Affine::UNCOMPRESSED_SIZE / 2;
+        let r_q_inv = E::Fq::from(2u64).pow([(n8q * 8) as u64]).inverse().unwrap();
+        let (encoded, rest) = reader.split_at(C::UNCOMPRESSED_SIZE);
+        *reader = rest;
+        if encoded.iter().all(|b| *b == 0) {
+            return C::zero();
+        }
+        let mut bytes = vec![];
+        for limb in encoded.chunks(n8q) {
+            (E::Fq::deserialize(limb).unwrap() * &r_q_inv)
+                .serialize(&mut bytes)
+                .unwrap();
+        }
+        C::deserialize_uncompressed(&bytes[..]).unwrap()
+    }
+
+    fn read_points<E: PairingEngine, C: AffineCurve>(mut section: &[u8]) -> Vec<C> {
+        assert_eq!(section.len() % C::UNCOMPRESSED_SIZE, 0);
+        let mut points = vec![];
+        while !section.is_empty() {
+            points.push(read_point::<E, C>(&mut section));
+        }
+        points
+    }
+
+    #[test]
+    fn coset_lagrange_h_query_matches_lagrange_coefficients() {
+        coset_lagrange_h_query_curve::<Bls12_377>();
+    }
+
+    fn coset_lagrange_h_query_curve<E: PairingEngine>() {
+        let rng = &mut rand::thread_rng();
+        let domain_size = 8;
+        let tau = E::Fr::rand(rng);
+        let g = E::G1Affine::prime_subgroup_generator();
+
+        // H query for delta = 1
+        let z_tau = tau.pow([domain_size as u64]) - &E::Fr::one();
+        let h_query = (0..domain_size - 1)
+            .map(|i| g.mul(tau.pow([i as u64]) * &z_tau).into_affine())
+            .collect::<Vec<_>>();
+
+        let double_domain = Radix2EvaluationDomain::<E::Fr>::new(2 * domain_size).unwrap();
+        let lagrange = double_domain.evaluate_all_lagrange_coefficients(tau);
+        for &coset_exponent in &[1, 3, 5] {
+            let got = coset_lagrange_h_query::<E>(&h_query, coset_exponent);
+            for (i, point) in got.iter().enumerate() {
+                let index = (coset_exponent * (2 * i + 1)) % (2 * domain_size);
+                assert_eq!(*point, g.mul(lagrange[index]).into_affine());
+            }
+        }
+    }
+
+    #[test]
+    fn only_snarkjs_curves_are_supported() {
+        assert_eq!(snarkjs_curve::<Bls12_381>().unwrap(), "bls12-381");
+        assert!(snarkjs_curve::<Bls12_377>().is_err());
+        assert!(snarkjs_curve::<BW6_761>().is_err());
+    }
+
+    #[test]
+    fn coset_exponent_is_odd() {
+        let a = snarkjs_coset_exponent::<<Bls12_377 as PairingEngine>::Fr>(16);
+        assert_eq!(a % 2, 1);
+    }
+}
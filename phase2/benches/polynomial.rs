@@ -0,0 +1,135 @@
+use phase1::helpers::testing::random_point_vec;
+use phase2::polynomial::{eval, EvaluatedQap};
+
+use algebra::{
+    bls12_377::{Bls12_377, Fr, G1Affine, G2Affine},
+    AffineCurve, One, PairingEngine, ProjectiveCurve, UniformRand, Zero,
+};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rand::Rng;
+use rayon::prelude::*;
+
+// This was the previous implementation, which multiplies every term individually,
+// we keep it here to compare performance against the batched implementation
+#[allow(clippy::too_many_arguments)]
+fn eval_unbatched<E: PairingEngine>(
+    coeffs_g1: &[E::G1Affine],
+    coeffs_g2: &[E::G2Affine],
+    alpha_coeffs_g1: &[E::G1Affine],
+    beta_coeffs_g1: &[E::G1Affine],
+    at: &[Vec<(E::Fr, usize)>],
+    bt: &[Vec<(E::Fr, usize)>],
+    ct: &[Vec<(E::Fr, usize)>],
+    num_inputs: usize,
+) -> EvaluatedQap<E> {
+    let a_g1 = dot_product_vec(at, coeffs_g1);
+    let b_g1 = dot_product_vec(bt, coeffs_g1);
+    let b_g2 = dot_product_vec(bt, coeffs_g2);
+    let mut ext = at
+        .par_iter()
+        .zip(bt.par_iter().zip(ct))
+        .map(|(at, (bt, ct))| {
+            dot_product(at, beta_coeffs_g1) + dot_product(bt, alpha_coeffs_g1) + dot_product(ct, coeffs_g1)
+        })
+        .collect::<Vec<_>>();
+    E::G1Projective::batch_normalization(&mut ext);
+
+    let (gamma_abc_g1, l) = ext.split_at(num_inputs);
+    (
+        a_g1.iter().map(|p| p.into_affine()).collect(),
+        b_g1.iter().map(|p| p.into_affine()).collect(),
+        b_g2.iter().map(|p| p.into_affine()).collect(),
+        gamma_abc_g1.iter().map(|p| p.into_affine()).collect(),
+        l.iter().map(|p| p.into_affine()).collect(),
+    )
+}
+
+fn dot_product_vec<C: AffineCurve>(input: &[Vec<(C::ScalarField, usize)>], coeffs: &[C]) -> Vec<C::Projective> {
+    let mut ret = input.par_iter().map(|row| dot_product(row, coeffs)).collect::<Vec<_>>();
+    C::Projective::batch_normalization(&mut ret);
+    ret
+}
+
+fn dot_product<C: AffineCurve>(input: &[(C::ScalarField, usize)], coeffs: &[C]) -> C::Projective {
+    input
+        .into_par_iter()
+        .fold(C::Projective::zero, |mut sum, &(coeff, ind)| {
+            sum += &coeffs[ind].mul(coeff);
+            sum
+        })
+        .sum()
+}
+
+// Generates a sparse matrix in the format returned by `process_matrix`, with
+// a mix of unit and random coefficients like in real circuits
+fn random_matrix(
+    rng: &mut impl Rng,
+    num_rows: usize,
+    terms_per_row: usize,
+    num_coeffs: usize,
+) -> Vec<Vec<(Fr, usize)>> {
+    (0..num_rows)
+        .map(|_| {
+            (0..terms_per_row)
+                .map(|_| {
+                    let coeff = if rng.gen() { Fr::one() } else { Fr::rand(rng) };
+                    (coeff, rng.gen_range(0, num_coeffs))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// Benchmark showing that the batched evaluation is faster
+fn benchmark_eval(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Eval");
+    group.sample_size(10);
+    let mut rng = rand::thread_rng();
+    let num_inputs = 4;
+    let terms_per_row = 8;
+    for num_rows in (8..13).map(|i| 2usize.pow(i)) {
+        group.throughput(Throughput::Elements((3 * num_rows * terms_per_row) as u64));
+        let coeffs_g1: Vec<G1Affine> = random_point_vec(num_rows, &mut rng);
+        let coeffs_g2: Vec<G2Affine> = random_point_vec(num_rows, &mut rng);
+        let alpha_coeffs_g1: Vec<G1Affine> = random_point_vec(num_rows, &mut rng);
+        let beta_coeffs_g1: Vec<G1Affine> = random_point_vec(num_rows, &mut rng);
+        let at = random_matrix(&mut rng, num_rows, terms_per_row, num_rows);
+        let bt = random_matrix(&mut rng, num_rows, terms_per_row, num_rows);
+        let ct = random_matrix(&mut rng, num_rows, terms_per_row, num_rows);
+
+        group.bench_with_input("batched", &num_rows, |b, _num_rows| {
+            b.iter(|| {
+                eval::<Bls12_377>(
+                    &coeffs_g1,
+                    &coeffs_g2,
+                    &alpha_coeffs_g1,
+                    &beta_coeffs_g1,
+                    &at,
+                    &bt,
+                    &ct,
+                    num_inputs,
+                )
+                .unwrap()
+            })
+        });
+        group.bench_with_input("unbatched", &num_rows, |b, _num_rows| {
+            b.iter(|| {
+                eval_unbatched::<Bls12_377>(
+                    &coeffs_g1,
+                    &coeffs_g2,
+                    &alpha_coeffs_g1,
+                    &beta_coeffs_g1,
+                    &at,
+                    &bt,
+                    &ct,
+                    num_inputs,
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_eval);
+criterion_main!(benches);
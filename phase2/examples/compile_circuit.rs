@@ -0,0 +1,31 @@
+//! Compiles a circuit to the `circuit.constraints` file expected by `phase2 new`.
+//!
+//! Circuit crates can copy this example and replace `SquareRootCircuit` with their
+//! own `ConstraintSynthesizer`, which is synthesized without any assignment.
+//!
+//! Usage: cargo run --example compile_circuit -- [output file]
+use phase2::{helpers::SquareRootCircuit, load_circuit::Matrices};
+
+use algebra::BW6_761;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+fn main() {
+    let output_filename = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "circuit.constraints".to_string());
+
+    let matrices = Matrices::<BW6_761>::from_circuit(SquareRootCircuit::<BW6_761>(None))
+        .expect("should have synthesized the circuit");
+    println!(
+        "Compiled circuit with {} constraints, {} instance variables and {} witness variables",
+        matrices.num_constraints, matrices.num_instance_variables, matrices.num_witness_variables
+    );
+
+    let mut writer = BufWriter::new(File::create(&output_filename).expect("should have created the output file"));
+    matrices.write(&mut writer).expect("should have written the circuit");
+    writer.flush().expect("should have flushed the output file");
+    println!("Wrote the circuit to {}", output_filename);
+}
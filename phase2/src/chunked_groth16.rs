@@ -6,6 +6,7 @@
 use crate::{
     keypair::{Keypair, PublicKey},
     parameters::*,
+    transcript_header::TranscriptHeader,
 };
 use setup_utils::{
     batch_mul, check_same_ratio, deserialize, merge_pairs, serialize, BatchExpMode, CheckForCorrectness, InvariantKind,
@@ -195,6 +196,12 @@ pub fn verify<E: PairingEngine>(
 
     debug!("previous contributions were unchanged");
 
+    let header_before = TranscriptHeader::read_optional(&mut before)?;
+    let header_after = TranscriptHeader::read_optional(&mut after)?;
+    ensure_unchanged(header_before, header_after, InvariantKind::TranscriptHeader)?;
+
+    debug!("transcript header was unchanged");
+
     // Ensure that the new pubkey has been properly calculated
     let pubkey = if let Some(pubkey) = contributions_after.last() {
         pubkey
@@ -224,7 +231,8 @@ pub fn verify<E: PairingEngine>(
 /// Given a buffer which corresponds to the format of `MPCParameters` (Groth16 Parameters
 /// followed by the contributions array and the contributions hash), this will modify the
 /// Delta_g1, the VK's Delta_g2 and will update the H and L queries in place while leaving
-/// everything else unchanged
+/// everything else unchanged. The buffer must have room for one more public key at its
+/// end, which is appended to the contributions, before the transcript header if any.
 pub fn contribute<E: PairingEngine, R: Rng>(
     buffer: &mut [u8],
     rng: &mut R,
@@ -257,6 +265,15 @@ pub fn contribute<E: PairingEngine, R: Rng>(
     let mut cs_hash = [0u8; 64];
     buffer.read_exact(&mut cs_hash)?;
     let contributions = PublicKey::<E>::read_batch(&mut buffer)?;
+    // the header is followed by the room reserved for the new public key
+    let header_start = buffer.position() as usize;
+    let header_end = buffer
+        .get_ref()
+        .len()
+        .checked_sub(PublicKey::<E>::size())
+        .filter(|end| *end >= header_start)
+        .ok_or(Phase2Error::InvalidLength)?;
+    let header = TranscriptHeader::read_optional(&buffer.get_ref()[header_start..header_end])?;
 
     // Create the keypair
     let Keypair {
@@ -356,6 +373,10 @@ pub fn contribute<E: PairingEngine, R: Rng>(
     // advance to where the next pubkey would be in the buffer and append it
     buffer.seek(SeekFrom::Current((PublicKey::<E>::size() * contributions.len()) as i64))?;
     public_key.write(&mut buffer)?;
+    // the header moves after the new public key
+    if let Some(header) = header {
+        header.write(&mut buffer)?;
+    }
 
     info!("done.");
 
@@ -1,2 +1,5 @@
+mod square_root;
+pub use square_root::SquareRootCircuit;
+
 #[cfg(any(test, feature = "testing"))]
 pub mod testing;
@@ -6,10 +6,23 @@ pub mod keypair;
 
 pub mod parameters;
 #[cfg(not(feature = "wasm"))]
-mod polynomial;
+pub mod polynomial;
 
 pub mod load_circuit;
 
+#[cfg(not(feature = "wasm"))]
+pub mod transpose;
+
+#[cfg(not(feature = "wasm"))]
+pub mod mmap_params;
+
+#[cfg(not(feature = "wasm"))]
+pub mod prover;
+
+pub mod transcript_header;
+
+pub mod transcript;
+
 pub mod chunked_groth16;
 
 cfg_if! {
@@ -1,7 +1,33 @@
-use algebra::{CanonicalDeserialize, CanonicalSerialize, PairingEngine, SerializationError};
-use r1cs_core::Matrix;
-use setup_utils::Error;
-use std::io::{Read, Write};
+use algebra::{
+    CanonicalDeserialize, CanonicalSerialize, Field, One, PairingEngine, PrimeField, SerializationError, Zero,
+};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use r1cs_core::{
+    lc, ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, LinearCombination, Matrix, SynthesisError,
+    Variable,
+};
+use setup_utils::{calculate_hash, domain_size, Error, UseMixedRadix};
+use std::{
+    io::{Read, Write},
+    marker::PhantomData,
+};
+use tracing::warn;
+
+/// Magic bytes at the start of a circuit file
+pub const CIRCUIT_FILE_MAGIC: &[u8; 4] = b"p2cs";
+/// Version of the circuit file format
+pub const CIRCUIT_FILE_VERSION: u32 = 1;
+
+const R1CS_MAGIC: &[u8; 4] = b"r1cs";
+const R1CS_VERSION: u32 = 1;
+const R1CS_HEADER_SECTION: u32 = 1;
+const R1CS_CONSTRAINTS_SECTION: u32 = 2;
+const R1CS_WIRE_TO_LABEL_SECTION: u32 = 3;
+
+const WTNS_MAGIC: &[u8; 4] = b"wtns";
+const WTNS_VERSION: u32 = 2;
+const WTNS_HEADER_SECTION: u32 = 1;
+const WTNS_WITNESS_SECTION: u32 = 2;
 
 // For serialization of the constraint system
 #[derive(Debug, PartialEq, CanonicalDeserialize, CanonicalSerialize, Clone)]
@@ -31,8 +57,774 @@ pub struct Matrices<E: PairingEngine> {
     pub c: Matrix<E::Fr>,
 }
 
+/// Summary of a circuit, as reported by `phase2 circuit-info`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitInfo {
+    pub num_constraints: usize,
+    pub num_instance_variables: usize,
+    pub num_witness_variables: usize,
+    pub a_num_non_zero: usize,
+    pub b_num_non_zero: usize,
+    pub c_num_non_zero: usize,
+    /// The fraction of non-zero entries over all of the matrices
+    pub density: f64,
+    /// The size of the radix-2 evaluation domain used by `phase2 new`
+    pub phase2_size: usize,
+    /// The size of a smaller mixed-radix domain, if the curve supports one.
+    /// `prepare_phase2` selects it when given this size, and `phase2 new` then
+    /// uses it instead.
+    pub mixed_radix_size: Option<usize>,
+    /// The smallest number of powers a phase 1 ceremony needs to support the circuit
+    pub required_phase1_power: usize,
+    /// Witness variables which do not appear in any constraint. These make the
+    /// L query sparse, so `MPCParameters::new` and `new_chunked` reject them
+    /// with `UnconstrainedVariable`.
+    pub unconstrained_variables: Vec<usize>,
+}
+
 impl<E: PairingEngine> Matrices<E> {
+    /// Reads and validates a circuit file written with `write`. Files without
+    /// a header, as written by previous versions, are still accepted but their
+    /// integrity cannot be checked beyond validating the matrices.
     pub fn read(input_map: &[u8]) -> Result<Self, Error> {
-        Ok(Matrices::deserialize(&mut &input_map[..])?)
+        let matrices = Matrices::deserialize(&mut matrices_bytes::<E>(input_map)?)?;
+        matrices.validate()?;
+        Ok(matrices)
+    }
+
+    /// Writes the matrices as a versioned circuit file, i.e. the
+    /// `circuit.constraints` file used by `phase2 new`. The header contains
+    /// the scalar field modulus and a BLAKE2b hash of the matrices.
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        let mut serialized = vec![];
+        self.serialize(&mut serialized)?;
+        let field_size = ((<E::Fr as PrimeField>::size_in_bits() + 63) / 64) * 8;
+
+        writer.write_all(CIRCUIT_FILE_MAGIC)?;
+        writer.write_u32::<LittleEndian>(CIRCUIT_FILE_VERSION)?;
+        writer.write_u32::<LittleEndian>(field_size as u32)?;
+        writer.write_all(&modulus_bytes::<E::Fr>(field_size))?;
+        writer.write_all(&calculate_hash(&serialized))?;
+        writer.write_all(&serialized)?;
+        Ok(())
+    }
+
+    /// Checks that the matrices are consistent with the declared number of
+    /// constraints, variables and non-zero entries
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.num_instance_variables == 0 {
+            return Err(invalid_circuit("missing the constant one instance variable"));
+        }
+        let num_variables = self.num_instance_variables + self.num_witness_variables;
+        for (name, matrix, num_non_zero) in &[
+            ("A", &self.a, self.a_num_non_zero),
+            ("B", &self.b, self.b_num_non_zero),
+            ("C", &self.c, self.c_num_non_zero),
+        ] {
+            if matrix.len() != self.num_constraints {
+                return Err(invalid_circuit(&format!(
+                    "{} has {} rows but there are {} constraints",
+                    name,
+                    matrix.len(),
+                    self.num_constraints
+                )));
+            }
+            let mut non_zero = 0;
+            for (constraint, row) in matrix.iter().enumerate() {
+                if let Some((_, variable)) = row.iter().find(|(_, variable)| *variable >= num_variables) {
+                    return Err(invalid_circuit(&format!(
+                        "variable {} in constraint {} of {} is out of range, there are {} variables",
+                        variable, constraint, name, num_variables
+                    )));
+                }
+                non_zero += row.len();
+            }
+            if non_zero != *num_non_zero {
+                return Err(invalid_circuit(&format!(
+                    "{} has {} non-zero entries but {} were declared",
+                    name, non_zero, num_non_zero
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Analyzes the circuit, which is expected to have been validated
+    pub fn info(&self) -> CircuitInfo {
+        let num_variables = self.num_instance_variables + self.num_witness_variables;
+        let mut constrained = vec![false; num_variables];
+        for matrix in &[&self.a, &self.b, &self.c] {
+            for (_, variable) in matrix.iter().flatten() {
+                constrained[*variable] = true;
+            }
+        }
+        let unconstrained_variables = (self.num_instance_variables..num_variables)
+            .filter(|variable| !constrained[*variable])
+            .collect();
+
+        let num_non_zero = self.a_num_non_zero + self.b_num_non_zero + self.c_num_non_zero;
+        let num_entries = 3 * self.num_constraints * num_variables;
+        let density = if num_entries == 0 {
+            0.0
+        } else {
+            num_non_zero as f64 / num_entries as f64
+        };
+        let phase2_size = self.phase2_size(UseMixedRadix::No);
+        let mixed_radix_size = Some(self.phase2_size(UseMixedRadix::Yes)).filter(|size| *size < phase2_size);
+
+        CircuitInfo {
+            num_constraints: self.num_constraints,
+            num_instance_variables: self.num_instance_variables,
+            num_witness_variables: self.num_witness_variables,
+            a_num_non_zero: self.a_num_non_zero,
+            b_num_non_zero: self.b_num_non_zero,
+            c_num_non_zero: self.c_num_non_zero,
+            density,
+            phase2_size,
+            mixed_radix_size,
+            required_phase1_power: phase2_size.next_power_of_two().trailing_zeros() as usize,
+            unconstrained_variables,
+        }
+    }
+
+    /// The size of the evaluation domain of the parameters `phase2 new` creates
+    /// for the circuit, i.e. the number of Lagrange coefficients they use. This
+    /// is the mixed-radix one if phase 1 was prepared for it.
+    pub fn phase2_size(&self, mixed_radix: UseMixedRadix) -> usize {
+        let num_variables = self.num_instance_variables + self.num_witness_variables;
+        domain_size::<E::Fr>(std::cmp::max(self.num_constraints, num_variables), mixed_radix)
+    }
+
+    /// The number of constraints before the instance density constraints
+    /// `x_i * 0 = 0` which `circuit_to_qap` and `read_r1cs` append. If the
+    /// matrices do not end with them, all of the constraints are counted.
+    pub fn num_circuit_constraints(&self) -> usize {
+        let start = match self.num_constraints.checked_sub(self.num_instance_variables) {
+            Some(start) => start,
+            None => return self.num_constraints,
+        };
+        let is_density_constraint = |i: usize| {
+            self.a[start + i] == [(E::Fr::one(), i)] && self.b[start + i].is_empty() && self.c[start + i].is_empty()
+        };
+        if (0..self.num_instance_variables).all(is_density_constraint) {
+            start
+        } else {
+            self.num_constraints
+        }
+    }
+
+    /// Extracts the matrices of a constraint system whose linear combinations
+    /// have been inlined, such as the one returned by `circuit_to_qap`
+    pub fn from_constraint_system(cs: &ConstraintSystemRef<E::Fr>) -> Result<Self, Error> {
+        let m = cs.to_matrices().ok_or(Error::MissingConstraintMatrices)?;
+        Ok(Matrices {
+            num_instance_variables: m.num_instance_variables,
+            num_witness_variables: m.num_witness_variables,
+            num_constraints: m.num_constraints,
+            a_num_non_zero: m.a_num_non_zero,
+            b_num_non_zero: m.b_num_non_zero,
+            c_num_non_zero: m.c_num_non_zero,
+            a: m.a,
+            b: m.b,
+            c: m.c,
+        })
+    }
+
+    /// Synthesizes a circuit with `circuit_to_qap`, including the instance density
+    /// constraints, and returns its matrices
+    pub fn from_circuit<C: ConstraintSynthesizer<E::Fr>>(circuit: C) -> Result<Self, Error> {
+        let cs = crate::parameters::circuit_to_qap::<E, C>(circuit)?;
+        Self::from_constraint_system(&cs)
+    }
+
+    /// Reads a circuit from circom's binary `.r1cs` format.
+    ///
+    /// Circom numbers its wires as the constant one, the public outputs, the
+    /// public inputs and then the private wires, which is the same order Zexe
+    /// uses for instance and witness variables, so wire ids are used as variable
+    /// indices directly. As with `circuit_to_qap`, a `x * 0 = 0` constraint is
+    /// appended for every instance variable to ensure full density of the IC query.
+    pub fn read_r1cs(input_map: &[u8]) -> Result<Self, Error> {
+        let mut reader = input_map;
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != R1CS_MAGIC {
+            return Err(invalid_r1cs("invalid magic"));
+        }
+        let version = reader.read_u32::<LittleEndian>()?;
+        if version != R1CS_VERSION {
+            return Err(invalid_r1cs(&format!("unsupported version {}", version)));
+        }
+
+        // sections may appear in any order, so they are located before parsing
+        let num_sections = reader.read_u32::<LittleEndian>()?;
+        let mut header = None;
+        let mut constraints = None;
+        let mut wire_to_label = None;
+        for _ in 0..num_sections {
+            let section_type = reader.read_u32::<LittleEndian>()?;
+            let section_size = reader.read_u64::<LittleEndian>()? as usize;
+            if reader.len() < section_size {
+                return Err(invalid_r1cs("truncated section"));
+            }
+            let (section, rest) = reader.split_at(section_size);
+            reader = rest;
+            match section_type {
+                R1CS_HEADER_SECTION => header = Some(section),
+                R1CS_CONSTRAINTS_SECTION => constraints = Some(section),
+                R1CS_WIRE_TO_LABEL_SECTION => wire_to_label = Some(section),
+                // custom gates are not expressible as R1CS
+                _ => return Err(invalid_r1cs(&format!("unsupported section type {}", section_type))),
+            }
+        }
+        let mut header = header.ok_or_else(|| invalid_r1cs("missing header section"))?;
+        let mut constraints = constraints.ok_or_else(|| invalid_r1cs("missing constraints section"))?;
+        let wire_to_label = wire_to_label.ok_or_else(|| invalid_r1cs("missing wire to label section"))?;
+
+        // header
+        let field_size = header.read_u32::<LittleEndian>()? as usize;
+        let mut prime = vec![0u8; field_size];
+        header.read_exact(&mut prime)?;
+        if prime != modulus_bytes::<E::Fr>(field_size) {
+            return Err(invalid_r1cs("the prime does not match the scalar field of the curve"));
+        }
+        let num_wires = header.read_u32::<LittleEndian>()? as usize;
+        let num_public_outputs = header.read_u32::<LittleEndian>()? as usize;
+        let num_public_inputs = header.read_u32::<LittleEndian>()? as usize;
+        let _num_private_inputs = header.read_u32::<LittleEndian>()?;
+        let _num_labels = header.read_u64::<LittleEndian>()?;
+        let num_constraints = header.read_u32::<LittleEndian>()? as usize;
+
+        let num_instance_variables = 1 + num_public_outputs + num_public_inputs;
+        if num_wires < num_instance_variables {
+            return Err(invalid_r1cs("fewer wires than public variables"));
+        }
+
+        // constraints
+        let mut a: Matrix<E::Fr> = Vec::with_capacity(num_constraints + num_instance_variables);
+        let mut b: Matrix<E::Fr> = Vec::with_capacity(num_constraints + num_instance_variables);
+        let mut c: Matrix<E::Fr> = Vec::with_capacity(num_constraints + num_instance_variables);
+        let mut coeff_bytes = vec![0u8; field_size];
+        for _ in 0..num_constraints {
+            for matrix in [&mut a, &mut b, &mut c].iter_mut() {
+                let num_factors = constraints.read_u32::<LittleEndian>()?;
+                let mut row = Vec::with_capacity(num_factors as usize);
+                for _ in 0..num_factors {
+                    let wire = constraints.read_u32::<LittleEndian>()? as usize;
+                    if wire >= num_wires {
+                        return Err(invalid_r1cs(&format!("wire {} out of range", wire)));
+                    }
+                    constraints.read_exact(&mut coeff_bytes)?;
+                    let coeff = E::Fr::deserialize(&mut &coeff_bytes[..])?;
+                    row.push((coeff, wire));
+                }
+                matrix.push(compact_row(row));
+            }
+        }
+        if !constraints.is_empty() {
+            return Err(invalid_r1cs("trailing data in constraints section"));
+        }
+
+        // the wire to label map is not needed beyond checking its size
+        if wire_to_label.len() != num_wires * 8 {
+            return Err(Error::InvalidLength {
+                expected: num_wires,
+                got: wire_to_label.len() / 8,
+            });
+        }
+
+        // Input constraints to ensure full density of IC query
+        // x * 0 = 0
+        for i in 0..num_instance_variables {
+            a.push(vec![(E::Fr::one(), i)]);
+            b.push(vec![]);
+            c.push(vec![]);
+        }
+
+        let num_non_zero = |matrix: &Matrix<E::Fr>| -> usize { matrix.iter().map(|row| row.len()).sum() };
+        Ok(Matrices {
+            num_instance_variables,
+            num_witness_variables: num_wires - num_instance_variables,
+            num_constraints: num_constraints + num_instance_variables,
+            a_num_non_zero: num_non_zero(&a),
+            b_num_non_zero: num_non_zero(&b),
+            c_num_non_zero: num_non_zero(&c),
+            a,
+            b,
+            c,
+        })
+    }
+
+    /// Returns the index of the first constraint which the full assignment,
+    /// starting with the constant one, does not satisfy
+    pub fn unsatisfied_constraint(&self, assignment: &[E::Fr]) -> Option<usize> {
+        let evaluate = |row: &[(E::Fr, usize)]| -> E::Fr {
+            row.iter().fold(E::Fr::zero(), |acc, (coeff, variable)| {
+                acc + &(*coeff * &assignment[*variable])
+            })
+        };
+        (0..self.num_constraints).find(|&i| evaluate(&self.a[i]) * &evaluate(&self.b[i]) != evaluate(&self.c[i]))
+    }
+}
+
+/// A circuit which enforces the constraints of `Matrices` for an assignment,
+/// so that proofs can be created for circuits which are only available as a
+/// circuit file, e.g. when they were compiled by circom.
+///
+/// The instance density constraints at the end of the matrices, if any, are
+/// skipped, since the Groth16 prover adds them itself.
+pub struct MatricesCircuit<'a, E: PairingEngine> {
+    pub matrices: &'a Matrices<E>,
+    /// The full assignment, starting with the constant one
+    pub assignment: &'a [E::Fr],
+}
+
+impl<'a, E: PairingEngine> ConstraintSynthesizer<E::Fr> for MatricesCircuit<'a, E> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<E::Fr>) -> std::result::Result<(), SynthesisError> {
+        let num_instance_variables = self.matrices.num_instance_variables;
+        let num_variables = num_instance_variables + self.matrices.num_witness_variables;
+        if self.assignment.len() != num_variables {
+            return Err(SynthesisError::AssignmentMissing);
+        }
+
+        let mut variables = vec![Variable::One];
+        for value in &self.assignment[1..num_instance_variables] {
+            variables.push(cs.new_input_variable(|| Ok(*value))?);
+        }
+        for value in &self.assignment[num_instance_variables..] {
+            variables.push(cs.new_witness_variable(|| Ok(*value))?);
+        }
+
+        let to_lc = |row: &[(E::Fr, usize)]| -> LinearCombination<E::Fr> {
+            row.iter()
+                .fold(lc!(), |lc, (coeff, variable)| lc + (*coeff, variables[*variable]))
+        };
+        for i in 0..self.matrices.num_circuit_constraints() {
+            cs.enforce_constraint(
+                to_lc(&self.matrices.a[i]),
+                to_lc(&self.matrices.b[i]),
+                to_lc(&self.matrices.c[i]),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Synthesizes a circuit with its witness and returns the full assignment,
+/// starting with the constant one, in the order of the variables of `Matrices`
+pub fn synthesize_assignment<F: Field, C: ConstraintSynthesizer<F>>(circuit: C) -> Result<Vec<F>, SynthesisError> {
+    let cs = ConstraintSystem::new_ref();
+    circuit.generate_constraints(cs.clone())?;
+    let cs = cs.borrow().expect("should have borrowed the constraint system");
+    Ok(cs
+        .instance_assignment
+        .iter()
+        .chain(&cs.witness_assignment)
+        .cloned()
+        .collect())
+}
+
+/// Reads a full assignment from the binary `.wtns` format of snarkjs, as
+/// computed by the witness calculators circom generates.
+pub fn read_wtns<E: PairingEngine>(input_map: &[u8]) -> Result<Vec<E::Fr>, Error> {
+    let mut reader = input_map;
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != WTNS_MAGIC {
+        return Err(invalid_wtns("invalid magic"));
+    }
+    let version = reader.read_u32::<LittleEndian>()?;
+    if version != WTNS_VERSION {
+        return Err(invalid_wtns(&format!("unsupported version {}", version)));
+    }
+
+    let num_sections = reader.read_u32::<LittleEndian>()?;
+    let mut header = None;
+    let mut witness = None;
+    for _ in 0..num_sections {
+        let section_type = reader.read_u32::<LittleEndian>()?;
+        let section_size = reader.read_u64::<LittleEndian>()? as usize;
+        if reader.len() < section_size {
+            return Err(invalid_wtns("truncated section"));
+        }
+        let (section, rest) = reader.split_at(section_size);
+        reader = rest;
+        match section_type {
+            WTNS_HEADER_SECTION => header = Some(section),
+            WTNS_WITNESS_SECTION => witness = Some(section),
+            _ => return Err(invalid_wtns(&format!("unsupported section type {}", section_type))),
+        }
+    }
+    let mut header = header.ok_or_else(|| invalid_wtns("missing header section"))?;
+    let mut witness = witness.ok_or_else(|| invalid_wtns("missing witness section"))?;
+
+    let field_size = header.read_u32::<LittleEndian>()? as usize;
+    let mut prime = vec![0u8; field_size];
+    header.read_exact(&mut prime)?;
+    if prime != modulus_bytes::<E::Fr>(field_size) {
+        return Err(invalid_wtns("the prime does not match the scalar field of the curve"));
+    }
+    let num_values = header.read_u32::<LittleEndian>()? as usize;
+    if witness.len() != num_values * field_size {
+        return Err(Error::InvalidLength {
+            expected: num_values,
+            got: witness.len() / field_size,
+        });
+    }
+
+    let mut value_bytes = vec![0u8; field_size];
+    let mut assignment = Vec::with_capacity(num_values);
+    for _ in 0..num_values {
+        witness.read_exact(&mut value_bytes)?;
+        assignment.push(E::Fr::deserialize(&mut &value_bytes[..])?);
+    }
+    if assignment.first() != Some(&E::Fr::one()) {
+        return Err(invalid_wtns("the first value must be the constant one"));
+    }
+    Ok(assignment)
+}
+
+/// A circuit file whose matrices are read one constraint at a time rather than
+/// deserialized at once, so that circuits which do not fit in memory can be
+/// processed. The matrices are only checked as they are read.
+pub struct CircuitFile<'a, E: PairingEngine> {
+    pub num_instance_variables: usize,
+    pub num_witness_variables: usize,
+    pub num_constraints: usize,
+    /// The number of non-zero entries of the A, B and C matrices
+    pub num_non_zero: [usize; 3],
+    /// The serialized A, B and C matrices
+    matrices: [&'a [u8]; 3],
+    _engine: PhantomData<E>,
+}
+
+impl<'a, E: PairingEngine> CircuitFile<'a, E> {
+    /// Reads the header and locates the matrices of a circuit file written
+    /// with `Matrices::write`, which is typically memory mapped
+    pub fn new(input_map: &'a [u8]) -> Result<Self, Error> {
+        let mut reader = matrices_bytes::<E>(input_map)?;
+        let num_instance_variables = usize::deserialize(&mut reader)?;
+        let num_witness_variables = usize::deserialize(&mut reader)?;
+        let num_constraints = usize::deserialize(&mut reader)?;
+        let mut num_non_zero = [0; 3];
+        for num_non_zero in num_non_zero.iter_mut() {
+            *num_non_zero = usize::deserialize(&mut reader)?;
+        }
+
+        // skip over the rows of each matrix to find where the next one starts
+        let entry_size = E::Fr::zero().serialized_size() + 8;
+        let mut matrices = [&reader[..0]; 3];
+        for matrix in matrices.iter_mut() {
+            let start = reader;
+            if usize::deserialize(&mut reader)? != num_constraints {
+                return Err(invalid_circuit("a matrix does not have a row per constraint"));
+            }
+            for _ in 0..num_constraints {
+                let row_size = usize::deserialize(&mut reader)?
+                    .checked_mul(entry_size)
+                    .filter(|row_size| *row_size <= reader.len())
+                    .ok_or_else(|| invalid_circuit("a matrix is truncated"))?;
+                reader = &reader[row_size..];
+            }
+            *matrix = &start[..start.len() - reader.len()];
+        }
+
+        Ok(CircuitFile {
+            num_instance_variables,
+            num_witness_variables,
+            num_constraints,
+            num_non_zero,
+            matrices,
+            _engine: PhantomData,
+        })
+    }
+
+    /// Iterates over the rows, i.e. the constraints, of the A (0), B (1) or C (2) matrix
+    pub fn rows(&self, matrix: usize) -> impl Iterator<Item = Result<Vec<(E::Fr, usize)>, Error>> + 'a {
+        // skip the number of rows
+        let mut reader = &self.matrices[matrix][8..];
+        (0..self.num_constraints).map(move |_| Vec::<(E::Fr, usize)>::deserialize(&mut reader).map_err(Error::from))
+    }
+}
+
+/// Checks the header of a circuit file, if it has one, and returns the
+/// serialized matrices
+fn matrices_bytes<E: PairingEngine>(input_map: &[u8]) -> Result<&[u8], Error> {
+    if !input_map.starts_with(CIRCUIT_FILE_MAGIC) {
+        warn!("circuit file has no header, its integrity cannot be checked");
+        return Ok(input_map);
+    }
+    let mut reader = &input_map[CIRCUIT_FILE_MAGIC.len()..];
+    let version = reader.read_u32::<LittleEndian>()?;
+    if version != CIRCUIT_FILE_VERSION {
+        return Err(invalid_circuit(&format!("unsupported version {}", version)));
+    }
+    let field_size = reader.read_u32::<LittleEndian>()? as usize;
+    let mut modulus = vec![0u8; field_size];
+    reader.read_exact(&mut modulus)?;
+    if modulus != modulus_bytes::<E::Fr>(field_size) {
+        return Err(invalid_circuit("the circuit is defined over a different field"));
+    }
+    let mut hash = [0u8; 64];
+    reader.read_exact(&mut hash)?;
+    if calculate_hash(reader).as_slice() != &hash[..] {
+        return Err(invalid_circuit("hash mismatch"));
+    }
+    Ok(reader)
+}
+
+fn invalid_circuit(reason: &str) -> Error {
+    Error::InvalidCircuitFile(reason.to_string())
+}
+
+fn invalid_r1cs(reason: &str) -> Error {
+    Error::InvalidCircuitFile(format!("r1cs: {}", reason))
+}
+
+fn invalid_wtns(reason: &str) -> Error {
+    Error::InvalidWitnessFile(reason.to_string())
+}
+
+/// Sorts a row by variable, merging repeated variables and dropping zero
+/// coefficients, as Zexe does when inlining linear combinations
+fn compact_row<F: Field>(mut row: Vec<(F, usize)>) -> Vec<(F, usize)> {
+    row.sort_by_key(|(_, variable)| *variable);
+    let mut compacted: Vec<(F, usize)> = Vec::with_capacity(row.len());
+    for (coeff, variable) in row {
+        match compacted.last_mut() {
+            Some((last_coeff, last_variable)) if *last_variable == variable => *last_coeff += &coeff,
+            _ => compacted.push((coeff, variable)),
+        }
+    }
+    compacted.retain(|(coeff, _)| !coeff.is_zero());
+    compacted
+}
+
+/// Returns the field modulus as `size` little-endian bytes, as in the headers
+/// of the circom and snarkjs file formats
+pub fn modulus_bytes<F: Field>(size: usize) -> Vec<u8> {
+    let mut bytes = F::characteristic()
+        .iter()
+        .flat_map(|limb| limb.to_le_bytes().to_vec())
+        .collect::<Vec<_>>();
+    bytes.resize(size, 0);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::testing::TestCircuit;
+    use algebra::{bls12_377::Fr, Bls12_377};
+
+    fn write_section(writer: &mut Vec<u8>, section_type: u32, section: &[u8]) {
+        writer.write_u32::<LittleEndian>(section_type).unwrap();
+        writer.write_u64::<LittleEndian>(section.len() as u64).unwrap();
+        writer.extend_from_slice(section);
+    }
+
+    // `TestCircuit` as compiled by circom: wire 1 is the public output `out`
+    // and wire 2 the private input `x`, with 4 constraints `x * x = out`
+    fn test_circuit_r1cs<E: PairingEngine>() -> Vec<u8> {
+        let field_size = ((<E::Fr as PrimeField>::size_in_bits() + 63) / 64) * 8;
+        let mut header = vec![];
+        header.write_u32::<LittleEndian>(field_size as u32).unwrap();
+        header.extend(modulus_bytes::<E::Fr>(field_size));
+        for &count in &[3u32, 1, 0, 1] {
+            header.write_u32::<LittleEndian>(count).unwrap();
+        }
+        header.write_u64::<LittleEndian>(3).unwrap();
+        header.write_u32::<LittleEndian>(4).unwrap();
+
+        let mut constraints = vec![];
+        for _ in 0..4 {
+            for &wire in &[2u32, 2, 1] {
+                constraints.write_u32::<LittleEndian>(1).unwrap();
+                constraints.write_u32::<LittleEndian>(wire).unwrap();
+                E::Fr::one().serialize(&mut constraints).unwrap();
+            }
+        }
+
+        let mut wire_to_label = vec![];
+        for label in 0..3u64 {
+            wire_to_label.write_u64::<LittleEndian>(label).unwrap();
+        }
+
+        let mut r1cs = vec![];
+        r1cs.extend_from_slice(R1CS_MAGIC);
+        r1cs.write_u32::<LittleEndian>(R1CS_VERSION).unwrap();
+        r1cs.write_u32::<LittleEndian>(3).unwrap();
+        // sections are not required to be in order
+        write_section(&mut r1cs, R1CS_CONSTRAINTS_SECTION, &constraints);
+        write_section(&mut r1cs, R1CS_HEADER_SECTION, &header);
+        write_section(&mut r1cs, R1CS_WIRE_TO_LABEL_SECTION, &wire_to_label);
+        r1cs
+    }
+
+    #[test]
+    fn r1cs_matches_zexe_circuit() {
+        r1cs_matches_zexe_circuit_curve::<Bls12_377>()
+    }
+
+    fn r1cs_matches_zexe_circuit_curve<E: PairingEngine>() {
+        let expected = Matrices::<E>::from_circuit(TestCircuit::<E>(None)).unwrap();
+        let matrices = Matrices::<E>::read_r1cs(&test_circuit_r1cs::<E>()).unwrap();
+        assert_eq!(matrices, expected);
+    }
+
+    #[test]
+    fn write_read_round_trip() {
+        let matrices = Matrices::<Bls12_377>::from_circuit(TestCircuit::<Bls12_377>(None)).unwrap();
+        // 4 constraints of the circuit and 2 for the instance variables
+        assert_eq!(matrices.num_constraints, 6);
+        assert_eq!(matrices.a_num_non_zero, 6);
+
+        let mut serialized = vec![];
+        matrices.write(&mut serialized).unwrap();
+        assert_eq!(Matrices::<Bls12_377>::read(&serialized).unwrap(), matrices);
+
+        // the matrices are covered by the hash
+        let last = serialized.len() - 1;
+        serialized[last] ^= 1;
+        assert!(Matrices::<Bls12_377>::read(&serialized).is_err());
+    }
+
+    #[test]
+    fn read_legacy_circuit_file() {
+        let matrices = Matrices::<Bls12_377>::from_circuit(TestCircuit::<Bls12_377>(None)).unwrap();
+        let mut serialized = vec![];
+        matrices.serialize(&mut serialized).unwrap();
+        assert_eq!(Matrices::<Bls12_377>::read(&serialized).unwrap(), matrices);
+    }
+
+    #[test]
+    fn validate_rejects_inconsistent_matrices() {
+        let matrices = Matrices::<Bls12_377>::from_circuit(TestCircuit::<Bls12_377>(None)).unwrap();
+        matrices.validate().unwrap();
+
+        let mut missing_row = matrices.clone();
+        missing_row.b.pop();
+        assert!(missing_row.validate().is_err());
+
+        let mut out_of_range = matrices.clone();
+        out_of_range.c[0][0].1 = 3;
+        assert!(out_of_range.validate().is_err());
+
+        let mut wrong_count = matrices;
+        wrong_count.a_num_non_zero += 1;
+        assert!(wrong_count.validate().is_err());
+    }
+
+    #[test]
+    fn info_reports_unconstrained_variables() {
+        let mut matrices = Matrices::<Bls12_377>::from_circuit(TestCircuit::<Bls12_377>(None)).unwrap();
+        let info = matrices.info();
+        assert_eq!(info.num_constraints, 6);
+        assert_eq!(info.phase2_size, 8);
+        assert_eq!(info.mixed_radix_size, None);
+        assert_eq!(info.required_phase1_power, 3);
+        assert!(info.unconstrained_variables.is_empty());
+
+        // a witness variable which is never used
+        matrices.num_witness_variables += 1;
+        assert_eq!(matrices.info().unconstrained_variables, vec![3]);
+    }
+
+    #[test]
+    fn r1cs_rejects_wrong_field() {
+        let r1cs = test_circuit_r1cs::<algebra::BW6_761>();
+        assert!(Matrices::<Bls12_377>::read_r1cs(&r1cs).is_err());
+    }
+
+    fn test_circuit_wtns<E: PairingEngine>(assignment: &[E::Fr]) -> Vec<u8> {
+        let field_size = ((<E::Fr as PrimeField>::size_in_bits() + 63) / 64) * 8;
+        let mut header = vec![];
+        header.write_u32::<LittleEndian>(field_size as u32).unwrap();
+        header.extend(modulus_bytes::<E::Fr>(field_size));
+        header.write_u32::<LittleEndian>(assignment.len() as u32).unwrap();
+
+        let mut witness = vec![];
+        for value in assignment {
+            value.serialize(&mut witness).unwrap();
+        }
+
+        let mut wtns = vec![];
+        wtns.extend_from_slice(WTNS_MAGIC);
+        wtns.write_u32::<LittleEndian>(WTNS_VERSION).unwrap();
+        wtns.write_u32::<LittleEndian>(2).unwrap();
+        write_section(&mut wtns, WTNS_HEADER_SECTION, &header);
+        write_section(&mut wtns, WTNS_WITNESS_SECTION, &witness);
+        wtns
+    }
+
+    // the constant one, `out` and `x`
+    fn test_circuit_assignment<E: PairingEngine>(x: u64) -> Vec<E::Fr> {
+        vec![
+            E::Fr::one(),
+            <E::Fr as From<u64>>::from(x * x),
+            <E::Fr as From<u64>>::from(x),
+        ]
+    }
+
+    #[test]
+    fn wtns_round_trip() {
+        let assignment = test_circuit_assignment::<Bls12_377>(5);
+        let wtns = test_circuit_wtns::<Bls12_377>(&assignment);
+        assert_eq!(read_wtns::<Bls12_377>(&wtns).unwrap(), assignment);
+
+        let mut truncated = wtns.clone();
+        truncated.pop();
+        assert!(read_wtns::<Bls12_377>(&truncated).is_err());
+        assert!(read_wtns::<algebra::BW6_761>(&wtns).is_err());
+    }
+
+    #[test]
+    fn matrices_circuit_proves() {
+        let rng = &mut rand::thread_rng();
+        let params =
+            groth16::generate_random_parameters::<Bls12_377, _, _>(TestCircuit::<Bls12_377>(None), rng).unwrap();
+        let pvk = groth16::prepare_verifying_key(&params.vk);
+        let matrices = Matrices::<Bls12_377>::from_circuit(TestCircuit::<Bls12_377>(None)).unwrap();
+
+        let assignment = test_circuit_assignment::<Bls12_377>(5);
+        assert_eq!(matrices.unsatisfied_constraint(&assignment), None);
+        let circuit = MatricesCircuit {
+            matrices: &matrices,
+            assignment: &assignment,
+        };
+        let proof = groth16::create_random_proof(circuit, &params, rng).unwrap();
+        assert!(groth16::verify_proof(&pvk, &proof, &assignment[1..2]).unwrap());
+
+        let mut wrong = assignment;
+        wrong[2] = Fr::from(4u64);
+        assert_eq!(matrices.unsatisfied_constraint(&wrong), Some(0));
+    }
+
+    #[test]
+    fn density_constraints_are_detected() {
+        let mut matrices = Matrices::<Bls12_377>::from_circuit(TestCircuit::<Bls12_377>(None)).unwrap();
+        assert_eq!(matrices.num_circuit_constraints(), 4);
+
+        // without the density constraints, every constraint belongs to the circuit
+        for matrix in &mut [&mut matrices.a, &mut matrices.b, &mut matrices.c] {
+            matrix.truncate(4);
+        }
+        matrices.num_constraints = 4;
+        assert_eq!(matrices.num_circuit_constraints(), 4);
+
+        // fewer constraints than instance variables
+        for matrix in &mut [&mut matrices.a, &mut matrices.b, &mut matrices.c] {
+            matrix.truncate(1);
+        }
+        matrices.num_constraints = 1;
+        assert_eq!(matrices.num_circuit_constraints(), 1);
+    }
+
+    #[test]
+    fn synthesized_assignment_satisfies_circuit() {
+        let matrices = Matrices::<Bls12_377>::from_circuit(TestCircuit::<Bls12_377>(None)).unwrap();
+        let assignment = synthesize_assignment(TestCircuit::<Bls12_377>(Some(Fr::from(5u64)))).unwrap();
+        assert_eq!(assignment, test_circuit_assignment::<Bls12_377>(5));
+        assert_eq!(matrices.unsatisfied_constraint(&assignment), None);
     }
 }
@@ -0,0 +1,382 @@
+//! A memory-mappable layout of the combined Groth16 parameters.
+//!
+//! Deserializing the `.params` file of a large circuit needs as much memory as
+//! the file itself. This layout stores each query as an array of points in
+//! their in-memory representation, every section aligned to `ALIGNMENT` bytes,
+//! so that `MmapParams` borrows the queries from a memory map of the file as
+//! slices of points, which provers can pass to their multi-scalar
+//! multiplications as bases without decoding them.
+//!
+//! ```text
+//! magic "p2pm" | version: u32 | G1 size: u32 | G2 size: u32 | layout hash: [u8; 64]
+//! (offset: u64, length: u64) for each of the 8 sections
+//! fixed section: beta_g1 | delta_g1 | verifying key, serialized uncompressed
+//! G1 generator | G2 generator
+//! a_query | b_g1_query | b_g2_query | h_query | l_query
+//! ```
+//!
+//! Integers are little-endian. The length of the fixed section is in bytes,
+//! the length of the other sections in points. The layout hash identifies the
+//! types of the points and the endianness of the machine which wrote the file,
+//! and the generators are compared with the ones of the reader, since the
+//! in-memory representation of points is only stable within the same build.
+use setup_utils::{calculate_hash, Error, Result};
+
+use algebra::{
+    bls12_377, bw6_761, short_weierstrass_jacobian::GroupAffine, AffineCurve, CanonicalDeserialize, CanonicalSerialize,
+    PairingEngine, SWModelParameters,
+};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use groth16::{Parameters, VerifyingKey};
+use std::{
+    any::Any,
+    io::Write,
+    mem::{align_of, size_of, size_of_val},
+    ops::Range,
+    slice,
+};
+
+const MMAP_MAGIC: &[u8; 4] = b"p2pm";
+const MMAP_VERSION: u32 = 2;
+/// The fixed section, the two generators and the 5 queries
+const NUM_SECTIONS: usize = 8;
+const HEADER_SIZE: usize = 16 + 64 + NUM_SECTIONS * 16;
+
+/// The alignment of every section in the file, which is also the alignment in
+/// memory when the file is mapped at a page boundary
+pub const ALIGNMENT: usize = 64;
+
+fn align(position: usize) -> usize {
+    (position + ALIGNMENT - 1) / ALIGNMENT * ALIGNMENT
+}
+
+/// Identifies the in-memory representation of the points of the curve
+fn layout_hash<E: PairingEngine>() -> [u8; 64] {
+    let layout = format!(
+        "{} {} {}",
+        std::any::type_name::<E::G1Affine>(),
+        std::any::type_name::<E::G2Affine>(),
+        if cfg!(target_endian = "little") { "le" } else { "be" }
+    );
+    calculate_hash(layout.as_bytes())
+}
+
+/// Writes the parameters in the memory-mappable layout
+pub fn write_mmap_params<E: PairingEngine, W: Write>(params: &Parameters<E>, mut writer: W) -> Result<()> {
+    let mut fixed = vec![];
+    params.beta_g1.serialize_uncompressed(&mut fixed)?;
+    params.delta_g1.serialize_uncompressed(&mut fixed)?;
+    params.vk.serialize_uncompressed(&mut fixed)?;
+
+    let g1_size = size_of::<E::G1Affine>();
+    let g2_size = size_of::<E::G2Affine>();
+    let lengths = [
+        (fixed.len(), 1),
+        (1, g1_size),
+        (1, g2_size),
+        (params.a_query.len(), g1_size),
+        (params.b_g1_query.len(), g1_size),
+        (params.b_g2_query.len(), g2_size),
+        (params.h_query.len(), g1_size),
+        (params.l_query.len(), g1_size),
+    ];
+
+    writer.write_all(MMAP_MAGIC)?;
+    writer.write_u32::<LittleEndian>(MMAP_VERSION)?;
+    writer.write_u32::<LittleEndian>(g1_size as u32)?;
+    writer.write_u32::<LittleEndian>(g2_size as u32)?;
+    writer.write_all(&layout_hash::<E>())?;
+    let mut offset = align(HEADER_SIZE);
+    let mut offsets = vec![];
+    for (length, stride) in lengths.iter() {
+        writer.write_u64::<LittleEndian>(offset as u64)?;
+        writer.write_u64::<LittleEndian>(*length as u64)?;
+        offsets.push(offset);
+        offset = align(offset + length * stride);
+    }
+
+    let mut padded = PaddedWriter {
+        writer,
+        position: HEADER_SIZE,
+    };
+    padded.pad_to(offsets[0])?;
+    padded.write_all(&fixed)?;
+    padded.pad_to(offsets[1])?;
+    padded.write_points(&[E::G1Affine::prime_subgroup_generator()])?;
+    padded.pad_to(offsets[2])?;
+    padded.write_points(&[E::G2Affine::prime_subgroup_generator()])?;
+    padded.pad_to(offsets[3])?;
+    padded.write_points(&params.a_query)?;
+    padded.pad_to(offsets[4])?;
+    padded.write_points(&params.b_g1_query)?;
+    padded.pad_to(offsets[5])?;
+    padded.write_points(&params.b_g2_query)?;
+    padded.pad_to(offsets[6])?;
+    padded.write_points(&params.h_query)?;
+    padded.pad_to(offsets[7])?;
+    padded.write_points(&params.l_query)?;
+    Ok(())
+}
+
+/// A writer which keeps track of its position to pad the sections
+struct PaddedWriter<W> {
+    writer: W,
+    position: usize,
+}
+
+impl<W: Write> PaddedWriter<W> {
+    fn write_all(&mut self, bytes: &[u8]) -> Result<()> {
+        self.writer.write_all(bytes)?;
+        self.position += bytes.len();
+        Ok(())
+    }
+
+    fn pad_to(&mut self, offset: usize) -> Result<()> {
+        self.write_all(&vec![0u8; offset - self.position])
+    }
+
+    /// Writes the points as they are laid out in memory. Only the bytes of their
+    /// fields are copied, the padding of their representation is written as
+    /// zeroes.
+    fn write_points<C: AffineCurve>(&mut self, points: &[C]) -> Result<()> {
+        let layout = PointLayout::of::<C>()?;
+        let mut buffer = vec![0u8; size_of::<C>()];
+        for point in points {
+            let bytes = point as *const C as *const u8;
+            for field in &[layout.x.clone(), layout.y.clone(), layout.infinity..layout.infinity + 1] {
+                // the fields are initialized, unlike the padding around them
+                let field_bytes = unsafe { slice::from_raw_parts(bytes.add(field.start), field.len()) };
+                buffer[field.clone()].copy_from_slice(field_bytes);
+            }
+            self.write_all(&buffer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Where the fields of a point are in its in-memory representation
+struct PointLayout {
+    x: Range<usize>,
+    y: Range<usize>,
+    infinity: usize,
+}
+
+impl PointLayout {
+    /// Finds the fields of the points of a supported short Weierstrass curve
+    fn of<C: AffineCurve>() -> Result<Self> {
+        let layouts: &[fn() -> Option<PointLayout>] = &[
+            PointLayout::of_curve::<C, bls12_377::g1::Parameters>,
+            PointLayout::of_curve::<C, bls12_377::g2::Parameters>,
+            PointLayout::of_curve::<C, bw6_761::g1::Parameters>,
+            PointLayout::of_curve::<C, bw6_761::g2::Parameters>,
+        ];
+        layouts
+            .iter()
+            .find_map(|layout| layout())
+            .ok_or_else(|| invalid_params("the points are not those of a supported curve"))
+    }
+
+    fn of_curve<C: AffineCurve, P: SWModelParameters>() -> Option<Self> {
+        let generator = C::prime_subgroup_generator();
+        let point = (&generator as &dyn Any).downcast_ref::<GroupAffine<P>>()?;
+        let start = point as *const GroupAffine<P> as usize;
+        let offset = |field: *const u8| field as usize - start;
+        let x = offset(&point.x as *const P::BaseField as *const u8);
+        let y = offset(&point.y as *const P::BaseField as *const u8);
+        Some(PointLayout {
+            x: x..x + size_of_val(&point.x),
+            y: y..y + size_of_val(&point.y),
+            infinity: offset(&point.infinity as *const bool as *const u8),
+        })
+    }
+}
+
+/// Groth16 parameters read from the memory-mappable layout. Only the fixed
+/// elements are decoded, the queries are borrowed from the underlying bytes.
+pub struct MmapParams<'a, E: PairingEngine> {
+    pub beta_g1: E::G1Affine,
+    pub delta_g1: E::G1Affine,
+    pub vk: VerifyingKey<E>,
+    pub a_query: &'a [E::G1Affine],
+    pub b_g1_query: &'a [E::G1Affine],
+    pub b_g2_query: &'a [E::G2Affine],
+    pub h_query: &'a [E::G1Affine],
+    pub l_query: &'a [E::G1Affine],
+}
+
+impl<'a, E: PairingEngine> MmapParams<'a, E> {
+    /// Reads the header and the fixed elements, checks that every section lies
+    /// within `bytes` and borrows the queries from it.
+    ///
+    /// # Safety
+    ///
+    /// The queries are borrowed as they are, so `bytes` must have been written
+    /// by `write_mmap_params` from a build with the same in-memory
+    /// representation of points. The header and the generators catch files
+    /// written for other curves or machines, but the points of the queries are
+    /// not checked, neither to be valid nor to be on the curve.
+    pub unsafe fn new(bytes: &'a [u8]) -> Result<Self> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(invalid_params("the file is too short for its header"));
+        }
+        if &bytes[..4] != MMAP_MAGIC {
+            return Err(invalid_params("invalid magic"));
+        }
+        let mut header = &bytes[4..HEADER_SIZE];
+        let version = header.read_u32::<LittleEndian>()?;
+        if version != MMAP_VERSION {
+            return Err(invalid_params(&format!("unsupported version {}", version)));
+        }
+        let g1_size = header.read_u32::<LittleEndian>()? as usize;
+        let g2_size = header.read_u32::<LittleEndian>()? as usize;
+        let (layout, mut header) = header.split_at(64);
+        if g1_size != size_of::<E::G1Affine>()
+            || g2_size != size_of::<E::G2Affine>()
+            || layout != &layout_hash::<E>()[..]
+        {
+            return Err(invalid_params(
+                "the points are not laid out as this build lays out the points of the curve",
+            ));
+        }
+
+        let strides = [1, g1_size, g2_size, g1_size, g1_size, g2_size, g1_size, g1_size];
+        let mut sections = vec![];
+        for (i, stride) in strides.iter().enumerate() {
+            let offset = header.read_u64::<LittleEndian>()? as usize;
+            let length = header.read_u64::<LittleEndian>()? as usize;
+            if offset % ALIGNMENT != 0 {
+                return Err(invalid_params(&format!("section {} is not aligned", i)));
+            }
+            let end = length
+                .checked_mul(*stride)
+                .and_then(|size| size.checked_add(offset))
+                .ok_or_else(|| invalid_params(&format!("section {} is too long", i)))?;
+            if end > bytes.len() {
+                return Err(invalid_params(&format!("section {} ends after the end of the file", i)));
+            }
+            sections.push(offset..end);
+        }
+
+        let g1_generator = points::<E::G1Affine>(bytes, sections[1].clone())?;
+        let g2_generator = points::<E::G2Affine>(bytes, sections[2].clone())?;
+        if g1_generator != [E::G1Affine::prime_subgroup_generator()]
+            || g2_generator != [E::G2Affine::prime_subgroup_generator()]
+        {
+            return Err(invalid_params("the generators do not match the ones of the curve"));
+        }
+
+        let mut fixed = &bytes[sections[0].clone()];
+        let beta_g1 = E::G1Affine::deserialize_uncompressed(&mut fixed)?;
+        let delta_g1 = E::G1Affine::deserialize_uncompressed(&mut fixed)?;
+        let vk = VerifyingKey::<E>::deserialize_uncompressed(&mut fixed)?;
+
+        Ok(MmapParams {
+            beta_g1,
+            delta_g1,
+            vk,
+            a_query: points(bytes, sections[3].clone())?,
+            b_g1_query: points(bytes, sections[4].clone())?,
+            b_g2_query: points(bytes, sections[5].clone())?,
+            h_query: points(bytes, sections[6].clone())?,
+            l_query: points(bytes, sections[7].clone())?,
+        })
+    }
+
+    /// Copies all the queries into `Parameters`
+    pub fn to_parameters(&self) -> Parameters<E> {
+        Parameters {
+            vk: self.vk.clone(),
+            beta_g1: self.beta_g1,
+            delta_g1: self.delta_g1,
+            a_query: self.a_query.to_vec(),
+            b_g1_query: self.b_g1_query.to_vec(),
+            b_g2_query: self.b_g2_query.to_vec(),
+            h_query: self.h_query.to_vec(),
+            l_query: self.l_query.to_vec(),
+        }
+    }
+}
+
+/// Borrows the points in `range` of `bytes`, which must be in their in-memory
+/// representation. Their infinity flags are checked to be valid booleans.
+unsafe fn points<C: AffineCurve>(bytes: &[u8], range: Range<usize>) -> Result<&[C]> {
+    let bytes = &bytes[range];
+    if bytes.as_ptr() as usize % align_of::<C>() != 0 {
+        return Err(invalid_params(
+            "the points are not aligned in memory, the file should be memory mapped",
+        ));
+    }
+    let infinity = PointLayout::of::<C>()?.infinity;
+    if bytes.chunks_exact(size_of::<C>()).any(|point| point[infinity] > 1) {
+        return Err(invalid_params("a point has an invalid infinity flag"));
+    }
+    Ok(slice::from_raw_parts(
+        bytes.as_ptr() as *const C,
+        bytes.len() / size_of::<C>(),
+    ))
+}
+
+fn invalid_params(reason: &str) -> Error {
+    Error::InvalidParamsFile(reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::testing::TestCircuit;
+    use algebra::{Bls12_377, BW6_761};
+
+    #[test]
+    fn mmap_params_round_trip() {
+        let serialized = mmap_params_round_trip_curve::<Bls12_377>();
+        mmap_params_round_trip_curve::<BW6_761>();
+
+        // a file written for another curve is rejected by its header
+        let buffer = aligned(&serialized);
+        let bytes = unsafe { slice::from_raw_parts(buffer.as_ptr() as *const u8, serialized.len()) };
+        assert!(unsafe { MmapParams::<BW6_761>::new(bytes) }.is_err());
+    }
+
+    /// Copies the bytes to a buffer which is aligned like a memory map
+    fn aligned(bytes: &[u8]) -> Vec<u64> {
+        let mut buffer = vec![0u64; (bytes.len() + 7) / 8];
+        unsafe { slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, bytes.len()) }.copy_from_slice(bytes);
+        buffer
+    }
+
+    fn mmap_params_round_trip_curve<E: PairingEngine>() -> Vec<u8> {
+        let rng = &mut rand::thread_rng();
+        let params = groth16::generate_random_parameters::<E, _, _>(TestCircuit::<E>(None), rng).unwrap();
+
+        let mut serialized = vec![];
+        write_mmap_params(&params, &mut serialized).unwrap();
+        let buffer = aligned(&serialized);
+        let bytes = unsafe { slice::from_raw_parts(buffer.as_ptr() as *const u8, serialized.len()) };
+        let mapped = unsafe { MmapParams::<E>::new(bytes) }.unwrap();
+        assert!(mapped.to_parameters() == params);
+
+        // the queries are borrowed at aligned offsets of the file
+        for query in &[mapped.a_query, mapped.b_g1_query, mapped.h_query, mapped.l_query] {
+            let offset = query.as_ptr() as usize - bytes.as_ptr() as usize;
+            assert_eq!(offset % ALIGNMENT, 0);
+        }
+        assert_eq!(mapped.h_query[2], params.h_query[2]);
+        assert_eq!(&mapped.b_g2_query[1..], &params.b_g2_query[1..]);
+
+        let mut wrong_magic = aligned(&serialized);
+        wrong_magic[0] ^= 1;
+        let wrong_magic = unsafe { slice::from_raw_parts(wrong_magic.as_ptr() as *const u8, serialized.len()) };
+        assert!(unsafe { MmapParams::<E>::new(wrong_magic) }.is_err());
+        assert!(unsafe { MmapParams::<E>::new(&bytes[..bytes.len() - 1]) }.is_err());
+
+        // the infinity flag of a point must be a boolean
+        let a_query_offset = mapped.a_query.as_ptr() as usize - bytes.as_ptr() as usize;
+        let infinity = PointLayout::of::<E::G1Affine>().unwrap().infinity;
+        let mut invalid_flag = aligned(&serialized);
+        let invalid_flag_bytes =
+            unsafe { slice::from_raw_parts_mut(invalid_flag.as_mut_ptr() as *mut u8, serialized.len()) };
+        invalid_flag_bytes[a_query_offset + infinity] = 2;
+        assert!(unsafe { MmapParams::<E>::new(invalid_flag_bytes) }.is_err());
+        serialized
+    }
+}
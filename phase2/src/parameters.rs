@@ -2,14 +2,18 @@ use cfg_if::cfg_if;
 
 cfg_if! {
     if #[cfg(not(feature = "wasm"))] {
-        use super::polynomial::eval;
+        use super::{polynomial::{eval, eval_transposed, eval_view, EvaluatedQap}, transpose::TransposedMatrices};
         use algebra::{ Zero };
         use groth16::{Parameters, VerifyingKey};
         use r1cs_core::SynthesisError;
     }
 }
 
-use super::keypair::{hash_cs_pubkeys, Keypair, PublicKey};
+use super::{
+    keypair::{hash_cs_pubkeys, Keypair, PublicKey},
+    transcript::Transcript,
+    transcript_header::TranscriptHeader,
+};
 
 use crate::load_circuit::Matrices;
 use setup_utils::*;
@@ -38,16 +42,20 @@ pub struct MPCParameters<E: PairingEngine> {
     pub params: Parameters<E>,
     pub cs_hash: [u8; 64],
     pub contributions: Vec<PublicKey<E>>,
+    /// The sources the parameters were generated from, which are bound into
+    /// `cs_hash`. Parameters created before the header existed have none.
+    pub header: Option<TranscriptHeader>,
 }
 
 impl<E: PairingEngine> fmt::Debug for MPCParameters<E> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "MPCParameters {{ params: {:?}, cs_hash: {:?}, contributions: {:?}}}",
+            "MPCParameters {{ params: {:?}, cs_hash: {:?}, contributions: {:?}, header: {:?}}}",
             self.params,
             &self.cs_hash[..],
-            self.contributions
+            self.contributions,
+            self.header
         )
     }
 }
@@ -57,6 +65,7 @@ impl<E: PairingEngine + PartialEq> PartialEq for MPCParameters<E> {
         self.params == other.params
             && &self.cs_hash[..] == other.cs_hash.as_ref()
             && self.contributions == other.contributions
+            && self.header == other.header
     }
 }
 
@@ -69,6 +78,7 @@ impl<E: PairingEngine> MPCParameters<E> {
         check_input_for_correctness: CheckForCorrectness,
         phase1_size: usize,
         phase2_size: usize,
+        header: Option<TranscriptHeader>,
     ) -> Result<MPCParameters<E>> {
         let params = Groth16Params::<E>::read(
             transcript,
@@ -77,7 +87,7 @@ impl<E: PairingEngine> MPCParameters<E> {
             phase1_size,
             phase2_size,
         )?;
-        Self::new(circuit, params)
+        Self::new(circuit, params, header)
     }
 
     #[cfg(not(feature = "wasm"))]
@@ -89,19 +99,20 @@ impl<E: PairingEngine> MPCParameters<E> {
         phase1_size: usize,
         phase2_size: usize,
         chunk_size: usize,
+        header: Option<TranscriptHeader>,
     ) -> Result<(MPCParameters<E>, Parameters<E>, Vec<MPCParameters<E>>)> {
-        let params = Groth16Params::<E>::read(
+        let params = Groth16ParamsView::<E>::new(
             transcript,
             compressed,
             check_input_for_correctness,
             phase1_size,
             phase2_size,
         )?;
-        Self::new_chunked(circuit, params, chunk_size)
+        Self::new_chunked_from_view(circuit, &params, chunk_size, header)
     }
 
     #[cfg(not(feature = "wasm"))]
-    fn process_matrix(xt: &[Vec<(E::Fr, usize)>], cs: &Matrices<E>) -> Vec<Vec<(E::Fr, usize)>> {
+    pub(crate) fn process_matrix(xt: &[Vec<(E::Fr, usize)>], cs: &Matrices<E>) -> Vec<Vec<(E::Fr, usize)>> {
         let mut xt_processed = vec![vec![]; cs.num_instance_variables + cs.num_witness_variables];
         for (constraint_num, vars) in xt.iter().enumerate() {
             for (coeff, var_index) in vars {
@@ -114,14 +125,21 @@ impl<E: PairingEngine> MPCParameters<E> {
     /// Create new Groth16 parameters (compatible with Zexe) for a
     /// given QAP which has been produced from a circuit. The resulting parameters
     /// are unsafe to use until there are contributions (see `contribute()`).
+    ///
+    /// If a `header` is provided, the circuit and Phase 1 hashes it contains
+    /// are bound into `cs_hash`.
     #[cfg(not(feature = "wasm"))]
-    pub fn new(cs: Matrices<E>, params: Groth16Params<E>) -> Result<MPCParameters<E>> {
+    pub fn new(
+        cs: Matrices<E>,
+        params: Groth16Params<E>,
+        header: Option<TranscriptHeader>,
+    ) -> Result<MPCParameters<E>> {
         // Evaluate the QAP against the coefficients created from phase 1
         let at = Self::process_matrix(&cs.a, &cs);
         let bt = Self::process_matrix(&cs.b, &cs);
         let ct = Self::process_matrix(&cs.c, &cs);
 
-        let (a_g1, b_g1, b_g2, gamma_abc_g1, l) = eval::<E>(
+        let evaluated = eval::<E>(
             // Lagrange coeffs for Tau, read in from Phase 1
             &params.coeffs_g1,
             &params.coeffs_g2,
@@ -133,41 +151,19 @@ impl<E: PairingEngine> MPCParameters<E> {
             &ct,
             // Helper
             cs.num_instance_variables,
-        );
-
-        // Reject unconstrained elements, so that
-        // the L query is always fully dense.
-        for e in l.iter() {
-            if e.is_zero() {
-                return Err(SynthesisError::UnconstrainedVariable.into());
-            }
-        }
-
-        let vk = VerifyingKey {
-            alpha_g1: params.alpha_g1,
-            beta_g2: params.beta_g2,
-            // Gamma_g2 is always 1, since we're implementing
-            // BGM17, pg14 https://eprint.iacr.org/2017/1050.pdf
-            gamma_g2: E::G2Affine::prime_subgroup_generator(),
-            delta_g2: E::G2Affine::prime_subgroup_generator(),
-            gamma_abc_g1,
-        };
-        let params = Parameters {
-            vk,
-            beta_g1: params.beta_g1,
-            delta_g1: E::G1Affine::prime_subgroup_generator(),
-            a_query: a_g1,
-            b_g1_query: b_g1,
-            b_g2_query: b_g2,
-            h_query: params.h_g1,
-            l_query: l,
-        };
+        )?;
+        let params = Self::initial_parameters(
+            evaluated,
+            (params.alpha_g1, params.beta_g1, params.beta_g2),
+            params.h_g1,
+        )?;
 
-        let cs_hash = hash_params(&params)?;
+        let cs_hash = compute_cs_hash(&params, header.as_ref())?;
         Ok(MPCParameters {
             params,
             cs_hash,
             contributions: vec![],
+            header,
         })
     }
 
@@ -176,6 +172,7 @@ impl<E: PairingEngine> MPCParameters<E> {
         cs: Matrices<E>,
         params: Groth16Params<E>,
         chunk_size: usize,
+        header: Option<TranscriptHeader>,
     ) -> Result<(MPCParameters<E>, Parameters<E>, Vec<MPCParameters<E>>)> {
         // Evaluate the QAP against the coefficients created from phase 1
 
@@ -183,7 +180,7 @@ impl<E: PairingEngine> MPCParameters<E> {
         let bt = Self::process_matrix(&cs.b, &cs);
         let ct = Self::process_matrix(&cs.c, &cs);
 
-        let (a_g1, b_g1, b_g2, gamma_abc_g1, l) = eval::<E>(
+        let evaluated = eval::<E>(
             // Lagrange coeffs for Tau, read in from Phase 1
             &params.coeffs_g1,
             &params.coeffs_g2,
@@ -195,8 +192,70 @@ impl<E: PairingEngine> MPCParameters<E> {
             &ct,
             // Helper
             cs.num_instance_variables,
-        );
+        )?;
+        let params = Self::initial_parameters(
+            evaluated,
+            (params.alpha_g1, params.beta_g1, params.beta_g2),
+            params.h_g1,
+        )?;
+        Self::split_in_chunks(params, chunk_size, header)
+    }
+
+    /// Same as `new_chunked`, but decodes the processed Phase 1 transcript from
+    /// a lazy view, which is typically memory mapped, only decoding the
+    /// Lagrange coefficients each batch of the QAP evaluation references
+    #[cfg(not(feature = "wasm"))]
+    pub fn new_chunked_from_view(
+        cs: Matrices<E>,
+        params: &Groth16ParamsView<E>,
+        chunk_size: usize,
+        header: Option<TranscriptHeader>,
+    ) -> Result<(MPCParameters<E>, Parameters<E>, Vec<MPCParameters<E>>)> {
+        // Evaluate the QAP against the coefficients created from phase 1
+        let at = Self::process_matrix(&cs.a, &cs);
+        let bt = Self::process_matrix(&cs.b, &cs);
+        let ct = Self::process_matrix(&cs.c, &cs);
+
+        let evaluated = eval_view::<E>(params, &at, &bt, &ct, cs.num_instance_variables)?;
+        drop((at, bt, ct));
+        let params = Self::initial_parameters(
+            evaluated,
+            (params.alpha_g1, params.beta_g1, params.beta_g2),
+            params.h_g1.read_all()?,
+        )?;
+        Self::split_in_chunks(params, chunk_size, header)
+    }
+
+    /// Same as `new_chunked_from_view`, but evaluates the QAP from the transposed
+    /// matrices of a circuit one range of variables at a time, so that the
+    /// memory used for the matrices is bounded by `memory_budget` bytes rather
+    /// than by the size of the circuit
+    #[cfg(not(feature = "wasm"))]
+    pub fn new_chunked_from_transposed(
+        matrices: &TransposedMatrices<E>,
+        params: &Groth16ParamsView<E>,
+        memory_budget: usize,
+        chunk_size: usize,
+        header: Option<TranscriptHeader>,
+    ) -> Result<(MPCParameters<E>, Parameters<E>, Vec<MPCParameters<E>>)> {
+        let ranges = matrices.ranges(memory_budget);
+        let evaluated = eval_transposed::<E>(params, matrices, &ranges)?;
+        let params = Self::initial_parameters(
+            evaluated,
+            (params.alpha_g1, params.beta_g1, params.beta_g2),
+            params.h_g1.read_all()?,
+        )?;
+        Self::split_in_chunks(params, chunk_size, header)
+    }
 
+    /// Assembles the initial Groth16 parameters from the evaluated QAP, alpha
+    /// and beta, and the H query
+    #[cfg(not(feature = "wasm"))]
+    fn initial_parameters(
+        (a_g1, b_g1, b_g2, gamma_abc_g1, l): EvaluatedQap<E>,
+        (alpha_g1, beta_g1, beta_g2): (E::G1Affine, E::G1Affine, E::G2Affine),
+        h_g1: Vec<E::G1Affine>,
+    ) -> Result<Parameters<E>> {
         // Reject unconstrained elements, so that
         // the L query is always fully dense.
         for e in l.iter() {
@@ -206,25 +265,34 @@ impl<E: PairingEngine> MPCParameters<E> {
         }
 
         let vk = VerifyingKey {
-            alpha_g1: params.alpha_g1,
-            beta_g2: params.beta_g2,
+            alpha_g1,
+            beta_g2,
             // Gamma_g2 is always 1, since we're implementing
             // BGM17, pg14 https://eprint.iacr.org/2017/1050.pdf
             gamma_g2: E::G2Affine::prime_subgroup_generator(),
             delta_g2: E::G2Affine::prime_subgroup_generator(),
             gamma_abc_g1,
         };
-        let params = Parameters {
+        Ok(Parameters {
             vk,
-            beta_g1: params.beta_g1,
+            beta_g1,
             delta_g1: E::G1Affine::prime_subgroup_generator(),
             a_query: a_g1,
             b_g1_query: b_g1,
             b_g2_query: b_g2,
-            h_query: params.h_g1,
+            h_query: h_g1,
             l_query: l,
-        };
+        })
+    }
 
+    /// Splits the initial parameters into the full parameters, the queries
+    /// and the chunks of the H and L queries
+    #[cfg(not(feature = "wasm"))]
+    fn split_in_chunks(
+        params: Parameters<E>,
+        chunk_size: usize,
+        header: Option<TranscriptHeader>,
+    ) -> Result<(MPCParameters<E>, Parameters<E>, Vec<MPCParameters<E>>)> {
         let query_parameters = Parameters::<E> {
             vk: params.vk.clone(),
             beta_g1: params.beta_g1.clone(),
@@ -235,11 +303,12 @@ impl<E: PairingEngine> MPCParameters<E> {
             h_query: vec![],
             l_query: vec![],
         };
-        let cs_hash = hash_params(&params)?;
+        let cs_hash = compute_cs_hash(&params, header.as_ref())?;
         let full_mpc = MPCParameters {
             params: params.clone(),
             cs_hash,
             contributions: vec![],
+            header,
         };
 
         let mut chunks = vec![];
@@ -271,6 +340,7 @@ impl<E: PairingEngine> MPCParameters<E> {
                 },
                 cs_hash,
                 contributions: vec![],
+                header,
             };
             chunks.push(chunk_params);
         }
@@ -282,6 +352,13 @@ impl<E: PairingEngine> MPCParameters<E> {
         &self.params
     }
 
+    /// Checks that `cs_hash` was derived from the parameters and the header.
+    /// This only holds for the full initial parameters, before any contribution.
+    pub fn verify_cs_hash(&self) -> Result<()> {
+        let cs_hash = compute_cs_hash(&self.params, self.header.as_ref())?;
+        ensure_unchanged(&self.cs_hash[..], &cs_hash[..], InvariantKind::CsHash)
+    }
+
     /// Contributes some randomness to the parameters. Only one
     /// contributor needs to be honest for the parameters to be
     /// secure.
@@ -320,23 +397,42 @@ impl<E: PairingEngine> MPCParameters<E> {
     /// `MPCParameters::contribute`, for ensuring that contributions
     /// exist in the final parameters.
     pub fn verify(&self, after: &Self) -> Result<Vec<[u8; 64]>> {
-        let before = self;
+        self.verify_params(after)?;
+
+        // generate the transcript from the current contributions and the previous cs_hash
+        verify_transcript(self.cs_hash, &after.contributions)
+    }
 
-        let pubkey = if let Some(pubkey) = after.contributions.last() {
+    /// Checks that the delta of the parameters is the one of the last contribution,
+    /// which binds the contributions of the transcript to the parameters.
+    pub fn verify_delta(&self) -> Result<()> {
+        let pubkey = if let Some(pubkey) = self.contributions.last() {
             pubkey
         } else {
             // if there were no contributions then we should error
             return Err(Phase2Error::NoContributions.into());
         };
         // Current parameters should have consistent delta in G1
-        ensure_unchanged(pubkey.delta_after, after.params.delta_g1, InvariantKind::DeltaG1)?;
+        ensure_unchanged(pubkey.delta_after, self.params.delta_g1, InvariantKind::DeltaG1)?;
         // Current parameters should have consistent delta in G2
         check_same_ratio::<E>(
             &(E::G1Affine::prime_subgroup_generator(), pubkey.delta_after),
-            &(E::G2Affine::prime_subgroup_generator(), after.params.vk.delta_g2),
+            &(E::G2Affine::prime_subgroup_generator(), self.params.vk.delta_g2),
             "Inconsistent G2 Delta",
         )?;
 
+        Ok(())
+    }
+
+    /// Performs the checks of `verify` except for verifying the transcript,
+    /// which is left to the caller. This is used for chunks sharing a detached
+    /// transcript, whose new contribution only needs to be verified once with
+    /// `Transcript::verify_contribution`.
+    pub fn verify_params(&self, after: &Self) -> Result<()> {
+        let before = self;
+
+        after.verify_delta()?;
+
         // None of the previous transformations should change
         ensure_unchanged(
             &before.contributions[..],
@@ -344,8 +440,9 @@ impl<E: PairingEngine> MPCParameters<E> {
             InvariantKind::Contributions,
         )?;
 
-        // cs_hash should be the same
+        // cs_hash and the sources bound into it should be the same
         ensure_unchanged(&before.cs_hash[..], &after.cs_hash[..], InvariantKind::CsHash)?;
+        ensure_unchanged(&before.header, &after.header, InvariantKind::TranscriptHeader)?;
 
         // H/L will change, but should have same length
         ensure_same_length(&before.params.h_query, &after.params.h_query)?;
@@ -414,11 +511,15 @@ impl<E: PairingEngine> MPCParameters<E> {
             )?;
         }
 
-        // generate the transcript from the current contributions and the previous cs_hash
-        verify_transcript(before.cs_hash, &after.contributions)
+        Ok(())
     }
 
     pub fn combine(queries: &Parameters<E>, mpcs: &[MPCParameters<E>]) -> Result<MPCParameters<E>> {
+        // all chunks must belong to the same transcript
+        for mpc in mpcs {
+            ensure_unchanged(&mpcs[0].cs_hash[..], &mpc.cs_hash[..], InvariantKind::CsHash)?;
+            ensure_unchanged(&mpcs[0].header, &mpc.header, InvariantKind::TranscriptHeader)?;
+        }
         let mut combined_mpc = MPCParameters::<E> {
             params: Parameters::<E> {
                 vk: mpcs[0].params.vk.clone(),
@@ -432,6 +533,7 @@ impl<E: PairingEngine> MPCParameters<E> {
             },
             cs_hash: mpcs[0].cs_hash,
             contributions: mpcs[0].contributions.clone(),
+            header: mpcs[0].header,
         };
         for mpc in mpcs {
             combined_mpc.params.h_query.extend_from_slice(&mpc.params.h_query);
@@ -450,10 +552,69 @@ impl<E: PairingEngine> MPCParameters<E> {
         }?;
         writer.write_all(&self.cs_hash)?;
         PublicKey::write_batch(&mut writer, &self.contributions)?;
+        if let Some(header) = &self.header {
+            header.write(&mut writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the parts of the parameters which are shared by all chunks
+    pub fn transcript(&self) -> Transcript<E> {
+        Transcript {
+            cs_hash: self.cs_hash,
+            contributions: self.contributions.clone(),
+            header: self.header,
+        }
+    }
+
+    /// Serializes the parameters in the detached format, i.e. followed by the
+    /// hash of their transcript instead of the transcript itself, which has to
+    /// be written separately with `Transcript::write`.
+    pub fn write_detached<W: Write>(&self, mut writer: W, compressed: UseCompression) -> Result<()> {
+        match compressed {
+            UseCompression::No => self.params.serialize_uncompressed(&mut writer),
+            UseCompression::Yes => self.params.serialize(&mut writer),
+        }?;
+        writer.write_all(&self.transcript().hash())?;
 
         Ok(())
     }
 
+    /// Deserializes parameters written with `write_detached`, checking that
+    /// they refer to the provided transcript.
+    pub fn read_detached<R: Read>(
+        mut reader: R,
+        compressed: UseCompression,
+        check_correctness: CheckForCorrectness,
+        check_subgroup_membership: bool,
+        subgroup_check_mode: SubgroupCheckMode,
+        transcript: &Transcript<E>,
+    ) -> Result<MPCParameters<E>> {
+        let params = Self::read_groth16_fast(
+            &mut reader,
+            compressed,
+            check_correctness,
+            check_subgroup_membership,
+            subgroup_check_mode,
+        )?;
+
+        let mut transcript_hash = [0u8; 64];
+        reader.read_exact(&mut transcript_hash)?;
+        ensure_unchanged(
+            &transcript_hash[..],
+            &transcript.hash()[..],
+            InvariantKind::TranscriptHash,
+        )?;
+
+        Ok(MPCParameters {
+            params,
+            cs_hash: transcript.cs_hash,
+            contributions: transcript.contributions.clone(),
+            header: transcript.header,
+        })
+    }
+
     /// Deserialize these parameters.
     pub fn read<R: Read>(
         mut reader: R,
@@ -495,11 +656,13 @@ impl<E: PairingEngine> MPCParameters<E> {
         reader.read_exact(&mut cs_hash)?;
 
         let contributions = PublicKey::read_batch(&mut reader)?;
+        let header = TranscriptHeader::read_optional(&mut reader)?;
 
         Ok(MPCParameters {
             params,
             cs_hash,
             contributions,
+            header,
         })
     }
 
@@ -522,11 +685,13 @@ impl<E: PairingEngine> MPCParameters<E> {
         reader.read_exact(&mut cs_hash)?;
 
         let contributions = PublicKey::read_batch(&mut reader)?;
+        let header = TranscriptHeader::read_optional(&mut reader)?;
 
         let mpc_params = MPCParameters::<E> {
             params,
             cs_hash,
             contributions,
+            header,
         };
 
         Ok(mpc_params)
@@ -641,34 +806,82 @@ pub fn ensure_unchanged<T: PartialEq>(before: T, after: T, kind: InvariantKind)
 }
 
 pub fn verify_transcript<E: PairingEngine>(cs_hash: [u8; 64], contributions: &[PublicKey<E>]) -> Result<Vec<[u8; 64]>> {
-    let mut result = vec![];
     let mut old_delta = E::G1Affine::prime_subgroup_generator();
+    let mut ratio_checks = Vec::with_capacity(2 * contributions.len());
+    let mut hashes = Vec::with_capacity(contributions.len());
     for (i, pubkey) in contributions.iter().enumerate() {
-        let hash = hash_cs_pubkeys(cs_hash, &contributions[0..i], pubkey.s, pubkey.s_delta);
-        ensure_unchanged(&pubkey.transcript[..], &hash.as_ref()[..], InvariantKind::Transcript)?;
+        let step_ratio_checks = transcript_step_ratios(cs_hash, &contributions[0..i], old_delta, pubkey)?;
+        ratio_checks.extend_from_slice(&step_ratio_checks);
+        hashes.push(pubkey.hash());
+        old_delta = pubkey.delta_after;
+    }
+
+    // The signatures of knowledge and delta changes of all the contributions are checked at once
+    check_same_ratios_batched::<E>(&ratio_checks)?;
+
+    Ok(hashes)
+}
+
+/// Verifies every contribution of the transcript against the previous one,
+/// returning the hash of each contribution or the reason its verification
+/// failed. Unlike `verify_transcript`, this does not stop at the first failure.
+pub fn verify_transcript_steps<E: PairingEngine>(
+    cs_hash: [u8; 64],
+    contributions: &[PublicKey<E>],
+) -> Vec<Result<[u8; 64]>> {
+    let mut old_delta = E::G1Affine::prime_subgroup_generator();
+    contributions
+        .iter()
+        .enumerate()
+        .map(|(i, pubkey)| {
+            let result = verify_transcript_step(cs_hash, &contributions[0..i], old_delta, pubkey);
+            old_delta = pubkey.delta_after;
+            result
+        })
+        .collect()
+}
 
-        // generate the G2 point from the hash
-        let r = hash_to_g2::<E>(hash.as_ref()).into_affine();
+pub(crate) fn verify_transcript_step<E: PairingEngine>(
+    cs_hash: [u8; 64],
+    previous_contributions: &[PublicKey<E>],
+    old_delta: E::G1Affine,
+    pubkey: &PublicKey<E>,
+) -> Result<[u8; 64]> {
+    let ratio_checks = transcript_step_ratios(cs_hash, previous_contributions, old_delta, pubkey)?;
+    check_same_ratios_batched::<E>(&ratio_checks)?;
 
+    Ok(pubkey.hash())
+}
+
+/// Checks the transcript hash of a contribution and returns the ratios which
+/// must be the same for the contribution to be valid
+#[allow(clippy::type_complexity)]
+fn transcript_step_ratios<E: PairingEngine>(
+    cs_hash: [u8; 64],
+    previous_contributions: &[PublicKey<E>],
+    old_delta: E::G1Affine,
+    pubkey: &PublicKey<E>,
+) -> Result<[((E::G1Affine, E::G1Affine), (E::G2Affine, E::G2Affine), &'static str); 2]> {
+    let hash = hash_cs_pubkeys(cs_hash, previous_contributions, pubkey.s, pubkey.s_delta);
+    ensure_unchanged(&pubkey.transcript[..], &hash.as_ref()[..], InvariantKind::Transcript)?;
+
+    // generate the G2 point from the hash
+    let r = hash_to_g2::<E>(hash.as_ref()).into_affine();
+
+    Ok([
         // Check the signature of knowledge
-        check_same_ratio::<E>(
-            &(pubkey.s, pubkey.s_delta),
-            &(r, pubkey.r_delta),
+        (
+            (pubkey.s, pubkey.s_delta),
+            (r, pubkey.r_delta),
             "Incorrect signature of knowledge",
-        )?;
-
+        ),
         // Check the change with the previous G1 Delta is consistent
-        check_same_ratio::<E>(
-            &(old_delta, pubkey.delta_after),
-            &(r, pubkey.r_delta),
+        (
+            (old_delta, pubkey.delta_after),
+            (r, pubkey.r_delta),
             "Inconsistent G1 Delta",
-        )?;
-        old_delta = pubkey.delta_after;
-
-        result.push(pubkey.hash());
-    }
-
-    Ok(result)
+        ),
+    ])
 }
 
 pub fn circuit_to_qap<Zexe: PairingEngine, C: ConstraintSynthesizer<Zexe::Fr>>(
@@ -690,7 +903,16 @@ pub fn circuit_to_qap<Zexe: PairingEngine, C: ConstraintSynthesizer<Zexe::Fr>>(
     Ok(cs)
 }
 
-#[allow(unused)]
+/// Derives the `cs_hash` of the initial parameters, binding the header into it
+/// when there is one
+fn compute_cs_hash<E: PairingEngine>(params: &Parameters<E>, header: Option<&TranscriptHeader>) -> Result<[u8; 64]> {
+    let params_hash = hash_params(params)?;
+    Ok(match header {
+        Some(header) => header.cs_hash(&params_hash),
+        None => params_hash,
+    })
+}
+
 fn hash_params<E: PairingEngine>(params: &Parameters<E>) -> Result<[u8; 64]> {
     let sink = io::sink();
     let mut sink = HashWriter::new(sink);
@@ -707,9 +929,10 @@ mod tests {
     use crate::{
         chunked_groth16::{contribute, verify},
         helpers::testing::TestCircuit,
+        load_circuit::CircuitFile,
     };
     use phase1::{helpers::testing::setup_verify, Phase1, Phase1Parameters, ProvingSystem};
-    use setup_utils::{Groth16Params, UseCompression};
+    use setup_utils::{Groth16Params, Groth16ParamsView, UseCompression, UseMixedRadix};
 
     use algebra::Bls12_377;
 
@@ -739,6 +962,34 @@ mod tests {
         assert_eq!(deserialized, mpc)
     }
 
+    #[test]
+    fn legacy_ceremony_without_header() {
+        legacy_ceremony_without_header_curve::<Bls12_377>()
+    }
+
+    fn legacy_ceremony_without_header_curve<E: PairingEngine + PartialEq>() {
+        let legacy = generate_ceremony_with_header::<E>(None);
+        legacy.verify_cs_hash().unwrap();
+        let mut writer = vec![];
+        legacy.write(&mut writer, UseCompression::Yes).unwrap();
+        let deserialized = MPCParameters::<E>::read(
+            &writer[..],
+            UseCompression::Yes,
+            CheckForCorrectness::Full,
+            false,
+            SubgroupCheckMode::Auto,
+        )
+        .unwrap();
+        assert_eq!(deserialized, legacy);
+
+        // the header changes the transcript, so it cannot be swapped
+        let mut mpc = generate_ceremony::<E>();
+        mpc.verify_cs_hash().unwrap();
+        assert!(mpc.cs_hash[..] != legacy.cs_hash[..]);
+        mpc.header = Some(TranscriptHeader::new(&[3; 64], &[2; 64]));
+        assert!(mpc.verify_cs_hash().is_err());
+    }
+
     #[test]
     fn verify_with_self_fails() {
         verify_with_self_fails_curve::<Bls12_377>()
@@ -863,11 +1114,35 @@ mod tests {
     // helper which generates the initial phase 2 params
     // for the TestCircuit
     fn generate_ceremony<E: PairingEngine>() -> MPCParameters<E> {
+        generate_ceremony_with_header(Some(TranscriptHeader::new(&[1; 64], &[2; 64])))
+    }
+
+    fn generate_ceremony_with_header<E: PairingEngine>(header: Option<TranscriptHeader>) -> MPCParameters<E> {
         // the phase2 params are generated correctly,
         // even though the powers of tau are >> the circuit size
-        let powers = 5;
+        let groth_params = generate_groth16_params::<E>(5, 7);
+
+        // this circuit requires 7 constraints, so a ceremony with size 8 is sufficient
+        let c = TestCircuit::<E>(None);
+        let cs = circuit_to_qap::<E, _>(c).unwrap();
+        let m = cs.to_matrices().unwrap();
+        let matrices = Matrices {
+            a: m.a,
+            b: m.b,
+            c: m.c,
+            a_num_non_zero: m.a_num_non_zero,
+            b_num_non_zero: m.b_num_non_zero,
+            c_num_non_zero: m.c_num_non_zero,
+            num_instance_variables: m.num_instance_variables,
+            num_witness_variables: m.num_witness_variables,
+            num_constraints: m.num_constraints,
+        };
+
+        MPCParameters::new(matrices, groth_params, header).unwrap()
+    }
+
+    fn generate_groth16_params<E: PairingEngine>(powers: usize, phase2_size: usize) -> Groth16Params<E> {
         let batch = 16;
-        let phase2_size = 7;
         let params = Phase1Parameters::<E>::new_full(ProvingSystem::Groth16, powers, batch);
         let accumulator = {
             let compressed = UseCompression::No;
@@ -881,32 +1156,60 @@ mod tests {
             Phase1::deserialize(&output, compressed, CheckForCorrectness::Full, &params).unwrap()
         };
 
-        let groth_params = Groth16Params::<E>::new(
+        Groth16Params::<E>::new(
             phase2_size,
+            UseMixedRadix::No,
             accumulator.tau_powers_g1,
             accumulator.tau_powers_g2,
             accumulator.alpha_tau_powers_g1,
             accumulator.beta_tau_powers_g1,
             accumulator.beta_g2,
         )
-        .unwrap();
+        .unwrap()
+    }
 
-        // this circuit requires 7 constraints, so a ceremony with size 8 is sufficient
-        let c = TestCircuit::<E>(None);
-        let cs = circuit_to_qap::<E, _>(c).unwrap();
-        let m = cs.to_matrices().unwrap();
-        let matrices = Matrices {
-            a: m.a,
-            b: m.b,
-            c: m.c,
-            a_num_non_zero: m.a_num_non_zero,
-            b_num_non_zero: m.b_num_non_zero,
-            c_num_non_zero: m.c_num_non_zero,
-            num_instance_variables: m.num_instance_variables,
-            num_witness_variables: m.num_witness_variables,
-            num_constraints: m.num_constraints,
-        };
+    #[test]
+    fn new_chunked_from_view() {
+        new_chunked_from_view_curve::<Bls12_377>()
+    }
 
-        MPCParameters::new(matrices, groth_params).unwrap()
+    // decoding the sections lazily gives the same parameters as decoding them at once
+    fn new_chunked_from_view_curve<E: PairingEngine + PartialEq>() {
+        let phase2_size = 8;
+        let groth_params = generate_groth16_params::<E>(4, phase2_size);
+        let mut serialized = vec![];
+        groth_params.write(&mut serialized, UseCompression::No).unwrap();
+        let view = Groth16ParamsView::<E>::new(
+            &serialized,
+            UseCompression::No,
+            CheckForCorrectness::Full,
+            phase2_size,
+            phase2_size,
+        )
+        .unwrap();
+        assert_eq!(view.coeffs_g2.len(), phase2_size);
+        assert_eq!(view.h_g1.get(3).unwrap(), groth_params.h_g1[3]);
+        assert_eq!(view.coeffs_g1.read(2..5).unwrap(), &groth_params.coeffs_g1[2..5]);
+
+        let header = Some(TranscriptHeader::new(&[1; 64], &[2; 64]));
+        let matrices = Matrices::from_circuit(TestCircuit::<E>(None)).unwrap();
+        let (full, query, chunks) = MPCParameters::<E>::new_chunked(matrices.clone(), groth_params, 2, header).unwrap();
+        let (full_from_view, query_from_view, chunks_from_view) =
+            MPCParameters::<E>::new_chunked_from_view(matrices.clone(), &view, 2, header).unwrap();
+        assert_eq!(full_from_view, full);
+        assert!(query_from_view == query);
+        assert_eq!(chunks_from_view, chunks);
+
+        // evaluating the transposed matrices a variable at a time gives the same parameters
+        let mut serialized_circuit = vec![];
+        matrices.write(&mut serialized_circuit).unwrap();
+        let circuit = CircuitFile::<E>::new(&serialized_circuit).unwrap();
+        let mut spill = vec![0; TransposedMatrices::spill_size(&circuit)];
+        let transposed = TransposedMatrices::new(&circuit, &mut spill).unwrap();
+        let (full_from_transposed, query_from_transposed, chunks_from_transposed) =
+            MPCParameters::<E>::new_chunked_from_transposed(&transposed, &view, 0, 2, header).unwrap();
+        assert_eq!(full_from_transposed, full);
+        assert!(query_from_transposed == query);
+        assert_eq!(chunks_from_transposed, chunks);
     }
 }
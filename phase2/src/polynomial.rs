@@ -1,12 +1,24 @@
-use algebra::{AffineCurve, PairingEngine, ProjectiveCurve, Zero};
+use crate::transpose::TransposedMatrices;
+use setup_utils::{batch_exp, BatchExpMode, Groth16ParamsView, LazyPoints, Result};
+
+use algebra::{AffineCurve, One, PairingEngine, ProjectiveCurve, Zero};
 
 use rayon::prelude::*;
+use std::ops::Range;
+
+/// The evaluated QAP polynomials, in the format [a_g1, b_g1, b_g2, gamma_abc_g1, l_g1]
+pub type EvaluatedQap<E> = (
+    Vec<<E as PairingEngine>::G1Affine>,
+    Vec<<E as PairingEngine>::G1Affine>,
+    Vec<<E as PairingEngine>::G2Affine>,
+    Vec<<E as PairingEngine>::G1Affine>,
+    Vec<<E as PairingEngine>::G1Affine>,
+);
 
 /// Evaluates and returns the provided QAP Polynomial vectors at the provided coefficients.
 /// Format: [a_g1, b_g1, b_g2, gamma_abc_g1, l_g1]
 /// The returned points are _affine_
 #[allow(clippy::too_many_arguments)]
-#[allow(clippy::type_complexity)]
 pub fn eval<E: PairingEngine>(
     // Lagrange coefficients for tau
     coeffs_g1: &[E::G1Affine],
@@ -19,19 +31,65 @@ pub fn eval<E: PairingEngine>(
     ct: &[Vec<(E::Fr, usize)>],
     // The number of inputs
     num_inputs: usize,
-) -> (
-    Vec<E::G1Affine>,
-    Vec<E::G1Affine>,
-    Vec<E::G2Affine>,
-    Vec<E::G1Affine>,
-    Vec<E::G1Affine>,
-) {
+) -> Result<EvaluatedQap<E>> {
     // calculate the evaluated polynomials
-    let a_g1 = dot_product_vec(at, coeffs_g1);
-    let b_g1 = dot_product_vec(bt, coeffs_g1);
-    let b_g2 = dot_product_vec(bt, coeffs_g2);
-    let ext = dot_product_ext::<E>((at, beta_coeffs_g1), (bt, alpha_coeffs_g1), (ct, coeffs_g1));
+    let a_g1 = dot_product_vec(at, coeffs_g1)?;
+    let b_g1 = dot_product_vec(bt, coeffs_g1)?;
+    let b_g2 = dot_product_vec(bt, coeffs_g2)?;
+    let ext = dot_product_ext::<E>((at, beta_coeffs_g1), (bt, alpha_coeffs_g1), (ct, coeffs_g1))?;
 
+    Ok(to_affine::<E>(a_g1, b_g1, b_g2, ext, num_inputs))
+}
+
+/// Same as `eval`, but decodes the Lagrange coefficients from a lazy view of
+/// the processed Phase 1 transcript as they are used, so that only the
+/// coefficients referenced by one batch of rows are in memory at once
+pub fn eval_view<E: PairingEngine>(
+    params: &Groth16ParamsView<E>,
+    at: &[Vec<(E::Fr, usize)>],
+    bt: &[Vec<(E::Fr, usize)>],
+    ct: &[Vec<(E::Fr, usize)>],
+    num_inputs: usize,
+) -> Result<EvaluatedQap<E>> {
+    let a_g1 = dot_product_vec(at, &params.coeffs_g1)?;
+    let b_g1 = dot_product_vec(bt, &params.coeffs_g1)?;
+    let mut ext = dot_product_vec(ct, &params.coeffs_g1)?;
+
+    let b_g2 = dot_product_vec(bt, &params.coeffs_g2)?;
+    let beta_ext = dot_product_vec(at, &params.beta_coeffs_g1)?;
+    let alpha_ext = dot_product_vec(bt, &params.alpha_coeffs_g1)?;
+    add_normalized::<E>(&mut ext, &beta_ext, &alpha_ext);
+
+    Ok(to_affine::<E>(a_g1, b_g1, b_g2, ext, num_inputs))
+}
+
+/// Same as `eval_view`, but reads the QAP polynomials of one range of variables
+/// at a time from the transposed matrices of the circuit, so that only the
+/// polynomials of one range are in memory at once
+pub fn eval_transposed<E: PairingEngine>(
+    params: &Groth16ParamsView<E>,
+    matrices: &TransposedMatrices<E>,
+    ranges: &[Range<usize>],
+) -> Result<EvaluatedQap<E>> {
+    let a_g1 = dot_product_columns(matrices, 0, ranges, &params.coeffs_g1)?;
+    let b_g1 = dot_product_columns(matrices, 1, ranges, &params.coeffs_g1)?;
+    let mut ext = dot_product_columns(matrices, 2, ranges, &params.coeffs_g1)?;
+
+    let b_g2 = dot_product_columns(matrices, 1, ranges, &params.coeffs_g2)?;
+    let beta_ext = dot_product_columns(matrices, 0, ranges, &params.beta_coeffs_g1)?;
+    let alpha_ext = dot_product_columns(matrices, 1, ranges, &params.alpha_coeffs_g1)?;
+    add_normalized::<E>(&mut ext, &beta_ext, &alpha_ext);
+
+    Ok(to_affine::<E>(a_g1, b_g1, b_g2, ext, matrices.num_instance_variables))
+}
+
+fn to_affine<E: PairingEngine>(
+    a_g1: Vec<E::G1Projective>,
+    b_g1: Vec<E::G1Projective>,
+    b_g2: Vec<E::G2Projective>,
+    ext: Vec<E::G1Projective>,
+    num_inputs: usize,
+) -> EvaluatedQap<E> {
     // break to `gamma_abc_g1` and `l` coeffs
     let (gamma_abc_g1, l) = ext.split_at(num_inputs);
 
@@ -45,49 +103,149 @@ pub fn eval<E: PairingEngine>(
     (a_g1, b_g1, b_g2, gamma_abc_g1, l)
 }
 
+/// Adds the beta and alpha terms to the C terms and batch normalizes the result
+fn add_normalized<E: PairingEngine>(
+    ext: &mut [E::G1Projective],
+    beta_ext: &[E::G1Projective],
+    alpha_ext: &[E::G1Projective],
+) {
+    ext.par_iter_mut()
+        .zip(beta_ext.par_iter().zip(alpha_ext))
+        .for_each(|(ext, (beta_ext, alpha_ext))| {
+            *ext += beta_ext;
+            *ext += alpha_ext;
+        });
+    E::G1Projective::batch_normalization(ext);
+}
+
 #[allow(clippy::type_complexity)]
-#[allow(clippy::op_ref)] // false positive by clippy
 fn dot_product_ext<E: PairingEngine>(
     (at, beta_coeffs_g1): (&[Vec<(E::Fr, usize)>], &[E::G1Affine]),
     (bt, alpha_coeffs_g1): (&[Vec<(E::Fr, usize)>], &[E::G1Affine]),
     (ct, coeffs_g1): (&[Vec<(E::Fr, usize)>], &[E::G1Affine]),
-) -> Vec<E::G1Projective> {
-    let mut ret = at
-        .par_iter()
-        .zip(bt.par_iter().zip(ct))
-        .map(|(at, (bt, ct))| {
-            dot_product(&at, &beta_coeffs_g1) + &dot_product(&bt, &alpha_coeffs_g1) + &dot_product(&ct, &coeffs_g1)
-        })
-        .collect::<Vec<_>>();
-    E::G1Projective::batch_normalization(&mut ret);
-    ret
+) -> Result<Vec<E::G1Projective>> {
+    let mut ret = dot_product_vec(ct, coeffs_g1)?;
+    let beta_ext = dot_product_vec(at, beta_coeffs_g1)?;
+    let alpha_ext = dot_product_vec(bt, alpha_coeffs_g1)?;
+    add_normalized::<E>(&mut ret, &beta_ext, &alpha_ext);
+    Ok(ret)
+}
+
+/// Applies `dot_product_vec` to the columns of a transposed matrix, one range of
+/// variables at a time
+fn dot_product_columns<E: PairingEngine, C: AffineCurve<ScalarField = E::Fr>>(
+    matrices: &TransposedMatrices<E>,
+    matrix: usize,
+    ranges: &[Range<usize>],
+    coeffs: &(impl Coefficients<C> + ?Sized),
+) -> Result<Vec<C::Projective>> {
+    let mut ret = Vec::with_capacity(ranges.last().map_or(0, |range| range.end));
+    for range in ranges {
+        ret.extend(dot_product_vec(&matrices.columns(matrix, range.clone())?, coeffs)?);
+    }
+    Ok(ret)
 }
 
+/// Lagrange coefficients which `dot_product_vec` gathers for each batch of rows
+trait Coefficients<C: AffineCurve>: Sync {
+    /// Returns the coefficients at the sorted `indices`
+    fn gather(&self, indices: &[usize]) -> Result<Vec<C>>;
+}
+
+impl<C: AffineCurve> Coefficients<C> for [C] {
+    fn gather(&self, indices: &[usize]) -> Result<Vec<C>> {
+        Ok(indices.iter().map(|&ind| self[ind]).collect())
+    }
+}
+
+/// Only the coefficients referenced by the batch are decoded
+impl<'a, C: AffineCurve> Coefficients<C> for LazyPoints<'a, C> {
+    fn gather(&self, indices: &[usize]) -> Result<Vec<C>> {
+        indices.par_iter().map(|&ind| self.get(ind)).collect()
+    }
+}
+
+/// The maximum number of terms which `dot_product_vec` exponentiates in one batch
+/// (a single row with more terms is still processed on its own)
+const BATCH_SIZE: usize = 1 << 20;
+
 /// Returns a batch normalized projective vector where the coefficients
 /// have been applied to the input
 /// This is a NxN * Nx1 -> Nx1 matrix multiplication basically
-fn dot_product_vec<C: AffineCurve>(input: &[Vec<(C::ScalarField, usize)>], coeffs: &[C]) -> Vec<C::Projective> {
-    let mut ret = input.par_iter().map(|row| dot_product(row, coeffs)).collect::<Vec<_>>();
+///
+/// The terms of consecutive rows are exponentiated together via `batch_exp`, which
+/// uses batch affine arithmetic, and then summed per row. Terms with a zero scalar
+/// or base are skipped and terms with a unit scalar are added directly. The
+/// coefficients referenced by each batch are gathered once before its terms.
+fn dot_product_vec<C: AffineCurve>(
+    input: &[Vec<(C::ScalarField, usize)>],
+    coeffs: &(impl Coefficients<C> + ?Sized),
+) -> Result<Vec<C::Projective>> {
+    let mut ret = Vec::with_capacity(input.len());
+    let mut start = 0;
+    while start < input.len() {
+        // take at least one row, and as many more as fit in the batch
+        let mut end = start + 1;
+        let mut batch_size = input[start].len();
+        while end < input.len() && batch_size + input[end].len() <= BATCH_SIZE {
+            batch_size += input[end].len();
+            end += 1;
+        }
+        let rows = &input[start..end];
+
+        let mut indices = rows.iter().flatten().map(|&(_, ind)| ind).collect::<Vec<_>>();
+        indices.sort_unstable();
+        indices.dedup();
+        let batch_coeffs = coeffs.gather(&indices)?;
+        let coeff_at = |ind: usize| &batch_coeffs[indices.binary_search(&ind).expect("index was gathered")];
+        let is_batched =
+            |&&(coeff, ind): &&(C::ScalarField, usize)| !coeff.is_zero() && !coeff.is_one() && !coeff_at(ind).is_zero();
+
+        let lengths = rows
+            .iter()
+            .map(|row| row.iter().filter(is_batched).count())
+            .collect::<Vec<_>>();
+        let mut bases = rows
+            .iter()
+            .flat_map(|row| row.iter().filter(is_batched).map(|&(_, ind)| *coeff_at(ind)))
+            .collect::<Vec<_>>();
+        let exps = rows
+            .iter()
+            .flat_map(|row| row.iter().filter(is_batched).map(|&(coeff, _)| coeff))
+            .collect::<Vec<_>>();
+        batch_exp(&mut bases, &exps, None, BatchExpMode::Auto)?;
+
+        // split the exponentiated terms back in rows
+        let mut remaining = &bases[..];
+        let terms = lengths
+            .iter()
+            .map(|&len| {
+                let (terms, tail) = remaining.split_at(len);
+                remaining = tail;
+                terms
+            })
+            .collect::<Vec<_>>();
+
+        let sums = rows
+            .par_iter()
+            .zip(terms)
+            .map(|(row, terms)| {
+                let unit_terms = row
+                    .iter()
+                    .filter(|(coeff, _)| coeff.is_one())
+                    .map(|&(_, ind)| coeff_at(ind));
+                unit_terms.chain(terms).fold(C::Projective::zero(), |mut sum, term| {
+                    sum.add_assign_mixed(term);
+                    sum
+                })
+            })
+            .collect::<Vec<_>>();
+        ret.extend(sums);
+        start = end;
+    }
     // Batch normalize
     C::Projective::batch_normalization(&mut ret);
-    ret
-}
-
-/// Executes a dot product between two vectors (1xN * Nx1)
-/// If the usize of the input is an Auxiliary index it uses the
-/// `coeffs` vector offset by `num_inputs`
-#[allow(clippy::redundant_closure)]
-fn dot_product<C: AffineCurve>(input: &[(C::ScalarField, usize)], coeffs: &[C]) -> C::Projective {
-    input
-        .into_par_iter()
-        .fold(
-            || C::Projective::zero(),
-            |mut sum, &(coeff, ind)| {
-                sum += &coeffs[ind].mul(coeff);
-                sum
-            },
-        )
-        .sum()
+    Ok(ret)
 }
 
 #[cfg(test)]
@@ -134,17 +292,51 @@ mod tests {
 
         let expected = get_expected(&elements, &scalar);
 
-        let got = dot_product(&input, &elements);
+        let got = dot_product_vec(&[input.clone()], elements.as_slice()).unwrap();
 
-        assert_eq!(got, expected);
+        assert_eq!(got, vec![expected]);
 
         // it also applies the coefficients vector to each row
         // in the inputs vector
         let input_vec = vec![input; 10];
-        let got = dot_product_vec(&input_vec, &elements);
+        let got = dot_product_vec(&input_vec, elements.as_slice()).unwrap();
         assert_eq!(got, vec![expected; 10])
     }
 
+    #[test]
+    fn test_dot_product_batched() {
+        let mut rng = thread_rng();
+        let elements: Vec<G1Affine> = random_point_vec(64, &mut rng);
+        // enough terms for `batch_exp` to use batch affine arithmetic, including
+        // zero and unit scalars which are handled separately
+        let input = (0..20)
+            .map(|_| {
+                (0..300)
+                    .map(|_| {
+                        let scalar = match rng.gen_range(0, 4) {
+                            0 => Fr::zero(),
+                            1 => Fr::one(),
+                            _ => Fr::rand(&mut rng),
+                        };
+                        (scalar, rng.gen_range(0, elements.len()))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let expected = input
+            .iter()
+            .map(|row| {
+                row.iter().fold(G1Projective::zero(), |sum, &(scalar, ind)| {
+                    sum + elements[ind].mul(scalar)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let got = dot_product_vec(&input, elements.as_slice()).unwrap();
+        assert_eq!(got, expected);
+    }
+
     #[test]
     fn test_dot_product_ext() {
         let mut rng = thread_rng();
@@ -157,7 +349,8 @@ mod tests {
         let alpha_coeffs_g1: Vec<G1Affine> = random_point_vec(6, &mut rng);
         let coeffs_g1: Vec<G1Affine> = random_point_vec(6, &mut rng);
 
-        let got = dot_product_ext::<Bls12_377>((&at, &beta_coeffs_g1), (&bt, &alpha_coeffs_g1), (&ct, &coeffs_g1));
+        let got =
+            dot_product_ext::<Bls12_377>((&at, &beta_coeffs_g1), (&bt, &alpha_coeffs_g1), (&ct, &coeffs_g1)).unwrap();
 
         // it should be the sum of the dot products
         let mut expected = Vec::new();
@@ -0,0 +1,210 @@
+//! A Groth16 prover for parameters over any of the evaluation domains Phase 2
+//! supports. Zexe's prover evaluates the QAP over `GeneralEvaluationDomain::new`,
+//! which always prefers radix-2 domains, so it cannot create proofs with
+//! parameters over a mixed-radix domain (see `setup_utils::UseMixedRadix`).
+//! This prover recovers the domain from the length of the H query instead.
+use crate::load_circuit::Matrices;
+use setup_utils::{dense_multiexp, domain_with_size, Error, Result};
+
+use algebra::{AffineCurve, Field, PairingEngine, PrimeField, ProjectiveCurve, UniformRand};
+use fft::EvaluationDomain;
+use groth16::{Parameters, Proof};
+
+use rand::Rng;
+
+/// Evaluates each row of a matrix at the full assignment, padding the
+/// evaluations with zeroes up to `size`
+fn evaluate_rows<F: Field>(matrix: &[Vec<(F, usize)>], assignment: &[F], size: usize) -> Vec<F> {
+    let mut evaluations = vec![F::zero(); size];
+    for (evaluation, row) in evaluations.iter_mut().zip(matrix) {
+        *evaluation = row.iter().fold(F::zero(), |acc, (coeff, variable)| {
+            acc + &(*coeff * &assignment[*variable])
+        });
+    }
+    evaluations
+}
+
+/// Computes the coefficients of `h(X) = (A(X) B(X) - C(X)) / Z(X)`, where `A`,
+/// `B` and `C` interpolate the constraints evaluated at the full assignment
+/// over `domain` and `Z` is the vanishing polynomial of `domain`
+pub fn witness_map<E: PairingEngine, D: EvaluationDomain<E::Fr>>(
+    matrices: &Matrices<E>,
+    assignment: &[E::Fr],
+    domain: &D,
+) -> Vec<E::Fr> {
+    let size = domain.size();
+    let mut a = evaluate_rows(&matrices.a, assignment, size);
+    let mut b = evaluate_rows(&matrices.b, assignment, size);
+    let mut c = evaluate_rows(&matrices.c, assignment, size);
+
+    // evaluate the polynomials over a coset of the domain, where Z does not vanish
+    for evaluations in &mut [&mut a, &mut b, &mut c] {
+        domain.ifft_in_place(evaluations);
+        domain.coset_fft_in_place(evaluations);
+    }
+    let mut h = a
+        .iter()
+        .zip(&b)
+        .zip(&c)
+        .map(|((a, b), c)| *a * b - c)
+        .collect::<Vec<_>>();
+    domain.divide_by_vanishing_poly_on_coset_in_place(&mut h);
+    domain.coset_ifft_in_place(&mut h);
+    h
+}
+
+/// Creates a proof for a circuit and its full assignment, starting with the
+/// constant one. The QAP is evaluated over the domain of the parameters, whose
+/// size is one more than the length of their H query.
+pub fn create_random_proof<E: PairingEngine, R: Rng>(
+    params: &Parameters<E>,
+    matrices: &Matrices<E>,
+    assignment: &[E::Fr],
+    rng: &mut R,
+) -> Result<Proof<E>> {
+    let num_instance_variables = matrices.num_instance_variables;
+    let num_variables = num_instance_variables + matrices.num_witness_variables;
+    let check_length = |expected: usize, got: usize| {
+        if expected == got {
+            Ok(())
+        } else {
+            Err(Error::InvalidLength { expected, got })
+        }
+    };
+    check_length(num_variables, assignment.len())?;
+    check_length(num_variables, params.a_query.len())?;
+    check_length(num_variables, params.b_g1_query.len())?;
+    check_length(num_variables, params.b_g2_query.len())?;
+    check_length(matrices.num_witness_variables, params.l_query.len())?;
+
+    let size = params.h_query.len() + 1;
+    let domain = domain_with_size::<E::Fr>(size).ok_or(Error::InvalidLength {
+        expected: size.next_power_of_two(),
+        got: size,
+    })?;
+    if matrices.num_constraints > size {
+        return Err(Error::InvalidLength {
+            expected: matrices.num_constraints,
+            got: size,
+        });
+    }
+    let h = witness_map(matrices, assignment, &domain);
+
+    let r = E::Fr::rand(rng);
+    let s = E::Fr::rand(rng);
+    let to_repr = |scalars: &[E::Fr]| scalars.iter().map(|scalar| scalar.into_repr()).collect::<Vec<_>>();
+    let assignment = to_repr(assignment);
+    let witness = &assignment[num_instance_variables..];
+    // h has degree at most size - 2
+    let h = to_repr(&h[..size - 1]);
+
+    let g_a =
+        params.vk.alpha_g1.into_projective() + dense_multiexp(&params.a_query, &assignment) + params.delta_g1.mul(r);
+    let g_b = params.vk.beta_g2.into_projective()
+        + dense_multiexp(&params.b_g2_query, &assignment)
+        + params.vk.delta_g2.mul(s);
+    let g1_b =
+        params.beta_g1.into_projective() + dense_multiexp(&params.b_g1_query, &assignment) + params.delta_g1.mul(s);
+    let g_c = dense_multiexp(&params.l_query, witness)
+        + dense_multiexp(&params.h_query, &h)
+        + g_a.into_affine().mul(s)
+        + g1_b.into_affine().mul(r)
+        - params.delta_g1.mul(r * s);
+
+    Ok(Proof {
+        a: g_a.into_affine(),
+        b: g_b.into_affine(),
+        c: g_c.into_affine(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{helpers::testing::TestCircuit, load_circuit::synthesize_assignment, parameters::MPCParameters};
+    use setup_utils::{domain_size, Groth16Params, UseMixedRadix};
+
+    use algebra::{Bls12_377, One, Zero, BW6_761};
+    use groth16::{prepare_verifying_key, verify_proof};
+
+    fn prove_curve<E: PairingEngine>(mixed_radix: UseMixedRadix) {
+        let rng = &mut rand::thread_rng();
+        let matrices = Matrices::<E>::from_circuit(TestCircuit::<E>(None)).unwrap();
+        let phase2_size = matrices.phase2_size(mixed_radix);
+
+        // the powers of tau a phase 1 ceremony with toxic waste tau, alpha and beta computes
+        let (tau, alpha, beta) = (E::Fr::rand(rng), E::Fr::rand(rng), E::Fr::rand(rng));
+        let powers = |num_powers: usize, factor: E::Fr| {
+            let mut power = factor;
+            (0..num_powers)
+                .map(|_| {
+                    let current = power;
+                    power *= &tau;
+                    current
+                })
+                .collect::<Vec<_>>()
+        };
+        let g1 = |scalars: Vec<E::Fr>| {
+            scalars
+                .into_iter()
+                .map(|scalar| E::G1Affine::prime_subgroup_generator().mul(scalar).into_affine())
+                .collect::<Vec<_>>()
+        };
+        let g2 = |scalars: Vec<E::Fr>| {
+            scalars
+                .into_iter()
+                .map(|scalar| E::G2Affine::prime_subgroup_generator().mul(scalar).into_affine())
+                .collect::<Vec<_>>()
+        };
+        let groth_params = Groth16Params::<E>::new(
+            phase2_size,
+            mixed_radix,
+            g1(powers(2 * phase2_size - 1, E::Fr::one())),
+            g2(powers(phase2_size, E::Fr::one())),
+            g1(powers(phase2_size, alpha)),
+            g1(powers(phase2_size, beta)),
+            g2(vec![beta])[0],
+        )
+        .unwrap();
+        let params = MPCParameters::<E>::new(matrices.clone(), groth_params, None)
+            .unwrap()
+            .params;
+        assert_eq!(params.h_query.len() + 1, phase2_size);
+
+        let x = E::Fr::rand(rng);
+        let assignment = synthesize_assignment(TestCircuit::<E>(Some(x))).unwrap();
+        let proof = create_random_proof(&params, &matrices, &assignment, rng).unwrap();
+        let pvk = prepare_verifying_key(&params.vk);
+        assert!(verify_proof(&pvk, &proof, &[x.square()]).unwrap());
+
+        // parameters with a truncated H query do not match the circuit
+        let mut truncated = params;
+        truncated.h_query.truncate(1);
+        assert!(create_random_proof(&truncated, &matrices, &assignment, rng).is_err());
+    }
+
+    #[test]
+    fn prove_radix2() {
+        prove_curve::<Bls12_377>(UseMixedRadix::No);
+    }
+
+    #[test]
+    fn prove_mixed_radix() {
+        // the test circuit has 6 constraints, so its mixed-radix domain is smaller
+        type Fr = <BW6_761 as PairingEngine>::Fr;
+        assert!(domain_size::<Fr>(6, UseMixedRadix::Yes) < domain_size::<Fr>(6, UseMixedRadix::No));
+        prove_curve::<BW6_761>(UseMixedRadix::Yes);
+    }
+
+    #[test]
+    fn witness_map_has_the_degree_of_h() {
+        let matrices = Matrices::<Bls12_377>::from_circuit(TestCircuit::<Bls12_377>(None)).unwrap();
+        let domain = domain_with_size::<<Bls12_377 as PairingEngine>::Fr>(8).unwrap();
+        let x = <Bls12_377 as PairingEngine>::Fr::from(3u64);
+        let assignment = synthesize_assignment(TestCircuit::<Bls12_377>(Some(x))).unwrap();
+        // A(X) B(X) - C(X) vanishes on the domain and has degree at most 14, so
+        // that h has degree at most 6
+        let h = witness_map(&matrices, &assignment, &domain);
+        assert!(h[7].is_zero());
+    }
+}
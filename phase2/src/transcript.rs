@@ -0,0 +1,196 @@
+//! # Detached transcript
+//!
+//! By default every chunk file repeats the `cs_hash`, the contributions and
+//! the header of the ceremony. In the detached format, chunk files only carry
+//! their parameters followed by the hash of a single transcript file, which
+//! holds the parts shared by all chunks in the same layout as the end of
+//! `MPCParameters`. This also lets each new contribution be verified once per
+//! transcript rather than once per chunk.
+use crate::{
+    keypair::PublicKey,
+    parameters::{ensure_unchanged, verify_transcript_step},
+    transcript_header::TranscriptHeader,
+};
+use setup_utils::{HashWriter, InvariantKind, Phase2Error, Result};
+
+use algebra::{AffineCurve, PairingEngine};
+use std::{
+    fmt,
+    io::{self, Read, Write},
+};
+
+#[derive(Clone)]
+pub struct Transcript<E: PairingEngine> {
+    pub cs_hash: [u8; 64],
+    pub contributions: Vec<PublicKey<E>>,
+    pub header: Option<TranscriptHeader>,
+}
+
+impl<E: PairingEngine> fmt::Debug for Transcript<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Transcript {{ cs_hash: {:?}, contributions: {:?}, header: {:?} }}",
+            &self.cs_hash[..],
+            self.contributions,
+            self.header
+        )
+    }
+}
+
+impl<E: PairingEngine> PartialEq for Transcript<E> {
+    fn eq(&self, other: &Transcript<E>) -> bool {
+        self.cs_hash[..] == other.cs_hash[..]
+            && self.contributions == other.contributions
+            && self.header == other.header
+    }
+}
+
+impl<E: PairingEngine> Transcript<E> {
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        writer.write_all(&self.cs_hash)?;
+        PublicKey::write_batch(&mut writer, &self.contributions)?;
+        if let Some(header) = &self.header {
+            header.write(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> Result<Self> {
+        let mut cs_hash = [0u8; 64];
+        reader.read_exact(&mut cs_hash)?;
+        let contributions = PublicKey::read_batch(&mut reader)?;
+        let header = TranscriptHeader::read_optional(&mut reader)?;
+        Ok(Transcript {
+            cs_hash,
+            contributions,
+            header,
+        })
+    }
+
+    /// The BLAKE2b hash of the serialized transcript, which detached chunk
+    /// files refer to
+    pub fn hash(&self) -> [u8; 64] {
+        let sink = io::sink();
+        let mut sink = HashWriter::new(sink);
+        self.write(&mut sink).unwrap();
+        let h = sink.into_hash();
+        let mut hash = [0u8; 64];
+        hash.copy_from_slice(h.as_ref());
+        hash
+    }
+
+    /// Checks that `after` extends this transcript with exactly one valid
+    /// contribution, and returns the hash of that contribution. Only the new
+    /// contribution is verified, so this transcript must have been verified
+    /// already.
+    pub fn verify_contribution(&self, after: &Self) -> Result<[u8; 64]> {
+        ensure_unchanged(&self.cs_hash[..], &after.cs_hash[..], InvariantKind::CsHash)?;
+        ensure_unchanged(&self.header, &after.header, InvariantKind::TranscriptHeader)?;
+        if after.contributions.len() != self.contributions.len() + 1 {
+            return Err(Phase2Error::InvalidLength.into());
+        }
+        let (pubkey, previous_contributions) = after.contributions.split_last().unwrap();
+        ensure_unchanged(
+            &self.contributions[..],
+            previous_contributions,
+            InvariantKind::Contributions,
+        )?;
+
+        let old_delta = match self.contributions.last() {
+            Some(previous) => previous.delta_after,
+            None => E::G1Affine::prime_subgroup_generator(),
+        };
+        verify_transcript_step(self.cs_hash, previous_contributions, old_delta, pubkey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{helpers::testing::TestCircuit, parameters::MPCParameters};
+    use algebra::Bls12_377;
+    use setup_utils::{BatchExpMode, CheckForCorrectness, SubgroupCheckMode, UseCompression};
+
+    fn test_parameters() -> MPCParameters<Bls12_377> {
+        let rng = &mut rand::thread_rng();
+        let params =
+            groth16::generate_random_parameters::<Bls12_377, _, _>(TestCircuit::<Bls12_377>(None), rng).unwrap();
+        MPCParameters {
+            params,
+            cs_hash: [1; 64],
+            contributions: vec![],
+            header: Some(TranscriptHeader::new(&[2; 64], &[3; 64])),
+        }
+    }
+
+    #[test]
+    fn transcript_serialization() {
+        let mut mpc = test_parameters();
+        mpc.contribute(BatchExpMode::Auto, &mut rand::thread_rng()).unwrap();
+        let transcript = mpc.transcript();
+
+        let mut serialized = vec![];
+        transcript.write(&mut serialized).unwrap();
+        let deserialized = Transcript::<Bls12_377>::read(&serialized[..]).unwrap();
+        assert_eq!(deserialized, transcript);
+        assert_eq!(deserialized.hash()[..], transcript.hash()[..]);
+    }
+
+    #[test]
+    fn detached_parameters_round_trip() {
+        let mut mpc = test_parameters();
+        mpc.contribute(BatchExpMode::Auto, &mut rand::thread_rng()).unwrap();
+        let transcript = mpc.transcript();
+
+        let mut serialized = vec![];
+        mpc.write_detached(&mut serialized, UseCompression::Yes).unwrap();
+        let deserialized = MPCParameters::<Bls12_377>::read_detached(
+            &serialized[..],
+            UseCompression::Yes,
+            CheckForCorrectness::Full,
+            true,
+            SubgroupCheckMode::Auto,
+            &transcript,
+        )
+        .unwrap();
+        assert_eq!(deserialized, mpc);
+
+        // the parameters must refer to the transcript they are read with
+        let mut other_transcript = transcript;
+        other_transcript.contributions.pop();
+        assert!(MPCParameters::<Bls12_377>::read_detached(
+            &serialized[..],
+            UseCompression::Yes,
+            CheckForCorrectness::Full,
+            true,
+            SubgroupCheckMode::Auto,
+            &other_transcript,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn verify_single_contribution() {
+        let rng = &mut rand::thread_rng();
+        let mut mpc = test_parameters();
+        mpc.contribute(BatchExpMode::Auto, rng).unwrap();
+        let before = mpc.transcript();
+        let hash = mpc.contribute(BatchExpMode::Auto, rng).unwrap();
+        let after = mpc.transcript();
+
+        assert_eq!(before.verify_contribution(&after).unwrap()[..], hash[..]);
+        // contributions can only be added one at a time
+        assert!(before.verify_contribution(&before).is_err());
+        mpc.contribute(BatchExpMode::Auto, rng).unwrap();
+        assert!(before.verify_contribution(&mpc.transcript()).is_err());
+
+        let mut wrong_cs_hash = after.clone();
+        wrong_cs_hash.cs_hash = [4; 64];
+        assert!(before.verify_contribution(&wrong_cs_hash).is_err());
+
+        let mut wrong_delta = after;
+        wrong_delta.contributions[1].delta_after = wrong_delta.contributions[0].delta_after;
+        assert!(before.verify_contribution(&wrong_delta).is_err());
+    }
+}
@@ -0,0 +1,135 @@
+//! # Transcript header
+//!
+//! Binds the Phase 2 transcript to the files it was created from. The header
+//! is written after the contributions of `MPCParameters`, so that the
+//! serialized parameters can still be read by Zexe, and it is hashed into
+//! `cs_hash` so that every contribution commits to it.
+use setup_utils::{HashWriter, Phase2Error, Result};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::{
+    fmt,
+    io::{self, Read, Write},
+};
+
+/// Magic bytes at the start of the header
+pub const TRANSCRIPT_HEADER_MAGIC: &[u8; 4] = b"p2th";
+/// Version of the header. Parameters without a header are considered to be
+/// version 0, in which `cs_hash` only depends on the initial parameters.
+pub const TRANSCRIPT_HEADER_VERSION: u32 = 1;
+
+#[derive(Clone, Copy)]
+pub struct TranscriptHeader {
+    /// BLAKE2b hash of the circuit file the parameters were generated for
+    pub circuit_hash: [u8; 64],
+    /// BLAKE2b hash of the prepared Phase 1 file the parameters were generated from
+    pub phase1_hash: [u8; 64],
+}
+
+impl fmt::Debug for TranscriptHeader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "TranscriptHeader {{ circuit_hash: {:?}, phase1_hash: {:?} }}",
+            &self.circuit_hash[..],
+            &self.phase1_hash[..]
+        )
+    }
+}
+
+impl PartialEq for TranscriptHeader {
+    fn eq(&self, other: &TranscriptHeader) -> bool {
+        self.circuit_hash[..] == other.circuit_hash[..] && self.phase1_hash[..] == other.phase1_hash[..]
+    }
+}
+
+impl TranscriptHeader {
+    pub fn new(circuit_hash: &[u8], phase1_hash: &[u8]) -> Self {
+        let mut header = TranscriptHeader {
+            circuit_hash: [0; 64],
+            phase1_hash: [0; 64],
+        };
+        header.circuit_hash.copy_from_slice(circuit_hash);
+        header.phase1_hash.copy_from_slice(phase1_hash);
+        header
+    }
+
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        writer.write_all(TRANSCRIPT_HEADER_MAGIC)?;
+        writer.write_u32::<BigEndian>(TRANSCRIPT_HEADER_VERSION)?;
+        writer.write_all(&self.circuit_hash)?;
+        writer.write_all(&self.phase1_hash)?;
+        Ok(())
+    }
+
+    /// Reads the header if there is one, i.e. if the reader is not exhausted
+    pub fn read_optional<R: Read>(mut reader: R) -> Result<Option<Self>> {
+        let mut magic = vec![];
+        (&mut reader).take(4).read_to_end(&mut magic)?;
+        if magic.is_empty() {
+            return Ok(None);
+        }
+        if magic[..] != TRANSCRIPT_HEADER_MAGIC[..] {
+            return Err(Phase2Error::InvalidTranscriptHeader.into());
+        }
+        let version = reader.read_u32::<BigEndian>()?;
+        if version != TRANSCRIPT_HEADER_VERSION {
+            return Err(Phase2Error::InvalidTranscriptHeader.into());
+        }
+        let mut header = TranscriptHeader {
+            circuit_hash: [0; 64],
+            phase1_hash: [0; 64],
+        };
+        reader.read_exact(&mut header.circuit_hash)?;
+        reader.read_exact(&mut header.phase1_hash)?;
+        Ok(Some(header))
+    }
+
+    /// Derives the `cs_hash` of a transcript from the hash of its initial parameters
+    pub fn cs_hash(&self, params_hash: &[u8; 64]) -> [u8; 64] {
+        let sink = io::sink();
+        let mut sink = HashWriter::new(sink);
+        sink.write_all(&params_hash[..]).unwrap();
+        self.write(&mut sink).unwrap();
+        let h = sink.into_hash();
+        let mut cs_hash = [0; 64];
+        cs_hash.copy_from_slice(h.as_ref());
+        cs_hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_serialization() {
+        let header = TranscriptHeader::new(&[1; 64], &[2; 64]);
+        let mut serialized = vec![];
+        header.write(&mut serialized).unwrap();
+        let deserialized = TranscriptHeader::read_optional(&serialized[..]).unwrap();
+        assert_eq!(deserialized, Some(header));
+
+        // no header at all
+        assert_eq!(TranscriptHeader::read_optional(&[0u8; 0][..]).unwrap(), None);
+        // garbage instead of a header
+        assert!(TranscriptHeader::read_optional(&[0u8; 8][..]).is_err());
+    }
+
+    #[test]
+    fn cs_hash_binds_sources() {
+        let params_hash = [3; 64];
+        let header = TranscriptHeader::new(&[1; 64], &[2; 64]);
+        let other_circuit = TranscriptHeader::new(&[4; 64], &[2; 64]);
+        let other_phase1 = TranscriptHeader::new(&[1; 64], &[4; 64]);
+        assert_ne!(
+            &header.cs_hash(&params_hash)[..],
+            &other_circuit.cs_hash(&params_hash)[..]
+        );
+        assert_ne!(
+            &header.cs_hash(&params_hash)[..],
+            &other_phase1.cs_hash(&params_hash)[..]
+        );
+        assert_ne!(&header.cs_hash(&params_hash)[..], &params_hash[..]);
+    }
+}
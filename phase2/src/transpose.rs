@@ -0,0 +1,188 @@
+//! Transposition of the circuit matrices through an on-disk spill, so that the
+//! QAP of circuits which do not fit in memory can be evaluated one range of
+//! variables at a time
+use crate::load_circuit::CircuitFile;
+use setup_utils::{Error, Result};
+
+use algebra::{CanonicalDeserialize, CanonicalSerialize, PairingEngine, Zero};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use rayon::prelude::*;
+use std::{marker::PhantomData, ops::Range};
+
+/// The size of the constraint index stored along with each coefficient
+const INDEX_SIZE: usize = 8;
+
+/// The A, B and C matrices of a circuit transposed in a spill buffer, where the
+/// entries of each matrix are stored by variable, i.e. column-major
+pub struct TransposedMatrices<'a, E: PairingEngine> {
+    pub num_instance_variables: usize,
+    pub num_witness_variables: usize,
+    pub num_constraints: usize,
+    /// For each matrix, the position in the spill of the first entry of each
+    /// variable, followed by the position after its last entry
+    offsets: [Vec<usize>; 3],
+    spill: &'a [u8],
+    _engine: PhantomData<E>,
+}
+
+impl<'a, E: PairingEngine> TransposedMatrices<'a, E> {
+    /// The size of the spill buffer needed to transpose the circuit
+    pub fn spill_size(circuit: &CircuitFile<E>) -> usize {
+        circuit.num_non_zero.iter().sum::<usize>() * entry_size::<E>()
+    }
+
+    /// Transposes the matrices of the circuit into `spill`, which must be
+    /// `spill_size` bytes long and is typically a memory mapped file. Each
+    /// matrix is read twice: once to count the entries of each variable, and
+    /// once to write the entries at their position in the spill.
+    pub fn new(circuit: &CircuitFile<E>, spill: &'a mut [u8]) -> Result<Self> {
+        if spill.len() != Self::spill_size(circuit) {
+            return Err(Error::InvalidLength {
+                expected: Self::spill_size(circuit),
+                got: spill.len(),
+            });
+        }
+        let num_variables = circuit.num_instance_variables + circuit.num_witness_variables;
+        let entry_size = entry_size::<E>();
+
+        let mut offsets: [Vec<usize>; 3] = Default::default();
+        let mut position = 0;
+        for (matrix, offsets) in offsets.iter_mut().enumerate() {
+            let mut counts = vec![0usize; num_variables];
+            for row in circuit.rows(matrix) {
+                for (_, variable) in row? {
+                    *counts
+                        .get_mut(variable)
+                        .ok_or_else(|| Error::InvalidCircuitFile("a variable is out of range".to_string()))? += 1;
+                }
+            }
+            if counts.iter().sum::<usize>() != circuit.num_non_zero[matrix] {
+                return Err(Error::InvalidCircuitFile(
+                    "the number of non-zero entries of a matrix does not match".to_string(),
+                ));
+            }
+
+            // the entries of each variable follow those of the previous variable
+            offsets.reserve(num_variables + 1);
+            for count in counts {
+                offsets.push(position);
+                position += count;
+            }
+            offsets.push(position);
+
+            let mut cursors = offsets[..num_variables].to_vec();
+            for (constraint, row) in circuit.rows(matrix).enumerate() {
+                for (coeff, variable) in row? {
+                    let mut entry = &mut spill[cursors[variable] * entry_size..(cursors[variable] + 1) * entry_size];
+                    entry.write_u64::<LittleEndian>(constraint as u64)?;
+                    coeff.serialize(&mut entry)?;
+                    cursors[variable] += 1;
+                }
+            }
+        }
+
+        Ok(TransposedMatrices {
+            num_instance_variables: circuit.num_instance_variables,
+            num_witness_variables: circuit.num_witness_variables,
+            num_constraints: circuit.num_constraints,
+            offsets,
+            spill,
+            _engine: PhantomData,
+        })
+    }
+
+    /// Splits the variables into consecutive ranges whose entries in the three
+    /// matrices take at most `memory_budget` bytes once read. A variable whose
+    /// entries exceed the budget gets a range of its own.
+    pub fn ranges(&self, memory_budget: usize) -> Vec<Range<usize>> {
+        let entry_memory = std::mem::size_of::<(E::Fr, usize)>();
+        let num_variables = self.num_instance_variables + self.num_witness_variables;
+
+        let mut ranges = vec![];
+        let mut start = 0;
+        let mut size = 0;
+        for variable in 0..num_variables {
+            let variable_size = self
+                .offsets
+                .iter()
+                .map(|offsets| offsets[variable + 1] - offsets[variable])
+                .sum::<usize>()
+                * entry_memory;
+            if variable > start && size + variable_size > memory_budget {
+                ranges.push(start..variable);
+                start = variable;
+                size = 0;
+            }
+            size += variable_size;
+        }
+        if start < num_variables {
+            ranges.push(start..num_variables);
+        }
+        ranges
+    }
+
+    /// Reads the entries of the variables in `range` of the A (0), B (1) or C (2)
+    /// matrix, in the format of `MPCParameters::process_matrix`
+    pub fn columns(&self, matrix: usize, range: Range<usize>) -> Result<Vec<Vec<(E::Fr, usize)>>> {
+        let offsets = &self.offsets[matrix];
+        let entry_size = entry_size::<E>();
+        range
+            .into_par_iter()
+            .map(|variable| {
+                self.spill[offsets[variable] * entry_size..offsets[variable + 1] * entry_size]
+                    .chunks(entry_size)
+                    .map(|mut entry| -> Result<(E::Fr, usize)> {
+                        let constraint = entry.read_u64::<LittleEndian>()? as usize;
+                        let coeff = E::Fr::deserialize(&mut entry)?;
+                        Ok((coeff, constraint))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// The size of an entry of the spill, i.e. a constraint index and a coefficient
+fn entry_size<E: PairingEngine>() -> usize {
+    INDEX_SIZE + E::Fr::zero().serialized_size()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{helpers::testing::TestCircuit, load_circuit::Matrices, parameters::MPCParameters};
+
+    use algebra::Bls12_377;
+
+    #[test]
+    fn transposes_like_process_matrix() {
+        transposes_like_process_matrix_curve::<Bls12_377>()
+    }
+
+    fn transposes_like_process_matrix_curve<E: PairingEngine>() {
+        let matrices = Matrices::<E>::from_circuit(TestCircuit::<E>(None)).unwrap();
+        let mut serialized = vec![];
+        matrices.write(&mut serialized).unwrap();
+
+        let circuit = CircuitFile::<E>::new(&serialized).unwrap();
+        assert_eq!(circuit.num_constraints, matrices.num_constraints);
+        assert_eq!(circuit.rows(1).collect::<Result<Vec<_>>>().unwrap(), matrices.b);
+
+        let mut spill = vec![0; TransposedMatrices::spill_size(&circuit)];
+        let transposed = TransposedMatrices::new(&circuit, &mut spill).unwrap();
+        let num_variables = matrices.num_instance_variables + matrices.num_witness_variables;
+        // a budget of a single entry gives a range per variable
+        let entry_memory = std::mem::size_of::<(E::Fr, usize)>();
+        assert_eq!(transposed.ranges(entry_memory).len(), num_variables);
+        assert_eq!(transposed.ranges(usize::MAX), vec![0..num_variables]);
+
+        for (matrix, xt) in [&matrices.a, &matrices.b, &matrices.c].iter().enumerate() {
+            let expected = MPCParameters::<E>::process_matrix(xt, &matrices);
+            let mut got = vec![];
+            for range in transposed.ranges(4 * entry_memory) {
+                got.extend(transposed.columns(matrix, range).unwrap());
+            }
+            assert_eq!(got, expected);
+        }
+    }
+}
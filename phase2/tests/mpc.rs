@@ -11,10 +11,11 @@ use phase2::{
     chunked_groth16::verify,
     helpers::testing::TestCircuit,
     parameters::{MPCParameters, Phase2ContributionMode},
+    transcript_header::TranscriptHeader,
 };
 use r1cs_core::{ConstraintSynthesizer, ConstraintSystem, SynthesisMode};
 use rand::{thread_rng, Rng};
-use setup_utils::{derive_rng_from_seed, BatchExpMode, Groth16Params, UseCompression};
+use setup_utils::{calculate_hash, derive_rng_from_seed, BatchExpMode, Groth16Params, UseCompression, UseMixedRadix};
 
 fn generate_mpc_parameters<E, C>(c: C, rng: &mut impl Rng) -> MPCParameters<E>
 where
@@ -50,6 +51,7 @@ where
     // prepare only the first 32 powers (for whatever reason)
     let groth_params = Groth16Params::<E>::new(
         1 << powers,
+        UseMixedRadix::No,
         accumulator.tau_powers_g1,
         accumulator.tau_powers_g2,
         accumulator.alpha_tau_powers_g1,
@@ -84,6 +86,7 @@ where
         CheckForCorrectness::Full,
         1 << powers,
         phase2_size,
+        None,
     )
     .unwrap();
 
@@ -129,6 +132,7 @@ where
     // prepare only the first 32 powers (for whatever reason)
     let groth_params = Groth16Params::<E>::new(
         1 << powers,
+        UseMixedRadix::No,
         accumulator.tau_powers_g1,
         accumulator.tau_powers_g2,
         accumulator.alpha_tau_powers_g1,
@@ -157,6 +161,11 @@ where
 
     let chunk_size = phase2_size / 3;
 
+    // bind the circuit and the phase 1 transcript into the parameters
+    let mut circuit = vec![];
+    matrices.write(&mut circuit).unwrap();
+    let header = TranscriptHeader::new(&calculate_hash(&circuit), &calculate_hash(&writer));
+
     let (full_mpc_before, queries, mut mpcs) = MPCParameters::<E>::new_from_buffer_chunked(
         matrices,
         writer.as_mut(),
@@ -165,8 +174,10 @@ where
         1 << powers,
         phase2_size,
         chunk_size,
+        Some(header),
     )
     .unwrap();
+    full_mpc_before.verify_cs_hash().unwrap();
 
     let mut full_mpc_before_serialized = vec![];
     full_mpc_before
@@ -183,6 +194,7 @@ where
     }
 
     let full_mpc_after = MPCParameters::<E>::combine(&queries, &mpcs).unwrap();
+    assert_eq!(full_mpc_after.header, Some(header));
     let mut full_mpc_after_serialized = vec![];
     full_mpc_after
         .write(&mut full_mpc_after_serialized, UseCompression::Yes)
@@ -1,8 +1,32 @@
-use setup_utils::{BatchDeserializer, BatchSerializer, UseCompression};
+use setup_utils::{
+    BatchDeserializer, BatchSerializer, CheckForCorrectness, Deserializer, SubgroupCheckMode, UseCompression,
+};
 
-use algebra::{AffineCurve, Bls12_377, PairingEngine};
+use algebra::{AffineCurve, Bls12_377, PairingEngine, ProjectiveCurve, UniformRand};
 
 use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rand::thread_rng;
+use rayon::prelude::*;
+
+// Returns random elements and a buffer they are written to
+fn random_vec_buf<C: AffineCurve>(num_els: usize, compression: UseCompression) -> (Vec<C>, Vec<u8>) {
+    let (elements, mut buf) = random_vec_empty_buf::<C>(num_els, compression);
+    buf.write_batch(&elements, compression).unwrap();
+    (elements, buf)
+}
+
+// Returns random elements and an empty buffer large enough to write them
+fn random_vec_empty_buf<C: AffineCurve>(num_els: usize, compression: UseCompression) -> (Vec<C>, Vec<u8>) {
+    let mut rng = thread_rng();
+    let elements = (0..num_els)
+        .map(|_| C::Projective::rand(&mut rng).into_affine())
+        .collect::<Vec<_>>();
+    let size = match compression {
+        UseCompression::Yes => C::SERIALIZED_SIZE,
+        UseCompression::No => C::UNCOMPRESSED_SIZE,
+    };
+    (elements, vec![0; num_els * size])
+}
 
 /// Benchmark comparing reading compressed/uncompressed points
 /// with preallocated vectors and allocating new vectors each time.
@@ -25,15 +49,57 @@ fn read<C: AffineCurve>(c: &mut Criterion, el_type: &str) {
 
             let (_, buf) = random_vec_buf::<C>(*num_els, *compression);
             group.bench_with_input(format!("normal_{}", compression), &num_els, |b, _num_els| {
-                b.iter(|| buf.read_batch::<C>(*compression).unwrap());
+                b.iter(|| buf.read_batch::<C>(*compression, CheckForCorrectness::No).unwrap());
             });
 
             let (mut elements, buf) = random_vec_buf(*num_els, *compression);
             group.bench_with_input(format!("preallocated_{}", compression), &num_els, |b, _num_els| {
-                b.iter(|| buf.read_batch_preallocated::<C>(&mut elements, *compression).unwrap());
+                b.iter(|| {
+                    buf.read_batch_preallocated::<C>(&mut elements, *compression, CheckForCorrectness::No)
+                        .unwrap()
+                });
+            });
+        }
+    }
+}
+
+/// Benchmark comparing reading compressed points one by one, with a subgroup check for each point
+/// while decompressing it, and reading them in a batch, which decompresses them together and then
+/// checks that they are in the subgroup with each mode. The batched decompression shares the
+/// inversions of the square roots of G2 coordinates, and the `Auto` check uses
+/// `batch_verify_in_subgroup` for batches of more than 2^12 elements.
+fn read_checked<C: AffineCurve>(c: &mut Criterion, el_type: &str) {
+    let mut group = c.benchmark_group(format!("read_checked_{}", el_type));
+    group.sample_size(10);
+    let compression = UseCompression::Yes;
+    let els = (10..16).map(|i| 2u32.pow(i) as usize).collect::<Vec<usize>>();
+
+    for num_els in &els {
+        group.throughput(Throughput::Elements(*num_els as u64));
+
+        let (_, buf) = random_vec_buf::<C>(*num_els, compression);
+        group.bench_with_input("individual", &num_els, |b, _num_els| {
+            b.iter(|| {
+                buf.par_chunks(C::SERIALIZED_SIZE)
+                    .map(|mut buf| buf.read_element::<C>(compression, CheckForCorrectness::Full))
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap()
+            });
+        });
+        for mode in &[
+            SubgroupCheckMode::Auto,
+            SubgroupCheckMode::Direct,
+            SubgroupCheckMode::Endomorphism,
+        ] {
+            group.bench_with_input(format!("batched_{}", mode), &num_els, |b, _num_els| {
+                b.iter(|| {
+                    buf.read_batch_with_subgroup_check_mode::<C>(compression, CheckForCorrectness::Full, *mode)
+                        .unwrap()
+                });
             });
         }
     }
+    group.finish()
 }
 
 /// Benchmark comparing writing compressed/uncompressed points in parallel & serial
@@ -61,6 +127,8 @@ fn write<C: AffineCurve>(c: &mut Criterion, el_type: &str) {
 fn read_curve<E: PairingEngine>(c: &mut Criterion) {
     read::<E::G1Affine>(c, "g1");
     read::<E::G2Affine>(c, "g2");
+    read_checked::<E::G1Affine>(c, "g1");
+    read_checked::<E::G2Affine>(c, "g2");
 }
 
 fn read_bls12_377(c: &mut Criterion) {
@@ -8,6 +8,7 @@ use algebra::{
 
 use criterion::{criterion_group, criterion_main, Criterion, Throughput};
 use rand::Rng;
+use rayon::prelude::*;
 use std::ops::MulAssign;
 
 // This was the previous implementation using chunks, we keep it here to compare performance
@@ -34,6 +35,15 @@ pub fn generate_powers_of_tau_crossbeam<E: PairingEngine>(tau: &E::Fr, start: us
     taupowers
 }
 
+// This was the previous implementation, with an exponentiation per power, we keep
+// it here to compare performance against the prefix product implementation
+pub fn generate_powers_of_tau_pow<E: PairingEngine>(tau: &E::Fr, start: usize, end: usize) -> Vec<E::Fr> {
+    (start as u64..end as u64)
+        .into_par_iter()
+        .map(|i| tau.pow([i]))
+        .collect()
+}
+
 // Benchmark showing that the Rayon generator is faster
 fn benchmark_phase1(c: &mut Criterion) {
     let mut rng = rand::thread_rng();
@@ -51,6 +61,26 @@ fn benchmark_phase1(c: &mut Criterion) {
     group.finish();
 }
 
+// Benchmark showing that the prefix product is faster than an exponentiation per power
+fn benchmark_powers_of_tau(c: &mut Criterion) {
+    let mut group = c.benchmark_group("PowersOfTauPrefix");
+    group.sample_size(10);
+    let mut rng = rand::thread_rng();
+    let tau = <Bls12_377 as PairingEngine>::Fr::rand(&mut rng);
+    for len in (10..17).map(|i| 2usize.pow(i)) {
+        group.throughput(Throughput::Elements(len as u64));
+        // start far from zero, as chunks do
+        let start = 1 << 20;
+        group.bench_with_input("prefix", &len, |b, len| {
+            b.iter(|| generate_powers_of_tau::<Bls12_377>(&tau, start, start + len))
+        });
+        group.bench_with_input("pow", &len, |b, len| {
+            b.iter(|| generate_powers_of_tau_pow::<Bls12_377>(&tau, start, start + len))
+        });
+    }
+    group.finish();
+}
+
 // Benchmark for finding the optimal batch size for batch_exp
 fn benchmark_batchexp(c: &mut Criterion) {
     let mut group = c.benchmark_group("Exponentiation");
@@ -88,5 +118,11 @@ fn randomness<G: AffineCurve>(v: &[G], rng: &mut impl Rng) -> Vec<<G::ScalarFiel
     (0..v.len()).map(|_| G::ScalarField::rand(rng).into_repr()).collect()
 }
 
-criterion_group!(benches, benchmark_phase1, benchmark_batchexp, benchmark_multiexp);
+criterion_group!(
+    benches,
+    benchmark_phase1,
+    benchmark_powers_of_tau,
+    benchmark_batchexp,
+    benchmark_multiexp
+);
 criterion_main!(benches);
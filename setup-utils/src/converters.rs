@@ -64,8 +64,11 @@ pub fn subgroup_check_mode_from_str(src: &str) -> Result<SubgroupCheckMode, Stri
         "auto" => SubgroupCheckMode::Auto,
         "direct" => SubgroupCheckMode::Direct,
         "batched" => SubgroupCheckMode::Batched,
+        "endomorphism" => SubgroupCheckMode::Endomorphism,
         _ => {
-            return Err("unsupported subgroup check mode. Currently supported: auto, direct, batched".to_string());
+            return Err(
+                "unsupported subgroup check mode. Currently supported: auto, direct, batched, endomorphism".to_string(),
+            );
         }
     };
     Ok(subgroup_check_mode)
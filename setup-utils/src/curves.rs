@@ -0,0 +1,245 @@
+//! Arithmetic which depends on the curve, implemented on the parameters of the curves
+//! the ceremony supports, each behind the crate feature of its curve. Generic code over
+//! `AffineCurve` reaches it through the functions of this module, which return `None`
+//! for the points of other curves.
+#![cfg_attr(
+    not(all(feature = "bls12_377", feature = "bw6_761")),
+    allow(dead_code, unused_imports)
+)]
+use crate::{endomorphism, Result};
+
+use algebra::{
+    batch_inversion, cfg_iter, short_weierstrass_jacobian::GroupAffine, AffineCurve, CanonicalDeserializeWithFlags,
+    ConstantSerializedSize, Field, One, SWFlags, SWModelParameters, SerializationError, SquareRootField, Zero,
+};
+use fft::cfg_chunks;
+
+#[cfg(feature = "bls12_377")]
+use algebra::bls12_377;
+#[cfg(feature = "bw6_761")]
+use algebra::bw6_761;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::any::{Any, TypeId};
+
+/// The parameters of a curve the ceremony supports
+pub(crate) trait SupportedCurveParameters: SWModelParameters {
+    /// Returns whether all the elements are in the prime order subgroup, using an endomorphism
+    /// of the curve, or `None` if the endomorphism could not be derived
+    fn check_subgroup_endomorphism(elements: &[&GroupAffine<Self>]) -> Option<bool>;
+
+    /// Returns the square roots of the elements, or `None` for the elements which are not squares
+    fn batch_sqrt(elements: &[Self::BaseField]) -> Vec<Option<Self::BaseField>> {
+        cfg_iter!(elements).map(|element| element.sqrt()).collect()
+    }
+}
+
+#[cfg(feature = "bls12_377")]
+impl SupportedCurveParameters for bls12_377::g1::Parameters {
+    fn check_subgroup_endomorphism(elements: &[&bls12_377::G1Affine]) -> Option<bool> {
+        endomorphism::check_bls12_377_g1(elements)
+    }
+}
+
+#[cfg(feature = "bls12_377")]
+impl SupportedCurveParameters for bls12_377::g2::Parameters {
+    fn check_subgroup_endomorphism(elements: &[&bls12_377::G2Affine]) -> Option<bool> {
+        endomorphism::check_bls12_377_g2(elements)
+    }
+
+    /// The square root of a0 + a1 u with a1 nonzero is c0 + c1 u, with c0^2 = (a0 ± sqrt(a0^2 - β a1^2)) / 2
+    /// and c1 = a1 / 2c0 (Algorithm 9 of https://eprint.iacr.org/2012/685), so the inversions
+    /// of 2c0 are shared across the batch with Montgomery's trick
+    fn batch_sqrt(elements: &[bls12_377::Fq2]) -> Vec<Option<bls12_377::Fq2>> {
+        let two_inv = bls12_377::Fq::one()
+            .double()
+            .inverse()
+            .expect("two should have an inverse");
+        let c0s = cfg_iter!(elements)
+            .map(|a| {
+                if a.c1.is_zero() {
+                    return None;
+                }
+                let alpha = a.norm().sqrt()?;
+                let delta = (alpha + &a.c0) * &two_inv;
+                delta.sqrt().or_else(|| (delta - &alpha).sqrt())
+            })
+            .collect::<Vec<_>>();
+        // zeroes are skipped by the batch inversion
+        let mut inverses = c0s
+            .iter()
+            .map(|c0| c0.map_or(bls12_377::Fq::zero(), |c0| c0.double()))
+            .collect::<Vec<_>>();
+        batch_inversion(&mut inverses);
+
+        cfg_iter!(elements)
+            .zip(c0s)
+            .zip(inverses)
+            .map(|((a, c0), inverse)| {
+                if a.c1.is_zero() {
+                    return a.sqrt();
+                }
+                let root = bls12_377::Fq2::new(c0?, a.c1 * &inverse);
+                // a is not a square if the candidate is not its root
+                if root.square() == *a {
+                    Some(root)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "bw6_761")]
+impl SupportedCurveParameters for bw6_761::g1::Parameters {
+    fn check_subgroup_endomorphism(elements: &[&bw6_761::G1Affine]) -> Option<bool> {
+        endomorphism::check_bw6_761_g1(elements)
+    }
+}
+
+#[cfg(feature = "bw6_761")]
+impl SupportedCurveParameters for bw6_761::g2::Parameters {
+    fn check_subgroup_endomorphism(elements: &[&bw6_761::G2Affine]) -> Option<bool> {
+        endomorphism::check_bw6_761_g2(elements)
+    }
+}
+
+/// Returns whether `C` is the group of affine points of the curve with parameters `P`
+fn is_curve<C: AffineCurve, P: SWModelParameters>() -> bool {
+    TypeId::of::<C>() == TypeId::of::<GroupAffine<P>>()
+}
+
+/// Returns whether all the elements are in the prime order subgroup, using an endomorphism
+/// of their curve, or `None` if there is no endomorphism based check for their curve
+pub(crate) fn check_subgroup_endomorphism<C: AffineCurve>(elements: &[C]) -> Option<bool> {
+    let checks: &[fn(&[C]) -> Option<bool>] = &[
+        #[cfg(feature = "bls12_377")]
+        check_subgroup_endomorphism_with::<C, bls12_377::g1::Parameters>,
+        #[cfg(feature = "bls12_377")]
+        check_subgroup_endomorphism_with::<C, bls12_377::g2::Parameters>,
+        #[cfg(feature = "bw6_761")]
+        check_subgroup_endomorphism_with::<C, bw6_761::g1::Parameters>,
+        #[cfg(feature = "bw6_761")]
+        check_subgroup_endomorphism_with::<C, bw6_761::g2::Parameters>,
+    ];
+    checks.iter().find_map(|check| check(elements))
+}
+
+fn check_subgroup_endomorphism_with<C: AffineCurve, P: SupportedCurveParameters>(elements: &[C]) -> Option<bool> {
+    if !is_curve::<C, P>() {
+        return None;
+    }
+    let elements = elements
+        .iter()
+        .map(|p| {
+            (p as &dyn Any)
+                .downcast_ref::<GroupAffine<P>>()
+                .expect("should have the type of the points")
+        })
+        .collect::<Vec<_>>();
+    P::check_subgroup_endomorphism(&elements)
+}
+
+/// Decompresses the points serialized in the buffer without checking that they are in the
+/// prime order subgroup, or returns `None` if they are not the points of a supported curve
+pub(crate) fn decompress_batch<C: AffineCurve>(buffer: &[u8]) -> Option<Result<Vec<C>>> {
+    let decompressions: &[fn(&[u8]) -> Option<Result<Vec<C>>>] = &[
+        #[cfg(feature = "bls12_377")]
+        decompress_batch_with::<C, bls12_377::g1::Parameters>,
+        #[cfg(feature = "bls12_377")]
+        decompress_batch_with::<C, bls12_377::g2::Parameters>,
+        #[cfg(feature = "bw6_761")]
+        decompress_batch_with::<C, bw6_761::g1::Parameters>,
+        #[cfg(feature = "bw6_761")]
+        decompress_batch_with::<C, bw6_761::g2::Parameters>,
+    ];
+    decompressions.iter().find_map(|decompress| decompress(buffer))
+}
+
+fn decompress_batch_with<C: AffineCurve, P: SupportedCurveParameters>(buffer: &[u8]) -> Option<Result<Vec<C>>> {
+    if !is_curve::<C, P>() {
+        return None;
+    }
+    Some(decompress::<P>(buffer).map(|points| {
+        *(Box::new(points) as Box<dyn Any>)
+            .downcast::<Vec<C>>()
+            .expect("should have the type of the points")
+    }))
+}
+
+/// Each point is serialized as its x coordinate, with flags for the sign of y or the point
+/// at infinity. The square roots of y^2 = x^3 + ax + b are computed together by `batch_sqrt`,
+/// and each y is then picked with its sign as in `GroupAffine::get_point_from_x`.
+fn decompress<P: SupportedCurveParameters>(buffer: &[u8]) -> Result<Vec<GroupAffine<P>>> {
+    let xs = cfg_chunks!(buffer, GroupAffine::<P>::SERIALIZED_SIZE)
+        .map(|mut chunk| -> Result<(P::BaseField, SWFlags)> {
+            Ok(CanonicalDeserializeWithFlags::deserialize_with_flags(&mut chunk)?)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let y_squares = cfg_iter!(xs)
+        .map(|(x, _)| P::add_b(&(x.square() * x + &P::mul_by_a(x))))
+        .collect::<Vec<_>>();
+    let ys = P::batch_sqrt(&y_squares);
+
+    cfg_iter!(xs)
+        .zip(ys)
+        .map(|(&(x, flags), y)| -> Result<GroupAffine<P>> {
+            match flags.is_positive() {
+                // the point at infinity
+                None => Ok(GroupAffine::zero()),
+                Some(greatest) => {
+                    // x is not the coordinate of a point of the curve
+                    let y = y.ok_or(SerializationError::InvalidData)?;
+                    let neg_y = -y;
+                    let y = if (y < neg_y) ^ greatest { y } else { neg_y };
+                    Ok(GroupAffine::new(x, y, false))
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BatchSerializer, UseCompression};
+
+    use algebra::{CanonicalDeserialize, ProjectiveCurve, UniformRand};
+    use rand::{thread_rng, Rng};
+
+    #[cfg(feature = "bls12_377")]
+    #[test]
+    fn decompress_bls12_377() {
+        decompress_curve::<bls12_377::G1Affine>();
+        decompress_curve::<bls12_377::G2Affine>();
+    }
+
+    #[cfg(feature = "bw6_761")]
+    #[test]
+    fn decompress_bw6_761() {
+        decompress_curve::<bw6_761::G1Affine>();
+        decompress_curve::<bw6_761::G2Affine>();
+    }
+
+    fn decompress_curve<C: AffineCurve>() {
+        let mut rng = thread_rng();
+        let mut elements = (0..10)
+            .map(|_| C::Projective::rand(&mut rng).into_affine())
+            .collect::<Vec<_>>();
+        elements.push(C::zero());
+        let mut buf = vec![0; elements.len() * C::SERIALIZED_SIZE];
+        buf.write_batch(&elements, UseCompression::Yes).unwrap();
+        assert_eq!(decompress_batch::<C>(&buf).unwrap().unwrap(), elements);
+
+        // bytes which do not encode a point are rejected as by Zexe
+        for _ in 0..100 {
+            let bytes = (0..C::SERIALIZED_SIZE).map(|_| rng.gen()).collect::<Vec<u8>>();
+            assert_eq!(
+                decompress_batch::<C>(&bytes).unwrap().ok(),
+                C::deserialize_unchecked(&bytes[..]).ok().map(|p| vec![p])
+            );
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{BatchDeserializer, Error};
+use crate::{curves::check_subgroup_endomorphism, BatchDeserializer, Error};
 use algebra::{
     batch_verify_in_subgroup, cfg_iter, AffineCurve, CanonicalDeserialize, CanonicalSerialize, FpParameters,
     PrimeField, Read, SerializationError, Write, Zero,
@@ -90,6 +90,7 @@ pub enum SubgroupCheckMode {
     Auto,
     Direct,
     Batched,
+    Endomorphism,
     No,
 }
 
@@ -99,6 +100,7 @@ impl fmt::Display for SubgroupCheckMode {
             SubgroupCheckMode::Auto => write!(f, "Auto"),
             SubgroupCheckMode::Direct => write!(f, "Direct"),
             SubgroupCheckMode::Batched => write!(f, "Batched"),
+            SubgroupCheckMode::Endomorphism => write!(f, "Endomorphism"),
             SubgroupCheckMode::No => write!(f, "No"),
         }
     }
@@ -145,10 +147,11 @@ pub fn check_subgroup<C: AffineCurve>(
                 _ => false,
             }
         }
-        (false, SubgroupCheckMode::Auto) | (_, SubgroupCheckMode::Direct) => cfg_iter!(elements).all(|p| {
-            p.mul(<<C::ScalarField as PrimeField>::Params as FpParameters>::MODULUS)
-                .is_zero()
-        }),
+        // fall back to the direct check on curves without an endomorphism check
+        (_, SubgroupCheckMode::Endomorphism) => {
+            check_subgroup_endomorphism(elements).unwrap_or_else(|| check_subgroup_direct(elements))
+        }
+        (false, SubgroupCheckMode::Auto) | (_, SubgroupCheckMode::Direct) => check_subgroup_direct(elements),
     };
     if !prime_order_subgroup_check_pass {
         return Err(Error::IncorrectSubgroup);
@@ -157,6 +160,14 @@ pub fn check_subgroup<C: AffineCurve>(
     Ok(())
 }
 
+/// Checks that the elements are in the prime order subgroup by multiplying them by its order
+fn check_subgroup_direct<C: AffineCurve>(elements: &[C]) -> bool {
+    cfg_iter!(elements).all(|p| {
+        p.mul(<<C::ScalarField as PrimeField>::Params as FpParameters>::MODULUS)
+            .is_zero()
+    })
+}
+
 pub fn read_vec<G: AffineCurve, R: Read>(
     mut reader: R,
     compressed: UseCompression,
@@ -0,0 +1,243 @@
+//! Subgroup checks which use an endomorphism of the curve instead of multiplying
+//! each point by the scalar field modulus, following "Faster Subgroup Checks for
+//! BLS12-381" (Bowe, https://eprint.iacr.org/2019/814) and "A note on group membership
+//! tests for G1, G2 and GT on BLS pairing-friendly curves" (Scott, https://eprint.iacr.org/2021/1130).
+//! They are the `check_subgroup_endomorphism` methods of the parameters of the supported curves.
+#![cfg_attr(not(all(feature = "bls12_377", feature = "bw6_761")), allow(dead_code))]
+use algebra::{cfg_iter, AffineCurve, BigInteger, Field, One, PrimeField, ProjectiveCurve, SquareRootField, Zero};
+
+#[cfg(feature = "bls12_377")]
+use algebra::bls12_377;
+#[cfg(feature = "bw6_761")]
+use algebra::bw6_761;
+
+#[cfg(not(feature = "wasm"))]
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+/// The parameter x of BLS12-377, which BW6-761 is built from
+const X: u64 = 0x8508c00000000001;
+
+/// φ(x, y) = (ωx, y) acts on G1 as a cube root of unity modulo r, and [x^2]P + φ(P)
+/// is zero exactly on G1 for the right choice of ω (Bowe)
+#[cfg(feature = "bls12_377")]
+pub(crate) fn check_bls12_377_g1(elements: &[&bls12_377::G1Affine]) -> Option<bool> {
+    let x = bls12_377::Fr::from(X);
+    let on_curve = cfg_iter!(elements).all(|p| p.is_on_curve());
+    let in_subgroup = check_with_candidates(
+        elements,
+        &cube_roots_of_unity::<bls12_377::Fq>()?,
+        |p, omega| bls12_377::G1Affine::new(p.x * omega, p.y, p.infinity),
+        x.square(),
+        (bls12_377::Fr::one(), false),
+    )?;
+    Some(on_curve && in_subgroup)
+}
+
+/// ψ, the untwist-Frobenius-twist endomorphism, acts on G2 as multiplication by p,
+/// which is x modulo r, and ψ(P) = [x]P only holds on G2 (Scott)
+#[cfg(feature = "bls12_377")]
+pub(crate) fn check_bls12_377_g2(elements: &[&bls12_377::G2Affine]) -> Option<bool> {
+    use bls12_377::{Fq12, Fq2, Fq6};
+
+    // Fq12 = Fq6[w] / (w^2 - v), so ψ(x, y) = (x^p * (w^2)^p / w^2, y^p * (w^3)^p / w^3)
+    // for a D-type twist, and the inverse coefficients for an M-type twist
+    let v = Fq6::new(Fq2::zero(), Fq2::one(), Fq2::zero());
+    let mut v_p = v;
+    v_p.frobenius_map(1);
+    let c_x = (v_p * &v.inverse()?).c0;
+    let vw = Fq12::new(Fq6::zero(), v);
+    let mut vw_p = vw;
+    vw_p.frobenius_map(1);
+    let c_y = (vw_p * &vw.inverse()?).c0.c0;
+
+    let on_curve = cfg_iter!(elements).all(|p| p.is_on_curve());
+    let in_subgroup = check_with_candidates(
+        elements,
+        &[(c_x, c_y), (c_x.inverse()?, c_y.inverse()?)],
+        |p, (c_x, c_y)| {
+            let (mut x, mut y) = (p.x, p.y);
+            x.frobenius_map(1);
+            y.frobenius_map(1);
+            bls12_377::G2Affine::new(x * c_x, y * c_y, p.infinity)
+        },
+        bls12_377::Fr::from(X),
+        (bls12_377::Fr::one(), true),
+    )?;
+    Some(on_curve && in_subgroup)
+}
+
+/// The short vector (a, b) for the subgroup order r of BW6-761, i.e. the base field
+/// modulus of BLS12-377, with a + bλ = 0 mod r and a^2 - ab + b^2 = r
+#[cfg(feature = "bw6_761")]
+fn bw6_761_short_vector() -> (bw6_761::Fr, (bw6_761::Fr, bool)) {
+    let x = bw6_761::Fr::from(X);
+    // (x - 1) / 3
+    let c = bw6_761::Fr::from((X - 1) / 3);
+    let c_x2 = c * &(x.square() + &bw6_761::Fr::one());
+    (c_x2 + &bw6_761::Fr::one(), (c_x2 - &x, true))
+}
+
+/// Both groups of BW6-761 have j-invariant 0, so φ(x, y) = (ωx, y) acts on them as a
+/// cube root of unity λ modulo r. a + bφ has degree a^2 - ab + b^2 = r, so its kernel is the
+/// subgroup when a + bλ = 0 mod r
+#[cfg(feature = "bw6_761")]
+pub(crate) fn check_bw6_761_g1(elements: &[&bw6_761::G1Affine]) -> Option<bool> {
+    let (a, b) = bw6_761_short_vector();
+    let on_curve = cfg_iter!(elements).all(|p| p.is_on_curve());
+    let in_subgroup = check_with_candidates(
+        elements,
+        &cube_roots_of_unity::<bw6_761::Fq>()?,
+        |p, omega| bw6_761::G1Affine::new(p.x * omega, p.y, p.infinity),
+        a,
+        b,
+    )?;
+    Some(on_curve && in_subgroup)
+}
+
+#[cfg(feature = "bw6_761")]
+pub(crate) fn check_bw6_761_g2(elements: &[&bw6_761::G2Affine]) -> Option<bool> {
+    let (a, b) = bw6_761_short_vector();
+    let on_curve = cfg_iter!(elements).all(|p| p.is_on_curve());
+    let in_subgroup = check_with_candidates(
+        elements,
+        &cube_roots_of_unity::<bw6_761::Fq>()?,
+        |p, omega| bw6_761::G2Affine::new(p.x * omega, p.y, p.infinity),
+        a,
+        b,
+    )?;
+    Some(on_curve && in_subgroup)
+}
+
+/// The two primitive cube roots of unity of the field, (-1 ± sqrt(-3)) / 2
+fn cube_roots_of_unity<F: SquareRootField>() -> Option<[F; 2]> {
+    let three = F::one().double() + &F::one();
+    let sqrt = (-three).sqrt()?;
+    let omega = (sqrt - &F::one()) * &F::one().double().inverse()?;
+    Some([omega, -omega - &F::one()])
+}
+
+/// Only one of the candidate endomorphisms acts on the subgroup with the eigenvalue
+/// expected by the check, which is the one the generator passes the check with.
+/// Returns `None` if there is no such candidate.
+fn check_with_candidates<C: AffineCurve, T: Sync>(
+    elements: &[&C],
+    candidates: &[T],
+    endomorphism: impl Fn(&C, &T) -> C + Sync,
+    a: C::ScalarField,
+    b: (C::ScalarField, bool),
+) -> Option<bool> {
+    let generator = C::prime_subgroup_generator();
+    let candidate = candidates
+        .iter()
+        .find(|&candidate| check(&[&generator], |p| endomorphism(p, candidate), a, b))?;
+    Some(check(elements, |p| endomorphism(p, candidate), a, b))
+}
+
+/// Checks that [a]P + [b]φ(P) is zero for each element P, where b may be negative
+fn check<C: AffineCurve>(
+    elements: &[&C],
+    endomorphism: impl Fn(&C) -> C + Sync,
+    a: C::ScalarField,
+    (b, b_is_negative): (C::ScalarField, bool),
+) -> bool {
+    let (a, b) = (a.into_repr(), b.into_repr());
+    cfg_iter!(elements).all(|&p| {
+        let phi_p = endomorphism(p);
+        let phi_p = if b_is_negative { -phi_p } else { phi_p };
+        double_scalar_mul(p, &a, &phi_p, &b).is_zero()
+    })
+}
+
+/// Computes [a]P + [b]Q with a single chain of doublings, adding P, Q or P + Q
+/// for each pair of bits of the scalars (Straus-Shamir's trick)
+fn double_scalar_mul<C: AffineCurve>(
+    p: &C,
+    a: &<C::ScalarField as PrimeField>::BigInt,
+    q: &C,
+    b: &<C::ScalarField as PrimeField>::BigInt,
+) -> C::Projective {
+    let mut p_plus_q = p.into_projective();
+    p_plus_q.add_assign_mixed(q);
+    let num_bits = std::cmp::max(a.num_bits(), b.num_bits()) as usize;
+    let mut result = C::Projective::zero();
+    for i in (0..num_bits).rev() {
+        result.double_in_place();
+        match (a.get_bit(i), b.get_bit(i)) {
+            (true, true) => result += &p_plus_q,
+            (true, false) => result.add_assign_mixed(p),
+            (false, true) => result.add_assign_mixed(q),
+            (false, false) => {}
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{check_subgroup, curves::check_subgroup_endomorphism, SubgroupCheckMode};
+
+    use algebra::{FpParameters, UniformRand};
+    use rand::{thread_rng, Rng};
+
+    #[cfg(feature = "bls12_377")]
+    #[test]
+    fn endomorphism_bls12_377() {
+        endomorphism_curve::<bls12_377::G1Affine>();
+        endomorphism_curve::<bls12_377::G2Affine>();
+    }
+
+    #[cfg(feature = "bw6_761")]
+    #[test]
+    fn endomorphism_bw6_761() {
+        endomorphism_curve::<bw6_761::G1Affine>();
+        endomorphism_curve::<bw6_761::G2Affine>();
+    }
+
+    #[test]
+    fn double_scalar_mul_matches_scalar_muls() {
+        use algebra::bls12_377::{Fr, G1Projective};
+        let mut rng = thread_rng();
+        let p = G1Projective::rand(&mut rng).into_affine();
+        let q = G1Projective::rand(&mut rng).into_affine();
+        let (a, b) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        assert_eq!(
+            double_scalar_mul(&p, &a.into_repr(), &q, &b.into_repr()),
+            p.mul(a) + &q.mul(b)
+        );
+        assert!(double_scalar_mul(&p, &Fr::zero().into_repr(), &q, &Fr::zero().into_repr()).is_zero());
+    }
+
+    fn endomorphism_curve<C: AffineCurve>() {
+        let mut rng = thread_rng();
+        let mut elements = (0..10)
+            .map(|_| C::Projective::rand(&mut rng).into_affine())
+            .collect::<Vec<_>>();
+        elements.push(C::zero());
+        assert_eq!(check_subgroup_endomorphism(&elements), Some(true));
+        assert!(check_subgroup(&elements, SubgroupCheckMode::Direct).is_ok());
+        assert!(check_subgroup(&elements, SubgroupCheckMode::Endomorphism).is_ok());
+
+        // points of the curve which are outside the prime order subgroup
+        let mut outside = 0;
+        while outside < 10 {
+            let bytes = (0..C::SERIALIZED_SIZE).map(|_| rng.gen()).collect::<Vec<u8>>();
+            if let Some(p) = C::from_random_bytes(&bytes) {
+                let in_subgroup = p
+                    .mul(<<C::ScalarField as PrimeField>::Params as FpParameters>::MODULUS)
+                    .is_zero();
+                assert_eq!(check_subgroup_endomorphism(&[p]), Some(in_subgroup));
+                assert_eq!(
+                    check_subgroup(&[p], SubgroupCheckMode::Direct).is_ok(),
+                    check_subgroup(&[p], SubgroupCheckMode::Endomorphism).is_ok()
+                );
+                if !in_subgroup {
+                    let mut with_outside = elements.clone();
+                    with_outside.insert(rng.gen_range(0, elements.len()), p);
+                    assert_eq!(check_subgroup_endomorphism(&with_outside), Some(false));
+                    outside += 1;
+                }
+            }
+        }
+    }
+}
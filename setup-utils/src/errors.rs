@@ -35,6 +35,16 @@ pub enum Error {
     InvalidDecompressionParametersError,
     #[error("Batch was too small to support ratio checks")]
     BatchTooSmall,
+    #[error("Invalid circuit file: {0}")]
+    InvalidCircuitFile(String),
+    #[error("Invalid witness file: {0}")]
+    InvalidWitnessFile(String),
+    #[error("Invalid memory-mappable parameters file: {0}")]
+    InvalidParamsFile(String),
+    #[error("Invalid prepared phase 1 file: {0}")]
+    InvalidPhase1File(String),
+    #[error("The constraint system has no matrices, it must be synthesized in setup mode")]
+    MissingConstraintMatrices,
 }
 
 impl From<Box<dyn std::any::Any + Send>> for Error {
@@ -53,6 +63,8 @@ pub enum Phase2Error {
     NoContributions,
     #[error("The Transcript was not consistent")]
     InvalidTranscript,
+    #[error("The transcript header is invalid or of an unsupported version")]
+    InvalidTranscriptHeader,
 }
 
 #[derive(PartialEq, Debug, Clone)]
@@ -69,6 +81,8 @@ pub enum InvariantKind {
     AlphaG1Query,
     BetaG1Query,
     BetaG2Query,
+    TranscriptHeader,
+    TranscriptHash,
 }
 
 use std::fmt;
@@ -87,6 +101,8 @@ impl fmt::Display for InvariantKind {
             InvariantKind::AlphaG1Query => write!(f, "AlphaG1Query"),
             InvariantKind::BetaG1Query => write!(f, "BetaG1Query"),
             InvariantKind::BetaG2Query => write!(f, "BetaG2Query"),
+            InvariantKind::TranscriptHeader => write!(f, "TranscriptHeader"),
+            InvariantKind::TranscriptHash => write!(f, "TranscriptHash"),
         }
     }
 }
@@ -1,19 +1,30 @@
 /// Utilities to read/write and convert the Powers of Tau from Phase 1
 /// to Phase 2-compatible Lagrange Coefficients.
-use crate::{buffer_size, CheckForCorrectness, Deserializer, Result, Serializer, UseCompression};
+use crate::{
+    buffer_size, BatchSerializer, CheckForCorrectness, Deserializer, Error, Result, Serializer, UseCompression,
+};
 
-use algebra::{AffineCurve, PairingEngine, PrimeField, ProjectiveCurve};
+use algebra::{AffineCurve, FftField, FftParameters, Field, One, PairingEngine, PrimeField, ProjectiveCurve};
 use fft::{
-    cfg_into_iter, cfg_iter,
-    domain::{radix2::Radix2EvaluationDomain, EvaluationDomain},
+    cfg_chunks, cfg_into_iter, cfg_iter,
+    domain::{
+        general::GeneralEvaluationDomain, mixed_radix::MixedRadixEvaluationDomain, radix2::Radix2EvaluationDomain,
+        EvaluationDomain,
+    },
 };
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
-use std::{fmt::Debug, io::Write};
+use std::{
+    cmp::{max, min},
+    fmt::Debug,
+    io::Write,
+    marker::PhantomData,
+    ops::Range,
+};
 use tracing::{debug, info, info_span};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Groth16Params<E: PairingEngine> {
     pub alpha_g1: E::G1Affine,
     pub beta_g1: E::G1Affine,
@@ -38,6 +49,62 @@ impl<E: PairingEngine> PartialEq for Groth16Params<E> {
     }
 }
 
+/// Whether the Phase 2 parameters of a circuit may use a mixed-radix domain
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum UseMixedRadix {
+    Yes,
+    No,
+}
+
+/// Returns the evaluation domain of the Phase 2 parameters of a circuit whose
+/// QAP has `size` coefficients. By default, this is the domain Zexe's prover
+/// evaluates the QAP over, `GeneralEvaluationDomain::new(size)`, i.e. the
+/// smallest radix-2 domain which fits them. With `UseMixedRadix::Yes`, a
+/// smaller mixed-radix domain is used if the scalar field supports one (e.g.
+/// for BW6-761, with domains of size `2^k * 3^j`), in which case proofs must be
+/// created with a prover which evaluates the QAP over that domain, such as
+/// `phase2::prover`.
+pub fn evaluation_domain<F: FftField>(size: usize, mixed_radix: UseMixedRadix) -> Option<GeneralEvaluationDomain<F>> {
+    let general = GeneralEvaluationDomain::<F>::new(size);
+    if mixed_radix == UseMixedRadix::No || F::FftParams::SMALL_SUBGROUP_BASE.is_none() {
+        return general;
+    }
+    match (general, MixedRadixEvaluationDomain::<F>::new(size)) {
+        (Some(general), Some(mixed_radix)) if mixed_radix.size() < general.size() => {
+            Some(GeneralEvaluationDomain::MixedRadix(mixed_radix))
+        }
+        (general, mixed_radix) => general.or_else(|| mixed_radix.map(GeneralEvaluationDomain::MixedRadix)),
+    }
+}
+
+/// The size of `evaluation_domain(size, mixed_radix)`, i.e. the number of
+/// Lagrange coefficients of the Phase 2 parameters of a circuit with `size`
+/// coefficients
+///
+/// # Panics
+///
+/// If there is no domain with at least `size` elements
+pub fn domain_size<F: FftField>(size: usize, mixed_radix: UseMixedRadix) -> usize {
+    evaluation_domain::<F>(size, mixed_radix)
+        .expect("could not create domain")
+        .size()
+}
+
+/// Returns the domain of exactly `size` elements which `evaluation_domain` can
+/// return, i.e. the radix-2 domain if `size` is a power of two and the
+/// mixed-radix one otherwise. This recovers the domain of Phase 2 parameters
+/// from the length of their H query.
+pub fn domain_with_size<F: FftField>(size: usize) -> Option<GeneralEvaluationDomain<F>> {
+    let domain = if size.is_power_of_two() {
+        GeneralEvaluationDomain::<F>::new(size)
+    } else if F::FftParams::SMALL_SUBGROUP_BASE.is_some() {
+        MixedRadixEvaluationDomain::<F>::new(size).map(GeneralEvaluationDomain::MixedRadix)
+    } else {
+        None
+    };
+    domain.filter(|domain| domain.size() == size)
+}
+
 /// Performs an IFFT over the provided evaluation domain to the provided
 /// vector of affine points. It then normalizes and returns them back into
 /// affine form
@@ -56,22 +123,149 @@ where
 /// H query used in Groth16
 /// x^i * (x^m - 1) for i in 0..=(m-2) a.k.a.
 /// x^(i + m) - x^i for i in 0..=(m-2)
-/// for radix2 evaluation domains
+/// for radix2 and mixed-radix evaluation domains, whose vanishing polynomial
+/// is x^m - 1 for a domain of size m
 fn h_query_groth16<C: AffineCurve>(powers: &[C], degree: usize) -> Vec<C> {
     cfg_into_iter!(0..degree - 1)
         .map(|i| powers[i + degree] + powers[i].neg())
         .collect()
 }
 
+/// Splits a radix-2 domain of size `n` in `n1` columns of `n2` rows for the
+/// out-of-core IFFT, with `n1` and `n2` as close to `sqrt(n)` as possible
+fn split_domain(n: usize) -> (usize, usize) {
+    let n1 = 1 << ((n.trailing_zeros() + 1) / 2);
+    (n1, n / n1)
+}
+
+/// Out-of-core version of `to_coeffs`, which reads the `n` uncompressed points
+/// from `input` and writes their IFFT uncompressed to `output`.
+///
+/// With `n = n1 * n2`, the input is seen as `n2` rows of `n1` columns. An IFFT
+/// of size `n2` is performed over each column, whose `k2`-th element is then
+/// multiplied by `omega^(-j1 * k2)` and stored transposed in `scratch`. An IFFT
+/// of size `n1` over each row of `scratch` gives the coefficients `k2 + n2 * k1`.
+/// Only the columns or rows spanning about `max_points` points are held in
+/// memory at a time.
+fn to_coeffs_out_of_core<F, C>(
+    n: usize,
+    input: &[u8],
+    check_input_for_correctness: CheckForCorrectness,
+    scratch: &mut [u8],
+    output: &mut [u8],
+    max_points: usize,
+) -> Result<()>
+where
+    F: PrimeField,
+    C: AffineCurve,
+    C::Projective: std::ops::MulAssign<F>,
+{
+    let (n1, n2) = split_domain(n);
+    let size = buffer_size::<C>(UseCompression::No);
+    let omega_inv = Radix2EvaluationDomain::<F>::new(n)
+        .expect("could not create domain")
+        .group_gen_inv;
+    let column_domain = Radix2EvaluationDomain::<F>::new(n2).expect("could not create domain");
+    let row_domain = Radix2EvaluationDomain::<F>::new(n1).expect("could not create domain");
+
+    let columns_per_batch = max(1, max_points / n2);
+    for start in (0..n1).step_by(columns_per_batch) {
+        let end = min(start + columns_per_batch, n1);
+        let mut columns = (start..end).map(|_| Vec::with_capacity(n2)).collect::<Vec<_>>();
+        for j2 in 0..n2 {
+            let row = input[(n1 * j2 + start) * size..(n1 * j2 + end) * size]
+                .read_batch::<C>(UseCompression::No, check_input_for_correctness)?;
+            for (column, point) in columns.iter_mut().zip(row) {
+                column.push(point.into_projective());
+            }
+        }
+
+        let columns = cfg_into_iter!(columns)
+            .enumerate()
+            .map(|(i, column)| {
+                let mut column = column_domain.ifft(&column);
+                let twiddle = omega_inv.pow([(start + i) as u64]);
+                let mut factor = F::one();
+                for point in column.iter_mut() {
+                    *point *= factor;
+                    factor *= &twiddle;
+                }
+                C::Projective::batch_normalization(&mut column);
+                column.iter().map(|p| p.into_affine()).collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        for k2 in 0..n2 {
+            let row = columns.iter().map(|column| column[k2]).collect::<Vec<_>>();
+            scratch[(n1 * k2 + start) * size..(n1 * k2 + end) * size].write_batch(&row, UseCompression::No)?;
+        }
+    }
+
+    let rows_per_batch = max(1, max_points / n1);
+    for start in (0..n2).step_by(rows_per_batch) {
+        let end = min(start + rows_per_batch, n2);
+        let points =
+            scratch[n1 * start * size..n1 * end * size].read_batch::<C>(UseCompression::No, CheckForCorrectness::No)?;
+        let rows = cfg_chunks!(points, n1)
+            .map(|row| {
+                let mut row = row_domain.ifft(&row.iter().map(|p| p.into_projective()).collect::<Vec<_>>());
+                C::Projective::batch_normalization(&mut row);
+                row.iter().map(|p| p.into_affine()).collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        for k1 in 0..n1 {
+            let column = rows.iter().map(|row| row[k1]).collect::<Vec<_>>();
+            output[(n2 * k1 + start) * size..(n2 * k1 + end) * size].write_batch(&column, UseCompression::No)?;
+        }
+    }
+    Ok(())
+}
+
+/// Out-of-core version of `h_query_groth16`, which reads the uncompressed
+/// powers and writes the H query uncompressed to `output`, `max_points` at a time
+fn h_query_groth16_out_of_core<C: AffineCurve>(
+    degree: usize,
+    powers: &[u8],
+    check_input_for_correctness: CheckForCorrectness,
+    output: &mut [u8],
+    max_points: usize,
+) -> Result<()> {
+    let size = buffer_size::<C>(UseCompression::No);
+    let points_per_batch = max(1, max_points / 2);
+    for start in (0..degree - 1).step_by(points_per_batch) {
+        let end = min(start + points_per_batch, degree - 1);
+        let low = powers[start * size..end * size].read_batch::<C>(UseCompression::No, check_input_for_correctness)?;
+        let high = powers[(start + degree) * size..(end + degree) * size]
+            .read_batch::<C>(UseCompression::No, check_input_for_correctness)?;
+        let h = cfg_into_iter!(0..end - start)
+            .map(|i| high[i] + low[i].neg())
+            .collect::<Vec<_>>();
+        output[start * size..end * size].write_batch(&h, UseCompression::No)?;
+    }
+    Ok(())
+}
+
+/// The uncompressed Powers of Tau of a Groth16 phase 1 accumulator, as split
+/// from its buffer
+pub struct PowersOfTauBuffers<'a> {
+    pub tau_g1: &'a [u8],
+    pub tau_g2: &'a [u8],
+    pub alpha_g1: &'a [u8],
+    pub beta_g1: &'a [u8],
+    pub beta_g2: &'a [u8],
+}
+
 impl<E: PairingEngine> Groth16Params<E> {
     /// Loads the Powers of Tau and transforms them to coefficient form
-    /// in preparation of Phase 2
+    /// in preparation of Phase 2, over `evaluation_domain(phase2_size, mixed_radix)`
     ///
     /// # Panics
     ///
-    /// If `phase2_size` > length of any of the provided vectors
+    /// If the size of the domain > length of any of the provided vectors
     pub fn new(
         phase2_size: usize,
+        mixed_radix: UseMixedRadix,
         tau_powers_g1: Vec<E::G1Affine>,
         tau_powers_g2: Vec<E::G2Affine>,
         alpha_tau_powers_g1: Vec<E::G1Affine>,
@@ -82,7 +276,8 @@ impl<E: PairingEngine> Groth16Params<E> {
         let _enter = span.enter();
 
         // Create the evaluation domain
-        let domain = Radix2EvaluationDomain::<E::Fr>::new(phase2_size).expect("could not create domain");
+        let domain = evaluation_domain::<E::Fr>(phase2_size, mixed_radix).expect("could not create domain");
+        let phase2_size = domain.size();
 
         info!("converting powers of tau to lagrange coefficients");
 
@@ -121,6 +316,130 @@ impl<E: PairingEngine> Groth16Params<E> {
         })??)
     }
 
+    /// The size of the parameters for `phase2_size` once written with `write`
+    pub fn serialized_size(phase2_size: usize, compression: UseCompression) -> usize {
+        let g1_size = buffer_size::<E::G1Affine>(compression);
+        let g2_size = buffer_size::<E::G2Affine>(compression);
+        (4 * phase2_size + 1) * g1_size + (phase2_size + 1) * g2_size
+    }
+
+    /// The size of the domain the parameters were prepared for, recovered from
+    /// the length of their serialization, i.e. the inverse of `serialized_size`.
+    /// The domain is a radix-2 one if this is a power of two and a mixed-radix
+    /// one otherwise, see `domain_with_size`. Returns `None` if no domain has
+    /// parameters of that length.
+    pub fn prepared_domain_size(serialized_size: usize, compression: UseCompression) -> Option<usize> {
+        let g1_size = buffer_size::<E::G1Affine>(compression);
+        let g2_size = buffer_size::<E::G2Affine>(compression);
+        let coefficients = serialized_size.checked_sub(g1_size + g2_size)?;
+        if coefficients % (4 * g1_size + g2_size) != 0 {
+            return None;
+        }
+        Some(coefficients / (4 * g1_size + g2_size))
+    }
+
+    /// Transforms the Powers of Tau to coefficient form like `new` and writes
+    /// them uncompressed to `output` like `write`, without loading them in
+    /// memory. The powers are read from the buffers, typically a memory map of
+    /// the phase 1 response, and the transformations run one after the other
+    /// on batches of points which take about `memory_budget` bytes, with their
+    /// intermediate results in `scratch`.
+    ///
+    /// # Panics
+    ///
+    /// If `phase2_size` is not a power of two, if it is larger than the Powers
+    /// of Tau, if `scratch` cannot hold `phase2_size` uncompressed G2 elements
+    /// or if `output` is smaller than `serialized_size`.
+    pub fn write_out_of_core(
+        phase2_size: usize,
+        powers: &PowersOfTauBuffers,
+        check_input_for_correctness: CheckForCorrectness,
+        memory_budget: usize,
+        scratch: &mut [u8],
+        output: &mut [u8],
+    ) -> Result<()> {
+        let span = info_span!("Groth16Utils_write_out_of_core");
+        let _enter = span.enter();
+
+        assert!(
+            phase2_size.is_power_of_two(),
+            "the phase 2 size should be a power of two"
+        );
+        let compression = UseCompression::No;
+        let g1_size = buffer_size::<E::G1Affine>(compression);
+        let g2_size = buffer_size::<E::G2Affine>(compression);
+        let max_g1_points = max(1, memory_budget / std::mem::size_of::<E::G1Projective>());
+        let max_g2_points = max(1, memory_budget / std::mem::size_of::<E::G2Projective>());
+
+        let alpha_g1: E::G1Affine =
+            (&powers.alpha_g1[..g1_size]).read_element(compression, check_input_for_correctness)?;
+        let beta_g1: E::G1Affine =
+            (&powers.beta_g1[..g1_size]).read_element(compression, check_input_for_correctness)?;
+        let beta_g2: E::G2Affine =
+            (&powers.beta_g2[..g2_size]).read_element(compression, check_input_for_correctness)?;
+
+        let (header, output) = output.split_at_mut(2 * g1_size + g2_size);
+        let (coeffs_g1, output) = output.split_at_mut(phase2_size * g1_size);
+        let (coeffs_g2, output) = output.split_at_mut(phase2_size * g2_size);
+        let (alpha_coeffs_g1, output) = output.split_at_mut(phase2_size * g1_size);
+        let (beta_coeffs_g1, output) = output.split_at_mut(phase2_size * g1_size);
+        let h_g1 = &mut output[..(phase2_size - 1) * g1_size];
+
+        header[..g1_size].write_element(&alpha_g1, compression)?;
+        header[g1_size..2 * g1_size].write_element(&beta_g1, compression)?;
+        header[2 * g1_size..].write_element(&beta_g2, compression)?;
+
+        info!("converting powers of tau to lagrange coefficients out of core");
+
+        to_coeffs_out_of_core::<E::Fr, E::G1Affine>(
+            phase2_size,
+            &powers.tau_g1[..phase2_size * g1_size],
+            check_input_for_correctness,
+            scratch,
+            coeffs_g1,
+            max_g1_points,
+        )?;
+        debug!("tau g1 coefficients calculated");
+        to_coeffs_out_of_core::<E::Fr, E::G2Affine>(
+            phase2_size,
+            &powers.tau_g2[..phase2_size * g2_size],
+            check_input_for_correctness,
+            scratch,
+            coeffs_g2,
+            max_g2_points,
+        )?;
+        debug!("tau g2 coefficients calculated");
+        to_coeffs_out_of_core::<E::Fr, E::G1Affine>(
+            phase2_size,
+            &powers.alpha_g1[..phase2_size * g1_size],
+            check_input_for_correctness,
+            scratch,
+            alpha_coeffs_g1,
+            max_g1_points,
+        )?;
+        debug!("alpha tau g1 coefficients calculated");
+        to_coeffs_out_of_core::<E::Fr, E::G1Affine>(
+            phase2_size,
+            &powers.beta_g1[..phase2_size * g1_size],
+            check_input_for_correctness,
+            scratch,
+            beta_coeffs_g1,
+            max_g1_points,
+        )?;
+        debug!("beta tau g1 coefficients calculated");
+        h_query_groth16_out_of_core::<E::G1Affine>(
+            phase2_size,
+            powers.tau_g1,
+            check_input_for_correctness,
+            h_g1,
+            max_g1_points,
+        )?;
+        debug!("h query coefficients calculated");
+
+        info!("successfully wrote groth16 parameters from powers of tau");
+        Ok(())
+    }
+
     /// Writes the data structure to the provided writer, in compressed or uncompressed form.
     pub fn write<W: Write>(&self, writer: &mut W, compression: UseCompression) -> Result<()> {
         // Write alpha (in g1)
@@ -171,32 +490,121 @@ impl<E: PairingEngine> Groth16Params<E> {
         let span = info_span!("Groth16Utils_read");
         let _enter = span.enter();
 
-        let mut reader = std::io::Cursor::new(reader);
-        let alpha_g1 = reader.read_element(compressed, check_input_for_correctness)?;
-        let beta_g1 = reader.read_element(compressed, check_input_for_correctness)?;
-        let beta_g2 = reader.read_element(compressed, check_input_for_correctness)?;
+        Groth16ParamsView::<E>::new(
+            reader,
+            compressed,
+            check_input_for_correctness,
+            phase1_size,
+            num_constraints,
+        )?
+        .decode()
+    }
+}
 
-        let position = reader.position() as usize;
-        let reader = &mut &reader.get_mut()[position..];
+/// Points of a section of a buffer, which are only decoded when accessed
+pub struct LazyPoints<'a, C: AffineCurve> {
+    bytes: &'a [u8],
+    compressed: UseCompression,
+    check_input_for_correctness: CheckForCorrectness,
+    _curve: PhantomData<C>,
+}
+
+impl<'a, C: AffineCurve> LazyPoints<'a, C> {
+    pub fn new(bytes: &'a [u8], compressed: UseCompression, check_input_for_correctness: CheckForCorrectness) -> Self {
+        LazyPoints {
+            bytes,
+            compressed,
+            check_input_for_correctness,
+            _curve: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len() / buffer_size::<C>(self.compressed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Decodes the point at `index`
+    pub fn get(&self, index: usize) -> Result<C> {
+        let size = buffer_size::<C>(self.compressed);
+        (&self.bytes[index * size..(index + 1) * size]).read_element(self.compressed, self.check_input_for_correctness)
+    }
+
+    /// Decodes the points in `range` in parallel
+    pub fn read(&self, range: Range<usize>) -> Result<Vec<C>> {
+        let size = buffer_size::<C>(self.compressed);
+        self.bytes[range.start * size..range.end * size].read_batch(self.compressed, self.check_input_for_correctness)
+    }
+
+    /// Decodes all the points in parallel
+    pub fn read_all(&self) -> Result<Vec<C>> {
+        self.read(0..self.len())
+    }
+}
+
+/// A lazy view of a processed Phase 1 transcript, which keeps the sections of
+/// the buffer, typically memory mapped, and only decodes their points when
+/// accessed. This lets Phase 2 decode one section at a time, or a part of a
+/// section, instead of holding all of them like `Groth16Params`.
+pub struct Groth16ParamsView<'a, E: PairingEngine> {
+    pub alpha_g1: E::G1Affine,
+    pub beta_g1: E::G1Affine,
+    pub beta_g2: E::G2Affine,
+    pub coeffs_g1: LazyPoints<'a, E::G1Affine>,
+    pub coeffs_g2: LazyPoints<'a, E::G2Affine>,
+    pub alpha_coeffs_g1: LazyPoints<'a, E::G1Affine>,
+    pub beta_coeffs_g1: LazyPoints<'a, E::G1Affine>,
+    pub h_g1: LazyPoints<'a, E::G1Affine>,
+}
+
+impl<'a, E: PairingEngine> Groth16ParamsView<'a, E> {
+    /// Views the first `num_constraints` coefficients of the provided processed
+    /// Phase 1 transcript with size `phase1_size`. Only alpha and beta are decoded.
+    /// The transcript is rejected if its length does not match `phase1_size`, or
+    /// if it was prepared for a mixed-radix domain other than the circuit's.
+    pub fn new(
+        reader: &'a [u8],
+        compressed: UseCompression,
+        check_input_for_correctness: CheckForCorrectness,
+        phase1_size: usize,
+        num_constraints: usize,
+    ) -> Result<Self> {
+        let mut header = reader;
+        let alpha_g1 = (&mut header).read_element(compressed, check_input_for_correctness)?;
+        let beta_g1 = (&mut header).read_element(compressed, check_input_for_correctness)?;
+        let beta_g2 = (&mut header).read_element(compressed, check_input_for_correctness)?;
 
         // Split the transcript in the appropriate sections
         let (in_coeffs_g1, in_coeffs_g2, in_alpha_coeffs_g1, in_beta_coeffs_g1, in_h_g1) =
-            split_transcript::<E>(reader, phase1_size, num_constraints, compressed);
+            split_transcript::<E>(header, phase1_size, num_constraints, compressed)?;
+
+        Ok(Groth16ParamsView {
+            alpha_g1,
+            beta_g1,
+            beta_g2,
+            coeffs_g1: LazyPoints::new(in_coeffs_g1, compressed, check_input_for_correctness),
+            coeffs_g2: LazyPoints::new(in_coeffs_g2, compressed, check_input_for_correctness),
+            alpha_coeffs_g1: LazyPoints::new(in_alpha_coeffs_g1, compressed, check_input_for_correctness),
+            beta_coeffs_g1: LazyPoints::new(in_beta_coeffs_g1, compressed, check_input_for_correctness),
+            h_g1: LazyPoints::new(in_h_g1, compressed, check_input_for_correctness),
+        })
+    }
 
+    /// Decodes all the sections at once
+    pub fn decode(&self) -> Result<Groth16Params<E>> {
         info!("reading groth16 parameters...");
         // Read all elements in parallel
         // note: '??' is used for getting the result from the threaded operation,
         // and then getting the result from the function inside the thread)
         Ok(crossbeam::scope(|s| -> Result<_> {
-            let coeffs_g1 =
-                s.spawn(|_| in_coeffs_g1.read_batch::<E::G1Affine>(compressed, check_input_for_correctness));
-            let coeffs_g2 =
-                s.spawn(|_| in_coeffs_g2.read_batch::<E::G2Affine>(compressed, check_input_for_correctness));
-            let alpha_coeffs_g1 =
-                s.spawn(|_| in_alpha_coeffs_g1.read_batch::<E::G1Affine>(compressed, check_input_for_correctness));
-            let beta_coeffs_g1 =
-                s.spawn(|_| in_beta_coeffs_g1.read_batch::<E::G1Affine>(compressed, check_input_for_correctness));
-            let h_g1 = s.spawn(|_| in_h_g1.read_batch::<E::G1Affine>(compressed, check_input_for_correctness));
+            let coeffs_g1 = s.spawn(|_| self.coeffs_g1.read_all());
+            let coeffs_g2 = s.spawn(|_| self.coeffs_g2.read_all());
+            let alpha_coeffs_g1 = s.spawn(|_| self.alpha_coeffs_g1.read_all());
+            let beta_coeffs_g1 = s.spawn(|_| self.beta_coeffs_g1.read_all());
+            let h_g1 = s.spawn(|_| self.h_g1.read_all());
 
             let coeffs_g1 = coeffs_g1.join()??;
             debug!("read tau g1 Coefficients");
@@ -212,9 +620,9 @@ impl<E: PairingEngine> Groth16Params<E> {
             info!("successfully read groth16 parameters");
 
             Ok(Groth16Params {
-                alpha_g1,
-                beta_g1,
-                beta_g2,
+                alpha_g1: self.alpha_g1,
+                beta_g1: self.beta_g1,
+                beta_g2: self.beta_g2,
                 coeffs_g1,
                 coeffs_g2,
                 alpha_coeffs_g1,
@@ -230,15 +638,46 @@ type SplitBuf<'a> = (&'a [u8], &'a [u8], &'a [u8], &'a [u8], &'a [u8]);
 
 use crate::BatchDeserializer;
 
-/// splits the transcript from phase 1 after it's been prepared and converted to coefficient form
+/// splits the transcript from phase 1 after it's been prepared and converted to coefficient form.
+/// `phase1_size` is the size of the domain phase 1 was prepared for, which must match the length of
+/// the transcript. If it is a power of two, the coefficients are those of a radix-2 domain and the
+/// first `size` of them are used, with `size` rounded up to a power of two. Otherwise they are those
+/// of a mixed-radix domain, which is only used as a whole, so `size` must round up to `phase1_size`.
 fn split_transcript<E: PairingEngine>(
     input: &[u8],
     phase1_size: usize,
     size: usize,
     compressed: UseCompression,
-) -> SplitBuf {
+) -> Result<SplitBuf> {
     let g1_size = buffer_size::<E::G1Affine>(compressed);
     let g2_size = buffer_size::<E::G2Affine>(compressed);
+    // alpha, beta in G1 and beta in G2 have already been read
+    let expected = Groth16Params::<E>::serialized_size(phase1_size, compressed) - 2 * g1_size - g2_size;
+    if input.len() != expected {
+        return Err(Error::InvalidPhase1File(format!(
+            "it should have been prepared for a domain of size {}, which takes {} bytes, but it has {} bytes",
+            phase1_size,
+            expected,
+            input.len()
+        )));
+    }
+    let size = if phase1_size.is_power_of_two() {
+        domain_size::<E::Fr>(size, UseMixedRadix::No)
+    } else if domain_size::<E::Fr>(size, UseMixedRadix::Yes) == phase1_size {
+        phase1_size
+    } else {
+        return Err(Error::InvalidPhase1File(format!(
+            "it was prepared for a mixed-radix domain of size {}, which is not the domain of size {}",
+            phase1_size, size
+        )));
+    };
+    if size > phase1_size {
+        return Err(Error::InvalidPhase1File(format!(
+            "it was prepared for a domain of size {}, which is smaller than the domain of size {}",
+            phase1_size, size
+        )));
+    }
+
     // N elements per coefficient
     let (coeffs_g1, others) = input.split_at(g1_size * size);
     let (_, others) = others.split_at((phase1_size - size) * g1_size);
@@ -250,7 +689,7 @@ fn split_transcript<E: PairingEngine>(
     let (_, others) = others.split_at((phase1_size - size) * g1_size);
     // N-1 for the h coeffs
     let (h_coeffs, _) = others.split_at(g1_size * (size - 1));
-    (coeffs_g1, coeffs_g2, alpha_coeffs_g1, beta_coeffs_g1, h_coeffs)
+    Ok((coeffs_g1, coeffs_g2, alpha_coeffs_g1, beta_coeffs_g1, h_coeffs))
 }
 
 #[cfg(test)]
@@ -258,6 +697,7 @@ mod tests {
     use super::*;
     use crate::UseCompression;
     use phase1::{
+        helpers::buffers::split,
         helpers::testing::{
             setup_verify, BatchExpMode as BatchExpModePhase1, CheckForCorrectness as CheckForCorrectnessPhase1,
             UseCompression as UseCompressionPhase1,
@@ -265,7 +705,8 @@ mod tests {
         Phase1, Phase1Parameters, ProvingSystem,
     };
 
-    use algebra::Bls12_377;
+    use algebra::{Bls12_377, UniformRand, Zero, BW6_761};
+    use rand::thread_rng;
 
     fn read_write_curve<E: PairingEngine>(powers: usize, prepared_phase1_size: usize, compressed: UseCompression) {
         fn compat(compression: UseCompression) -> UseCompressionPhase1 {
@@ -303,6 +744,7 @@ mod tests {
 
         let groth_params = Groth16Params::<E>::new(
             prepared_phase1_size,
+            UseMixedRadix::No,
             accumulator.tau_powers_g1,
             accumulator.tau_powers_g2,
             accumulator.alpha_tau_powers_g1,
@@ -346,6 +788,90 @@ mod tests {
         );
         assert_eq!(&deserialized_subset.h_g1[..], &groth_params.h_g1[..subset - 1]);
         // h_query is 1 less element
+
+        // a file prepared for another domain is rejected
+        assert!(Groth16Params::<E>::read(
+            &mut reader.get_mut(),
+            compressed,
+            CheckForCorrectness::Full,
+            2 * prepared_phase1_size,
+            subset,
+        )
+        .is_err());
+    }
+
+    fn out_of_core_curve<E: PairingEngine>(powers: usize, phase2_size: usize, memory_budget: usize) {
+        let batch = ((1 << powers) << 1) - 1;
+        let params = Phase1Parameters::<E>::new_full(ProvingSystem::Groth16, powers, batch);
+        let (_, output, _, _) = setup_verify(
+            UseCompressionPhase1::No,
+            CheckForCorrectnessPhase1::Full,
+            UseCompressionPhase1::No,
+            BatchExpModePhase1::Auto,
+            &params,
+        );
+        let accumulator = Phase1::deserialize(
+            &output,
+            UseCompressionPhase1::No,
+            CheckForCorrectnessPhase1::Full,
+            &params,
+        )
+        .unwrap();
+
+        let groth_params = Groth16Params::<E>::new(
+            phase2_size,
+            UseMixedRadix::No,
+            accumulator.tau_powers_g1,
+            accumulator.tau_powers_g2,
+            accumulator.alpha_tau_powers_g1,
+            accumulator.beta_tau_powers_g1,
+            accumulator.beta_g2,
+        )
+        .unwrap();
+        let mut expected = vec![];
+        groth_params.write(&mut expected, UseCompression::No).unwrap();
+        assert_eq!(
+            expected.len(),
+            Groth16Params::<E>::serialized_size(phase2_size, UseCompression::No)
+        );
+        assert_eq!(
+            Groth16Params::<E>::prepared_domain_size(expected.len(), UseCompression::No),
+            Some(phase2_size)
+        );
+        assert_eq!(
+            Groth16Params::<E>::prepared_domain_size(expected.len() - 1, UseCompression::No),
+            None
+        );
+
+        let (tau_g1, tau_g2, alpha_g1, beta_g1, beta_g2) = split(&output, &params, UseCompressionPhase1::No);
+        let powers = PowersOfTauBuffers {
+            tau_g1,
+            tau_g2,
+            alpha_g1,
+            beta_g1,
+            beta_g2,
+        };
+        let mut scratch = vec![0; phase2_size * buffer_size::<E::G2Affine>(UseCompression::No)];
+        let mut written = vec![0; expected.len()];
+        Groth16Params::<E>::write_out_of_core(
+            phase2_size,
+            &powers,
+            CheckForCorrectness::Full,
+            memory_budget,
+            &mut scratch,
+            &mut written,
+        )
+        .unwrap();
+        assert!(written == expected);
+    }
+
+    #[test]
+    fn out_of_core_matches_in_memory() {
+        // one column or row at a time
+        out_of_core_curve::<Bls12_377>(4, 16, 1);
+        out_of_core_curve::<Bls12_377>(4, 8, 1);
+        // everything at once
+        out_of_core_curve::<Bls12_377>(3, 8, 1 << 20);
     }
 
     #[test]
@@ -375,4 +901,40 @@ mod tests {
     fn large_phase2_uncompressed_fails() {
         read_write_curve::<Bls12_377>(3, 9, UseCompression::No);
     }
+
+    #[test]
+    fn mixed_radix_domain() {
+        type Fr = <BW6_761 as PairingEngine>::Fr;
+        // a power of two is its own domain
+        assert_eq!(domain_size::<Fr>(16, UseMixedRadix::Yes), 16);
+        // mixed-radix domains are opt-in
+        assert_eq!(domain_size::<Fr>(17, UseMixedRadix::No), 32);
+        // BW6-761 supports domains of size 2^k * 3^j, which are smaller than the
+        // next power of two just above a power of two
+        let domain = evaluation_domain::<Fr>(17, UseMixedRadix::Yes).unwrap();
+        let size = domain.size();
+        assert!(size >= 17 && size < 32);
+        assert_eq!(domain_size::<Fr>(17, UseMixedRadix::Yes), size);
+        assert!(domain_with_size::<Fr>(size).unwrap().elements().eq(domain.elements()));
+        assert!(domain_with_size::<Fr>(size + 1).is_none());
+
+        // the Lagrange coefficients at tau sum to one and interpolate x at tau
+        let tau = Fr::rand(&mut thread_rng());
+        let g = <BW6_761 as PairingEngine>::G1Affine::prime_subgroup_generator();
+        let powers = (0..size)
+            .map(|i| g.mul(tau.pow([i as u64])).into_affine())
+            .collect::<Vec<_>>();
+        let coeffs = to_coeffs(&domain, &powers);
+        let sum = coeffs
+            .iter()
+            .fold(<BW6_761 as PairingEngine>::G1Projective::zero(), |sum, coeff| {
+                sum + coeff.into_projective()
+            });
+        assert_eq!(sum.into_affine(), g);
+        let interpolated = coeffs.iter().zip(domain.elements()).fold(
+            <BW6_761 as PairingEngine>::G1Projective::zero(),
+            |sum, (coeff, element)| sum + coeff.mul(element),
+        );
+        assert_eq!(interpolated.into_affine(), g.mul(tau).into_affine());
+    }
 }
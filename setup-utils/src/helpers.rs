@@ -7,7 +7,7 @@ use algebra::{
     AffineCurve, BatchGroupArithmeticSlice, BigInteger, CanonicalSerialize, ConstantSerializedSize, Field, One,
     PairingEngine, PrimeField, ProjectiveCurve, UniformRand, Zero,
 };
-use fft::{cfg_chunks_mut, cfg_into_iter, cfg_iter, cfg_iter_mut};
+use fft::{cfg_chunks_mut, cfg_iter, cfg_iter_mut};
 
 use blake2::{digest::generic_array::GenericArray, Blake2b, Digest};
 use rand::{rngs::OsRng, thread_rng, Rng, SeedableRng};
@@ -29,12 +29,31 @@ use crate::elements::BatchExpMode;
 use rayon::prelude::*;
 
 /// Generate the powers by raising the key's `tau` to all powers
-/// belonging to this chunk
+/// belonging to this chunk. The chunk is split in a block per thread, whose
+/// first power is computed by exponentiation and the rest by repeatedly
+/// multiplying by `tau`.
 pub fn generate_powers_of_tau<E: PairingEngine>(tau: &E::Fr, start: usize, end: usize) -> Vec<E::Fr> {
-    // Uh no better way to do this, this should never fail
-    let start: u64 = start.try_into().expect("could not convert to u64");
-    let end: u64 = end.try_into().expect("could not convert to u64");
-    cfg_into_iter!(start..end).map(|i| tau.pow([i])).collect()
+    if end <= start {
+        return vec![];
+    }
+    let mut powers = vec![E::Fr::zero(); end - start];
+
+    #[cfg(feature = "parallel")]
+    let num_threads = rayon::current_num_threads();
+    #[cfg(not(feature = "parallel"))]
+    let num_threads = 1;
+    let block_size = (powers.len() + num_threads - 1) / num_threads;
+
+    cfg_chunks_mut!(powers, block_size).enumerate().for_each(|(i, block)| {
+        // Uh no better way to do this, this should never fail
+        let block_start: u64 = (start + i * block_size).try_into().expect("could not convert to u64");
+        let mut acc = tau.pow([block_start]);
+        for power in block {
+            *power = acc;
+            acc *= tau;
+        }
+    });
+    powers
 }
 
 pub fn print_hash(hash: &[u8]) {
@@ -325,6 +344,31 @@ mod tests {
         );
     }
 
+    // the previous implementation, with an exponentiation per power
+    fn generate_powers_of_tau_pow<E: PairingEngine>(tau: &E::Fr, start: usize, end: usize) -> Vec<E::Fr> {
+        (start as u64..end as u64).map(|i| tau.pow([i])).collect()
+    }
+
+    #[test]
+    fn test_generate_powers_of_tau() {
+        let tau = Fr::rand(&mut thread_rng());
+        // empty, smaller than the number of threads, uneven and larger chunks
+        for &(start, end) in &[
+            (0, 0),
+            (5, 3),
+            (0, 1),
+            (3, 5),
+            (0, 7),
+            (10, 1033),
+            (1 << 16, (1 << 16) + 4096),
+        ] {
+            assert_eq!(
+                generate_powers_of_tau::<Bls12_381>(&tau, start, end),
+                generate_powers_of_tau_pow::<Bls12_381>(&tau, start, end)
+            );
+        }
+    }
+
     #[test]
     fn test_same_ratio() {
         let rng = &mut thread_rng();
@@ -339,6 +383,39 @@ mod tests {
         assert!(!same_ratio::<Bls12_381>(&(g1_s, g1), &(g2, g2_s)));
     }
 
+    #[test]
+    fn test_same_ratios_batched() {
+        let rng = &mut thread_rng();
+
+        let g1 = G1Affine::prime_subgroup_generator();
+        let g2 = G2Affine::prime_subgroup_generator();
+        let mut checks = (0..4)
+            .map(|_| {
+                let (s, t) = (Fr::rand(rng), Fr::rand(rng));
+                let g1_s = g1.mul(s).into_affine();
+                let g1_st = g1.mul(s * &t).into_affine();
+                let g2_t = g2.mul(t).into_affine();
+                ((g1_s, g1_st), (g2, g2_t), "valid")
+            })
+            .collect::<Vec<_>>();
+        assert!(check_same_ratios_batched::<Bls12_381>(&[]).is_ok());
+        assert!(check_same_ratios_batched::<Bls12_381>(&checks).is_ok());
+
+        // the error of the failing check is returned
+        let (g1_s, g1_st) = checks[2].0;
+        checks[2] = ((g1_st, g1_s), checks[2].1, "invalid");
+        assert_eq!(
+            check_same_ratios_batched::<Bls12_381>(&checks).unwrap_err().to_string(),
+            check_same_ratio::<Bls12_381>(&checks[2].0, &checks[2].1, "invalid")
+                .unwrap_err()
+                .to_string()
+        );
+
+        // zero elements fail the check
+        checks[2] = ((G1Affine::zero(), G1Affine::zero()), (g2, g2), "zero");
+        assert!(check_same_ratios_batched::<Bls12_381>(&checks).is_err());
+    }
+
     #[test]
     fn test_power_pairs() {
         use std::ops::MulAssign;
@@ -419,6 +496,48 @@ pub fn check_same_ratio<E: PairingEngine>(
     Ok(())
 }
 
+/// Runs `check_same_ratio` on each of the `checks` with a single multi-Miller loop
+/// and final exponentiation, by checking that the product of
+/// e(ρ_i * g1_i.0, g2_i.1) * e(-ρ_i * g1_i.1, g2_i.0) for random scalars ρ_i is one.
+/// If it is not, the checks are run one by one to return the error of the first failing check.
+pub fn check_same_ratios_batched<E: PairingEngine>(
+    checks: &[((E::G1Affine, E::G1Affine), (E::G2Affine, E::G2Affine), &'static str)],
+) -> Result<()> {
+    if checks
+        .iter()
+        .any(|(g1, g2, _)| g1.0.is_zero() || g1.1.is_zero() || g2.0.is_zero() || g2.1.is_zero())
+    {
+        return check_same_ratios_individually::<E>(checks);
+    }
+
+    let rng = &mut thread_rng();
+    let pairs = checks
+        .iter()
+        .flat_map(|(g1, g2, _)| {
+            // the random scalars prevent failing checks from cancelling each other out
+            let rho = E::Fr::rand(rng);
+            vec![
+                (g1.0.mul(rho).into_affine().into(), g2.1.into()),
+                ((-g1.1.mul(rho)).into_affine().into(), g2.0.into()),
+            ]
+        })
+        .collect::<Vec<(E::G1Prepared, E::G2Prepared)>>();
+
+    if E::final_exponentiation(&E::miller_loop(&pairs)) != Some(E::Fqk::one()) {
+        return check_same_ratios_individually::<E>(checks);
+    }
+    Ok(())
+}
+
+fn check_same_ratios_individually<E: PairingEngine>(
+    checks: &[((E::G1Affine, E::G1Affine), (E::G2Affine, E::G2Affine), &'static str)],
+) -> Result<()> {
+    for (g1, g2, err) in checks {
+        check_same_ratio::<E>(g1, g2, *err)?;
+    }
+    Ok(())
+}
+
 /// Compute BLAKE2b(personalization | transcript | g^s | g^{s*x})
 /// and then hash it to G2
 pub fn compute_g2_s<E: PairingEngine>(
@@ -21,10 +21,13 @@ mod tests {
     use super::*;
     use phase1::helpers::testing::random_point_vec;
 
-    use algebra::bls12_377::{G1Affine, G2Affine};
+    use algebra::{
+        bls12_377::{G1Affine, G2Affine},
+        FpParameters, PrimeField, Zero,
+    };
 
-    use crate::CheckForCorrectness;
-    use rand::thread_rng;
+    use crate::{CheckForCorrectness, SubgroupCheckMode};
+    use rand::{thread_rng, Rng};
 
     #[test]
     fn read_write_single() {
@@ -58,6 +61,14 @@ mod tests {
         read_write_batch_element_preallocated::<G2Affine>(UseCompression::Yes);
     }
 
+    #[test]
+    fn read_batch_subgroup_check() {
+        read_batch_subgroup_check_element::<G1Affine>(UseCompression::No);
+        read_batch_subgroup_check_element::<G1Affine>(UseCompression::Yes);
+        read_batch_subgroup_check_element::<G2Affine>(UseCompression::No);
+        read_batch_subgroup_check_element::<G2Affine>(UseCompression::Yes);
+    }
+
     fn read_write_single_element<E: AffineCurve>(compression: UseCompression) {
         // uncompressed buffers are twice the size
         let el = E::prime_subgroup_generator();
@@ -114,4 +125,56 @@ mod tests {
         assert_eq!(elements, prealloc);
         assert_eq!(elements, prealloc2);
     }
+
+    fn read_batch_subgroup_check_element<E: AffineCurve>(compression: UseCompression) {
+        let num_els = 10;
+        let mut rng = thread_rng();
+        let mut elements: Vec<E> = random_point_vec(num_els, &mut rng);
+        let mut buf = vec![0; buffer_size::<E>(compression) * num_els];
+        buf.write_batch(&elements, compression).unwrap();
+        let deserialized: Vec<E> = buf.read_batch(compression, CheckForCorrectness::Full).unwrap();
+        assert_eq!(elements, deserialized);
+
+        // replace an element with a point of the curve outside the prime order subgroup
+        elements[num_els / 2] = loop {
+            let bytes = (0..E::SERIALIZED_SIZE).map(|_| rng.gen()).collect::<Vec<u8>>();
+            if let Some(p) = E::from_random_bytes(&bytes) {
+                if !p
+                    .mul(<<E::ScalarField as PrimeField>::Params as FpParameters>::MODULUS)
+                    .is_zero()
+                {
+                    break p;
+                }
+            }
+        };
+        buf.write_batch(&elements, compression).unwrap();
+        assert!(buf.read_batch::<E>(compression, CheckForCorrectness::Full).is_err());
+        assert!(buf
+            .read_batch::<E>(compression, CheckForCorrectness::OnlyInGroup)
+            .is_err());
+        let mut prealloc = vec![E::zero(); num_els];
+        assert!(buf
+            .read_batch_preallocated(&mut prealloc, compression, CheckForCorrectness::Full)
+            .is_err());
+        // the caller's subgroup check mode is used
+        for mode in &[SubgroupCheckMode::Direct, SubgroupCheckMode::Batched] {
+            assert!(buf
+                .read_batch_with_subgroup_check_mode::<E>(compression, CheckForCorrectness::Full, *mode)
+                .is_err());
+        }
+        let deserialized: Vec<E> = buf
+            .read_batch_with_subgroup_check_mode(compression, CheckForCorrectness::Full, SubgroupCheckMode::No)
+            .unwrap();
+        assert_eq!(elements, deserialized);
+        buf.read_batch_preallocated_with_subgroup_check_mode(
+            &mut prealloc,
+            compression,
+            CheckForCorrectness::Full,
+            SubgroupCheckMode::No,
+        )
+        .unwrap();
+        assert_eq!(elements, prealloc);
+        let deserialized: Vec<E> = buf.read_batch(compression, CheckForCorrectness::OnlyNonZero).unwrap();
+        assert_eq!(elements, deserialized);
+    }
 }
@@ -1,6 +1,9 @@
-use crate::{buffer_size, CheckForCorrectness, Error, Result, UseCompression};
+use crate::{
+    buffer_size, check_subgroup, curves::decompress_batch, CheckForCorrectness, Error, Result, SubgroupCheckMode,
+    UseCompression,
+};
 
-use algebra::AffineCurve;
+use algebra::{cfg_iter, AffineCurve};
 use fft::cfg_chunks;
 
 #[cfg(feature = "parallel")]
@@ -43,7 +46,9 @@ pub trait BatchDeserializer {
         &self,
         compression: UseCompression,
         check_correctness: CheckForCorrectness,
-    ) -> Result<Vec<G>>;
+    ) -> Result<Vec<G>> {
+        self.read_batch_with_subgroup_check_mode(compression, check_correctness, SubgroupCheckMode::Auto)
+    }
 
     /// Reads multiple elements from the buffer to a preallocated array of Group elements
     fn read_batch_preallocated<G: AffineCurve>(
@@ -51,6 +56,33 @@ pub trait BatchDeserializer {
         elements: &mut [G],
         compression: UseCompression,
         check_correctness: CheckForCorrectness,
+    ) -> Result<()> {
+        self.read_batch_preallocated_with_subgroup_check_mode(
+            elements,
+            compression,
+            check_correctness,
+            SubgroupCheckMode::Auto,
+        )
+    }
+
+    /// Reads multiple elements from the buffer, checking that they are in the prime order
+    /// subgroup with `subgroup_check_mode` if `check_correctness` requires it
+    fn read_batch_with_subgroup_check_mode<G: AffineCurve>(
+        &self,
+        compression: UseCompression,
+        check_correctness: CheckForCorrectness,
+        subgroup_check_mode: SubgroupCheckMode,
+    ) -> Result<Vec<G>>;
+
+    /// Reads multiple elements from the buffer to a preallocated array of Group elements,
+    /// checking that they are in the prime order subgroup with `subgroup_check_mode`
+    /// if `check_correctness` requires it
+    fn read_batch_preallocated_with_subgroup_check_mode<G: AffineCurve>(
+        &self,
+        elements: &mut [G],
+        compression: UseCompression,
+        check_correctness: CheckForCorrectness,
+        subgroup_check_mode: SubgroupCheckMode,
     ) -> Result<()>;
 }
 
@@ -115,28 +147,87 @@ impl Deserializer for [u8] {
 // We implement this specifically for slices so that we can take advantage
 // of parallel iterators
 impl BatchDeserializer for [u8] {
-    fn read_batch<G: AffineCurve>(
+    fn read_batch_with_subgroup_check_mode<G: AffineCurve>(
         &self,
         compression: UseCompression,
         check_correctness: CheckForCorrectness,
+        subgroup_check_mode: SubgroupCheckMode,
     ) -> Result<Vec<G>> {
         let size = buffer_size::<G>(compression);
-        cfg_chunks!(&*self, size)
-            .map(|mut buf| buf.read_element(compression, check_correctness))
-            .collect::<Result<Vec<_>>>()
+        let (element_check, batch_subgroup_check) = batch_checks(check_correctness);
+        let elements = match decompress(self, compression) {
+            Some(elements) => {
+                let elements = elements?;
+                check_nonzero(&elements, element_check)?;
+                elements
+            }
+            None => cfg_chunks!(&*self, size)
+                .map(|mut buf| buf.read_element(compression, element_check))
+                .collect::<Result<Vec<_>>>()?,
+        };
+        if batch_subgroup_check {
+            check_subgroup(&elements, subgroup_check_mode)?;
+        }
+        Ok(elements)
     }
 
-    fn read_batch_preallocated<G: AffineCurve>(
+    fn read_batch_preallocated_with_subgroup_check_mode<G: AffineCurve>(
         &self,
         elements: &mut [G],
         compression: UseCompression,
         check_correctness: CheckForCorrectness,
+        subgroup_check_mode: SubgroupCheckMode,
     ) -> Result<()> {
         let size = buffer_size::<G>(compression);
-        cfg_chunks!(&*self, size)
-            .zip(elements)
-            .map(|(mut buf, el)| buf.read_element_preallocated(el, compression, check_correctness))
-            .collect::<Result<Vec<_>>>()?;
+        // only the elements which fit in the buffer are read
+        let num_read = std::cmp::min(elements.len(), self.len() / size);
+        let elements = &mut elements[..num_read];
+        let (element_check, batch_subgroup_check) = batch_checks(check_correctness);
+        match decompress(&self[..num_read * size], compression) {
+            Some(read) => {
+                elements.copy_from_slice(&read?);
+                check_nonzero(elements, element_check)?;
+            }
+            None => {
+                cfg_chunks!(&*self, size)
+                    .zip(&mut *elements)
+                    .map(|(mut buf, el)| buf.read_element_preallocated(el, compression, element_check))
+                    .collect::<Result<Vec<_>>>()?;
+            }
+        }
+        if batch_subgroup_check {
+            check_subgroup(elements, subgroup_check_mode)?;
+        }
         Ok(())
     }
 }
+
+/// Decompresses the buffer in one batch if it holds compressed points of a supported curve
+fn decompress<G: AffineCurve>(buffer: &[u8], compression: UseCompression) -> Option<Result<Vec<G>>> {
+    match compression {
+        UseCompression::Yes => decompress_batch(buffer),
+        UseCompression::No => None,
+    }
+}
+
+/// Checks that the points decompressed in a batch are nonzero if `check_correctness` requires it
+fn check_nonzero<G: AffineCurve>(elements: &[G], check_correctness: CheckForCorrectness) -> Result<()> {
+    if check_correctness == CheckForCorrectness::OnlyNonZero && cfg_iter!(elements).any(|el| el.is_zero()) {
+        return Err(Error::PointAtInfinity);
+    }
+    Ok(())
+}
+
+/// Points are read without the subgroup check, which multiplies each of them by the scalar
+/// field modulus, and are then checked together by `check_subgroup` with the caller's mode.
+/// Compressed points of the supported curves are decompressed together by `decompress_batch`,
+/// which shares the inversions of their square roots across the batch.
+/// Returns the check to perform on each element, and whether the batch must be checked
+/// to be in the prime order subgroup after it is read.
+fn batch_checks(check_correctness: CheckForCorrectness) -> (CheckForCorrectness, bool) {
+    match check_correctness {
+        CheckForCorrectness::Full => (CheckForCorrectness::OnlyNonZero, true),
+        CheckForCorrectness::OnlyInGroup => (CheckForCorrectness::No, true),
+        _ => (check_correctness, false),
+    }
+}
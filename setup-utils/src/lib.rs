@@ -9,7 +9,12 @@ pub use errors::{Error, InvariantKind, Phase2Error, VerificationError};
 pub type Result<T> = std::result::Result<T, Error>;
 
 mod groth16_utils;
-pub use groth16_utils::Groth16Params;
+pub use groth16_utils::{
+    domain_size, domain_with_size, evaluation_domain, Groth16Params, Groth16ParamsView, LazyPoints, PowersOfTauBuffers,
+    UseMixedRadix,
+};
+
+mod curves;
 
 mod elements;
 pub use elements::{
@@ -17,6 +22,8 @@ pub use elements::{
     SubgroupCheckMode, UseCompression,
 };
 
+mod endomorphism;
+
 mod helpers;
 pub use helpers::*;
 